@@ -1 +1 @@
-casper-client put-deploy -n http://95.216.44.9:7777 --chain-name casper-test --payment-amount 38000000000 -k keys/acc2.pem --session-path deploy/session.wasm --session-arg "cnt:u64='1'" --session-arg "approved_id:u64='1'" --session-arg "amount:u512='30000000000'" --session-arg "contract_hash:key='hash-!DeployedContractHashHere!'" --session-arg "current_price_timestamp:string='!HowManyMotesIs1USD?!,!CurrentUnixEpochInMs!'" --session-arg "signature:string='!hexSignatureOfPreviousInput!'"
\ No newline at end of file
+casper-client put-deploy -n http://95.216.44.9:7777 --chain-name casper-test --payment-amount 38000000000 -k keys/acc2.pem --session-path deploy/session.wasm --session-arg "cnt:u64='1'" --session-arg "approved_id:u64='1'" --session-arg "amount:u512='30000000000'" --session-arg "contract_hash:key='hash-!DeployedContractHashHere!'" --session-arg "quote:string='!HexOfBytesreprEncodedPriceQuote!'" --session-args-complex '!PathToTomlWithSignersAndSignaturesOfQuoteBytes!'
\ No newline at end of file
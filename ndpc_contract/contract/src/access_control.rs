@@ -0,0 +1,128 @@
+use casper_contract::{
+    contract_api::{
+        runtime::{get_key, revert},
+        storage,
+    },
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::Key;
+
+use crate::{
+    constants::{
+        NAMED_KEY_DICT_OPERATORS, NAMED_KEY_OWNER, NAMED_KEY_PENDING_OWNER, RUNTIME_ARG_ACCOUNT,
+        RUNTIME_ARG_NEW_OWNER,
+    },
+    event::{emit, DropLinkedEvent},
+    ndpc_types::AsStrized,
+    ndpc_utils::{get_immediate_caller, get_named_key_by_name, get_party_arg},
+    Error,
+};
+
+/// A getter function, which returns the owner of the contract (the account which installed it, or the last account or contract package which accepted the ownership)
+pub fn get_owner() -> Key {
+    let owner_uref = get_key(NAMED_KEY_OWNER)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .into_uref()
+        .unwrap_or_revert_with(Error::KeyNotUref);
+    storage::read(owner_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// Returns true if the account (or contract package) is granted the operator role
+pub fn is_operator(account: Key) -> bool {
+    let operators_dict = get_named_key_by_name(NAMED_KEY_DICT_OPERATORS);
+    storage::dictionary_get::<bool>(operators_dict, account.as_string().as_str())
+        .unwrap_or_revert()
+        .unwrap_or_default()
+}
+
+/// Reverts with `AccessDenied` if the immediate caller is not the owner of the contract
+pub fn only_owner() {
+    if get_owner() != get_immediate_caller() {
+        revert(Error::AccessDenied);
+    }
+}
+
+/// Reverts with `AccessDenied` if the immediate caller is neither the owner, nor an operator of the contract
+pub fn only_operator() {
+    let caller = get_immediate_caller();
+    if get_owner() != caller && !is_operator(caller) {
+        revert(Error::AccessDenied);
+    }
+}
+
+/// transfer_ownership entrypoint of the contract
+/// 
+/// Gets `new_owner` (an account or a contract package) from the runtime args and stores it as the pending owner, the ownership is only transferred when the new owner calls `accept_ownership`.
+/// Calling it again replaces the pending owner. It would revert if the caller is not the owner
+/// # Emits
+/// `DropLinkedEvent::OwnershipTransferStarted`
+#[no_mangle]
+pub extern "C" fn transfer_ownership() {
+    only_owner();
+    let new_owner = get_party_arg(RUNTIME_ARG_NEW_OWNER);
+    storage::write(get_named_key_by_name(NAMED_KEY_PENDING_OWNER), Some(new_owner));
+    emit(DropLinkedEvent::OwnershipTransferStarted {
+        previous_owner: get_immediate_caller(),
+        new_owner,
+    });
+}
+
+/// accept_ownership entrypoint of the contract
+/// 
+/// Should be called by the pending owner, to become the owner of the contract. It would revert with `NotPendingOwner` for any other caller
+/// # Emits
+/// `DropLinkedEvent::OwnershipTransferred`
+#[no_mangle]
+pub extern "C" fn accept_ownership() {
+    let pending_owner_uref = get_named_key_by_name(NAMED_KEY_PENDING_OWNER);
+    let pending_owner: Option<Key> = storage::read(pending_owner_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    let caller = get_immediate_caller();
+    if pending_owner != Some(caller) {
+        revert(Error::NotPendingOwner);
+    }
+    let previous_owner = get_owner();
+    storage::write(get_named_key_by_name(NAMED_KEY_OWNER), caller);
+    storage::write(pending_owner_uref, Option::<Key>::None);
+    emit(DropLinkedEvent::OwnershipTransferred {
+        previous_owner,
+        new_owner: caller,
+    });
+}
+
+/// grant_operator entrypoint of the contract
+/// 
+/// Gets `account` (an account or a contract package) from the runtime args and grants it the operator role. It would revert if the caller is not the owner
+/// # Emits
+/// `DropLinkedEvent::OperatorGranted`
+#[no_mangle]
+pub extern "C" fn grant_operator() {
+    only_owner();
+    let account = get_party_arg(RUNTIME_ARG_ACCOUNT);
+    storage::dictionary_put(
+        get_named_key_by_name(NAMED_KEY_DICT_OPERATORS),
+        account.as_string().as_str(),
+        true,
+    );
+    emit(DropLinkedEvent::OperatorGranted { account });
+}
+
+/// revoke_operator entrypoint of the contract
+/// 
+/// Gets `account` (an account or a contract package) from the runtime args and revokes its operator role. It would revert if the caller is not the owner
+/// # Emits
+/// `DropLinkedEvent::OperatorRevoked`
+#[no_mangle]
+pub extern "C" fn revoke_operator() {
+    only_owner();
+    let account = get_party_arg(RUNTIME_ARG_ACCOUNT);
+    storage::dictionary_put(
+        get_named_key_by_name(NAMED_KEY_DICT_OPERATORS),
+        account.as_string().as_str(),
+        false,
+    );
+    emit(DropLinkedEvent::OperatorRevoked { account });
+}
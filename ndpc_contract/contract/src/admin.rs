@@ -0,0 +1,310 @@
+use alloc::string::ToString;
+use casper_contract::{
+    contract_api::{
+        runtime::{self, get_blocktime},
+        storage,
+    },
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{Key, PublicKey};
+
+use crate::{
+    access_control::{only_operator, only_owner},
+    constants::{
+        MAX_FEE, MAX_ORACLE_SIGNERS, MAX_QUOTE_FRESHNESS, MAX_ROTATION_GRACE_PERIOD,
+        MIN_QUOTE_FRESHNESS, NAMED_KEY_ORACLE_SIGNERS, NAMED_KEY_ORACLE_THRESHOLD,
+        NAMED_KEY_PAUSED, NAMED_KEY_PENDING_FEE, NAMED_KEY_QUOTE_FRESHNESS,
+        NAMED_KEY_RETIRED_ORACLE_SIGNERS, NAMED_KEY_TREASURY, PAUSE_ALL, RUNTIME_ARG_EFFECTIVE_AT,
+        RUNTIME_ARG_GRACE_PERIOD, RUNTIME_ARG_NEW_PUBLIC_KEY, RUNTIME_ARG_PAUSED,
+        RUNTIME_ARG_PUBLIC_KEY, RUNTIME_ARG_QUOTE_FRESHNESS, RUNTIME_ARG_THRESHOLD,
+        RUNTIME_ARG_TREASURY, RUNTIME_FEE,
+    },
+    event::{emit, DropLinkedEvent},
+    governance::without_governance,
+    ndpc_utils::{
+        self, get_fee, get_oracle_signers, get_oracle_threshold, get_paused, get_quote_freshness,
+        get_retired_oracle_signers, get_treasury, is_valid_treasury,
+    },
+    Error,
+};
+
+/// set_quote_freshness entrypoint of the contract
+/// 
+/// Gets `quote_freshness` (in ms) from the runtime args and stores it as the time window that a signed price quote is accepted for by `buy`.
+/// It would revert if the caller is not the owner or an operator of the contract, or if the value is out of `MIN_QUOTE_FRESHNESS..=MAX_QUOTE_FRESHNESS`
+/// # Emits
+/// `DropLinkedEvent::ConfigChanged`
+#[no_mangle]
+pub extern "C" fn set_quote_freshness() {
+    only_operator();
+    let quote_freshness: u64 = runtime::get_named_arg(RUNTIME_ARG_QUOTE_FRESHNESS);
+    if !(MIN_QUOTE_FRESHNESS..=MAX_QUOTE_FRESHNESS).contains(&quote_freshness) {
+        runtime::revert(Error::InvalidQuoteFreshness);
+    }
+    let old_quote_freshness = get_quote_freshness();
+    storage::write(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_QUOTE_FRESHNESS),
+        quote_freshness,
+    );
+    emit(DropLinkedEvent::ConfigChanged {
+        name: NAMED_KEY_QUOTE_FRESHNESS.to_string(),
+        old_value: old_quote_freshness.to_string(),
+        new_value: quote_freshness.to_string(),
+    });
+}
+
+/// add_oracle_signer entrypoint of the contract
+/// 
+/// Gets `public_key` from the runtime args and adds it to the oracle signers, which sign the price quotes for `buy`.
+/// It would revert if the caller is not the owner, if governance is configured (it should be proposed then), if the key is already a signer, or if there are already `MAX_ORACLE_SIGNERS` signers
+/// # Emits
+/// `DropLinkedEvent::OracleSignerAdded`
+#[no_mangle]
+pub extern "C" fn add_oracle_signer() {
+    only_owner();
+    without_governance();
+    apply_add_oracle_signer(runtime::get_named_arg(RUNTIME_ARG_PUBLIC_KEY));
+}
+
+/// Adds `public_key` to the oracle signers, used by the add_oracle_signer entrypoint and by the executed governance proposals
+pub(crate) fn apply_add_oracle_signer(public_key: PublicKey) {
+    ndpc_utils::ensure_supported_signer(&public_key);
+    let mut oracle_signers = get_oracle_signers();
+    if oracle_signers.contains(&public_key) {
+        runtime::revert(Error::OracleSignerExists);
+    }
+    if oracle_signers.len() >= MAX_ORACLE_SIGNERS {
+        runtime::revert(Error::TooManyOracleSigners);
+    }
+    oracle_signers.push(public_key.clone());
+    storage::write(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_ORACLE_SIGNERS),
+        oracle_signers,
+    );
+    emit(DropLinkedEvent::OracleSignerAdded { public_key });
+}
+
+/// remove_oracle_signer entrypoint of the contract
+/// 
+/// Gets `public_key` from the runtime args and removes it from the oracle signers.
+/// It would revert if the caller is not the owner, if governance is configured, if the key is not a signer, or if the remaining signers would be less than the `oracle_threshold`
+/// # Emits
+/// `DropLinkedEvent::OracleSignerRemoved`
+#[no_mangle]
+pub extern "C" fn remove_oracle_signer() {
+    only_owner();
+    without_governance();
+    apply_remove_oracle_signer(runtime::get_named_arg(RUNTIME_ARG_PUBLIC_KEY));
+}
+
+/// Removes `public_key` from the oracle signers, used by the remove_oracle_signer entrypoint and by the executed governance proposals
+pub(crate) fn apply_remove_oracle_signer(public_key: PublicKey) {
+    let mut oracle_signers = get_oracle_signers();
+    if !oracle_signers.contains(&public_key) {
+        runtime::revert(Error::UnknownOracleSigner);
+    }
+    oracle_signers.retain(|signer| *signer != public_key);
+    if oracle_signers.len() < get_oracle_threshold() as usize {
+        runtime::revert(Error::InvalidOracleThreshold);
+    }
+    storage::write(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_ORACLE_SIGNERS),
+        oracle_signers,
+    );
+    emit(DropLinkedEvent::OracleSignerRemoved { public_key });
+}
+
+/// set_oracle_threshold entrypoint of the contract
+/// 
+/// Gets `threshold` from the runtime args and stores it as the number of distinct oracle signers that should sign a price quote.
+/// It would revert if the caller is not the owner, if governance is configured, or if the threshold is 0 or more than the number of oracle signers
+/// # Emits
+/// `DropLinkedEvent::ConfigChanged`
+#[no_mangle]
+pub extern "C" fn set_oracle_threshold() {
+    only_owner();
+    without_governance();
+    apply_set_oracle_threshold(runtime::get_named_arg(RUNTIME_ARG_THRESHOLD));
+}
+
+/// Stores `threshold` as the oracle threshold, used by the set_oracle_threshold entrypoint and by the executed governance proposals
+pub(crate) fn apply_set_oracle_threshold(threshold: u8) {
+    if threshold == 0 || threshold as usize > get_oracle_signers().len() {
+        runtime::revert(Error::InvalidOracleThreshold);
+    }
+    let old_threshold = get_oracle_threshold();
+    storage::write(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_ORACLE_THRESHOLD),
+        threshold,
+    );
+    emit(DropLinkedEvent::ConfigChanged {
+        name: NAMED_KEY_ORACLE_THRESHOLD.to_string(),
+        old_value: old_threshold.to_string(),
+        new_value: threshold.to_string(),
+    });
+}
+
+/// set_treasury entrypoint of the contract
+/// 
+/// Gets `treasury` from the runtime args, and stores it as the account (or purse) which receives droplinked's fee on `buy` and `direct_pay`.
+/// It would revert if the caller is not the owner, if governance is configured, or if the treasury is not an account hash or a purse URef with add access
+/// # Emits
+/// `DropLinkedEvent::ConfigChanged`
+#[no_mangle]
+pub extern "C" fn set_treasury() {
+    only_owner();
+    without_governance();
+    apply_set_treasury(runtime::get_named_arg(RUNTIME_ARG_TREASURY));
+}
+
+/// Stores `treasury` as the treasury, used by the set_treasury entrypoint and by the executed governance proposals
+pub(crate) fn apply_set_treasury(treasury: Key) {
+    if !is_valid_treasury(&treasury) {
+        runtime::revert(Error::InvalidTreasury);
+    }
+    let old_treasury = get_treasury();
+    storage::write(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_TREASURY),
+        treasury,
+    );
+    emit(DropLinkedEvent::ConfigChanged {
+        name: NAMED_KEY_TREASURY.to_string(),
+        old_value: old_treasury.to_formatted_string(),
+        new_value: treasury.to_formatted_string(),
+    });
+}
+
+/// set_fee entrypoint of the contract
+/// 
+/// Gets `fee` (in basis points) and `effective_at` (an optional blocktime in ms) from the runtime args. If `effective_at` is `None` the fee is changed right away,
+/// otherwise the change is scheduled and is applied by the first call that needs the fee after `effective_at`, so producers and publishers get a notice.
+/// A new call replaces the scheduled change. It would revert if the caller is not the owner, if governance is configured, if the fee is more than `MAX_FEE`,
+/// or if `effective_at` is not in the future
+/// # Emits
+/// `DropLinkedEvent::FeeChanged`
+#[no_mangle]
+pub extern "C" fn set_fee() {
+    only_owner();
+    without_governance();
+    apply_set_fee(
+        runtime::get_named_arg(RUNTIME_FEE),
+        runtime::get_named_arg(RUNTIME_ARG_EFFECTIVE_AT),
+    );
+}
+
+/// Changes (or schedules) the fee, used by the set_fee entrypoint and by the executed governance proposals
+pub(crate) fn apply_set_fee(fee: u64, effective_at: Option<u64>) {
+    if fee > MAX_FEE {
+        runtime::revert(Error::InvalidFee);
+    }
+    let old_fee = get_fee();
+    let now = u64::from(get_blocktime());
+    let pending_fee_uref = ndpc_utils::get_named_key_by_name(NAMED_KEY_PENDING_FEE);
+    let effective_at = match effective_at {
+        Some(effective_at) => {
+            if effective_at <= now {
+                runtime::revert(Error::InvalidFeeSchedule);
+            }
+            storage::write(pending_fee_uref, Some((fee, effective_at)));
+            effective_at
+        }
+        None => {
+            storage::write(ndpc_utils::get_named_key_by_name(RUNTIME_FEE), fee);
+            storage::write(pending_fee_uref, Option::<(u64, u64)>::None);
+            now
+        }
+    };
+    emit(DropLinkedEvent::FeeChanged {
+        old_fee,
+        new_fee: fee,
+        effective_at,
+    });
+}
+
+/// rotate_oracle_signer entrypoint of the contract
+/// 
+/// Gets `public_key`, `new_public_key` and `grace_period` (in ms) from the runtime args, and replaces the oracle signer `public_key` with `new_public_key`.
+/// Quotes signed by the old key are still accepted (in place of the new key) until `grace_period` passes, so purchases that are in flight could settle;
+/// a `grace_period` of 0 retires the old key right away. It would revert if the caller is not the owner, if governance is configured, if `public_key` is not a signer,
+/// if `new_public_key` is already a signer, or if `grace_period` is more than `MAX_ROTATION_GRACE_PERIOD`
+/// # Emits
+/// `DropLinkedEvent::OracleSignerRotated`
+#[no_mangle]
+pub extern "C" fn rotate_oracle_signer() {
+    only_owner();
+    without_governance();
+    apply_rotate_oracle_signer(
+        runtime::get_named_arg(RUNTIME_ARG_PUBLIC_KEY),
+        runtime::get_named_arg(RUNTIME_ARG_NEW_PUBLIC_KEY),
+        runtime::get_named_arg(RUNTIME_ARG_GRACE_PERIOD),
+    );
+}
+
+/// Replaces the oracle signer `public_key` with `new_public_key`, used by the rotate_oracle_signer entrypoint and by the executed governance proposals
+pub(crate) fn apply_rotate_oracle_signer(
+    public_key: PublicKey,
+    new_public_key: PublicKey,
+    grace_period: u64,
+) {
+    if grace_period > MAX_ROTATION_GRACE_PERIOD {
+        runtime::revert(Error::InvalidGracePeriod);
+    }
+    ndpc_utils::ensure_supported_signer(&new_public_key);
+    let mut oracle_signers = get_oracle_signers();
+    if oracle_signers.contains(&new_public_key) {
+        runtime::revert(Error::OracleSignerExists);
+    }
+    let signer_index = oracle_signers
+        .iter()
+        .position(|signer| *signer == public_key)
+        .unwrap_or_revert_with(Error::UnknownOracleSigner);
+    oracle_signers[signer_index] = new_public_key.clone();
+    storage::write(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_ORACLE_SIGNERS),
+        oracle_signers,
+    );
+
+    let now = u64::from(get_blocktime());
+    let grace_until = now + grace_period;
+    let mut retired_oracle_signers = get_retired_oracle_signers();
+    retired_oracle_signers
+        .retain(|(retired, _, valid_until)| *valid_until >= now && *retired != new_public_key);
+    if grace_period > 0 {
+        retired_oracle_signers.push((public_key.clone(), new_public_key.clone(), grace_until));
+    }
+    storage::write(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_RETIRED_ORACLE_SIGNERS),
+        retired_oracle_signers,
+    );
+    emit(DropLinkedEvent::OracleSignerRotated {
+        old_public_key: public_key,
+        new_public_key,
+        grace_until,
+    });
+}
+
+/// set_paused entrypoint of the contract
+/// 
+/// Gets `paused` (a bitmask of `PAUSE_BUY`, `PAUSE_DIRECT_PAY`, `PAUSE_MINT`, `PAUSE_APPROVE` and `PAUSE_PUBLISH_REQUEST`, or `PAUSE_ALL`) from the runtime args and replaces the paused flags with it.
+/// Paused entrypoints revert with `ContractPaused`, while `cancel_request` and `disapprove` stay open. Operators could only pause more entrypoints (to act fast in an emergency),
+/// but unpausing any of them needs the owner. It would revert if `paused` has a flag that is not known
+/// # Emits
+/// `DropLinkedEvent::PausedChanged`
+#[no_mangle]
+pub extern "C" fn set_paused() {
+    let paused: u8 = runtime::get_named_arg(RUNTIME_ARG_PAUSED);
+    if paused & !PAUSE_ALL != 0 {
+        runtime::revert(Error::InvalidPauseFlags);
+    }
+    let old_paused = get_paused();
+    if old_paused & !paused != 0 {
+        only_owner();
+    } else {
+        only_operator();
+    }
+    storage::write(ndpc_utils::get_named_key_by_name(NAMED_KEY_PAUSED), paused);
+    emit(DropLinkedEvent::PausedChanged {
+        old_paused,
+        new_paused: paused,
+    });
+}
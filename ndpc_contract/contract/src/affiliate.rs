@@ -1,50 +1,26 @@
 use alloc::string::{String, ToString};
 use casper_contract::{
-    contract_api::{
-        runtime::{self, get_caller},
-        storage,
-    },
+    contract_api::{runtime, storage},
     unwrap_or_revert::UnwrapOrRevert,
 };
-use casper_types::{account::AccountHash, ApiError, CLValue, Key, URef};
+use casper_types::{ApiError, CLValue, Key};
 
 use crate::{
     constants::{
-        NAMED_KEY_APPROVED_CNT, NAMED_KEY_DICT_OWNERS_NAME, RUNTIME_ARG_HOLDER_ID,
-        RUNTIME_ARG_PRODUCER_ACCOUNT_HASH,
-    },
-    constants::{
-        NAMED_KEY_DICT_APPROVED_NAME, NAMED_KEY_DICT_HOLDERS_NAME,
-        NAMED_KEY_DICT_PRODAPPROVED_NAME, NAMED_KEY_DICT_PROD_REQS,
-        NAMED_KEY_DICT_PUBAPPROVED_NAME, NAMED_KEY_DICT_PUB_REQS, NAMED_KEY_DICT_REQ_OBJ,
-        NAMED_KEY_REQ_CNT, RUNTIME_ARG_AMOUNT, RUNTIME_ARG_APPROVED_ID, RUNTIME_ARG_REQUEST_ID,
+        PAUSE_APPROVE, PAUSE_PUBLISH_REQUEST, RUNTIME_ARG_AMOUNT, RUNTIME_ARG_APPROVED_ID,
+        RUNTIME_ARG_HOLDER_ID, RUNTIME_ARG_PRODUCER_ACCOUNT_HASH, RUNTIME_ARG_REQUEST_ID,
         RUNTIME_ARG_SPENDER,
     },
     event::{emit, DropLinkedEvent},
-    ndpc_types::{self, ApprovedNFT, AsStrized, NFTHolder, PublishRequest, U64list},
-    ndpc_utils::{self, get_holder_by_id, get_holder_ids, get_request_by_id},
+    ndpc_types::{self, ApprovedNFT, AsStrized},
+    ndpc_utils::{get_immediate_caller, get_party_arg, when_not_paused, when_storage_migrated},
+    storage_context::StorageContext,
     Error,
 };
-/// Get dicts from contract namedkeys that are needed for the approve function of the contract to run
-/// 
-/// The needed dicts are : `NAMED_KEY_DICT_REQ_OBJ`, `NAMED_KEY_DICT_PROD_REQS`, `NAMED_KEY_DICT_PUB_REQS`, `NAMED_KEY_DICT_OWNERS_NAME`, `NAMED_KEY_DICT_HOLDERS_NAME`, `NAMED_KEY_DICT_PUBAPPROVED_NAME`, `NAMED_KEY_DICT_PRODAPPROVED_NAME`, `NAMED_KEY_APPROVED_CNT`, `NAMED_KEY_DICT_APPROVED_NAME`
-fn get_approve_dicts() -> (URef, URef, URef, URef, URef, URef, URef, URef, URef) {
-    (
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_REQ_OBJ),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PROD_REQS),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PUB_REQS),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_OWNERS_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_HOLDERS_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PUBAPPROVED_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PRODAPPROVED_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_APPROVED_CNT),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_APPROVED_NAME),
-    )
-}
 
 /// Approve Entrypoint of the contract
 /// 
-/// It would get `request_id` from the runtime args, and approve that PublishRequest, it would panic if any account calls it except the owner account of the token
+/// It would get `request_id` from the runtime args, and approve that PublishRequest, it would panic if any account (or contract) calls it except the owner of the token
 /// It would panic if the request with the given request_id does not exist
 /// # Returns
 /// `approved_id`: `u64`
@@ -52,41 +28,32 @@ fn get_approve_dicts() -> (URef, URef, URef, URef, URef, URef, URef, URef, URef)
 /// `DropLinkedEvent::ApprovedPublish`
 #[no_mangle]
 pub extern "C" fn approve() {
-    // Get dicts
-    let (
-        requests_dict,
-        prod_reqs_dict,
-        pub_reqs_dict,
-        owners_dict,
-        holders_dict,
-        publishers_approved_dict,
-        producers_approved_dict,
-        approved_cnt_uref,
-        approved_dict,
-    ) = get_approve_dicts();
+    when_not_paused(PAUSE_APPROVE);
+    when_storage_migrated();
+    // the dicts are resolved on their first use
+    let mut ctx = StorageContext::new();
 
     let request_id: u64 = runtime::get_named_arg(RUNTIME_ARG_REQUEST_ID);
-    let request_obj = get_request_by_id(requests_dict, request_id);
+    let request_obj = ctx.request(request_id);
 
     let amount: u64 = request_obj.amount;
     let holder_id: u64 = request_obj.holder_id;
 
-    let spender_acc: AccountHash = request_obj.publisher;
+    let spender_acc: Key = request_obj.publisher;
     let spender: String = request_obj.publisher.as_string();
     
-    let caller_account = runtime::get_caller();
+    let caller_account = get_immediate_caller();
     let caller: String = caller_account.as_string();
 
-    let caller_holder_ids =
-        get_holder_ids(owners_dict, &caller).unwrap_or_revert_with(Error::HolderDoesentExist);
-
+    let caller_holder_ids = ctx.owner_holders(&caller);
+    if caller_holder_ids.is_empty() {
+        runtime::revert(ApiError::from(Error::HolderDoesentExist));
+    }
     if !caller_holder_ids.contains(holder_id) {
         runtime::revert(ApiError::from(Error::NotOwnerOfHolderId));
     }
 
-    let holder: NFTHolder = storage::dictionary_get(holders_dict, holder_id.to_string().as_str())
-        .unwrap_or_revert()
-        .unwrap_or_revert();
+    let token_id = ctx.holder(holder_id).token_id;
 
     //create the approved holder
     let approved_holder = ApprovedNFT::new(
@@ -94,12 +61,11 @@ pub extern "C" fn approve() {
         amount,
         caller_account,
         spender_acc,
-        holder.token_id,
+        token_id,
     );
     
-    storage::dictionary_put(holders_dict, holder_id.to_string().as_str(), holder);
-    
     //get approved_cnt, increment it and save it
+    let approved_cnt_uref = ctx.approved_cnt();
     let approved_cnt: u64 = storage::read(approved_cnt_uref)
         .unwrap_or_revert()
         .unwrap_or_revert();
@@ -109,55 +75,18 @@ pub extern "C" fn approve() {
     let approved_id = new_approved_cnt;
     //save the approved holder
     storage::dictionary_put(
-        approved_dict,
+        ctx.approved_dict(),
         approved_id.to_string().as_str(),
         approved_holder,
     );
 
-    //add the approved holder to the publishers approved dictionary
-    let publisher_approved_holders_opt =
-        storage::dictionary_get(publishers_approved_dict, &spender).unwrap_or_revert();
-    if publisher_approved_holders_opt.is_none() {
-        let mut new_list = ndpc_types::U64list::new();
-        new_list.list.insert(approved_id);
-        storage::dictionary_put(publishers_approved_dict, &spender, new_list);
-    } else {
-        let mut publisher_approved_holders: ndpc_types::U64list =
-            publisher_approved_holders_opt.unwrap_or_revert();
-        publisher_approved_holders.list.insert(approved_id);
-        storage::dictionary_put(
-            publishers_approved_dict,
-            &spender,
-            publisher_approved_holders,
-        );
-    }
-    
-    //add the approved holder to the producers approved dictionary
-    let producer_approved_holders_opt =
-        storage::dictionary_get(producers_approved_dict, &caller).unwrap_or_revert();
-    if producer_approved_holders_opt.is_none() {
-        let mut new_list = ndpc_types::U64list::new();
-        new_list.list.insert(approved_id);
-        storage::dictionary_put(producers_approved_dict, &caller, new_list);
-    } else {
-        let mut producer_approved_holders: ndpc_types::U64list =
-            producer_approved_holders_opt.unwrap_or_revert();
-        producer_approved_holders.list.insert(approved_id);
-        storage::dictionary_put(producers_approved_dict, &caller, producer_approved_holders);
-    }
+    //add the approved holder to the publishers approved list and to the producers approved list
+    ctx.publisher_approved(&spender).push(approved_id);
+    ctx.producer_approved(&caller).push(approved_id);
 
-    //remove the request from the publishers requests dictionary and the producers requests dictionary
-    let publisher_requests_opt =
-        storage::dictionary_get::<U64list>(pub_reqs_dict, &spender).unwrap_or_revert();
-    let mut publisher_requests: U64list = publisher_requests_opt.unwrap_or_revert();
-    publisher_requests.remove(request_id);
-    storage::dictionary_put(pub_reqs_dict, &spender, publisher_requests);
-
-    let producer_requests_opt =
-        storage::dictionary_get::<U64list>(prod_reqs_dict, &caller).unwrap_or_revert();
-    let mut producer_requests: U64list = producer_requests_opt.unwrap_or_revert();
-    producer_requests.remove(request_id);
-    storage::dictionary_put(prod_reqs_dict, &caller, producer_requests);
+    //remove the request from the publishers requests list and the producers requests list
+    ctx.publisher_requests(&spender).remove(request_id);
+    ctx.producer_requests(&caller).remove(request_id);
 
     //return the approved_id
     let ret = CLValue::from_t(approved_id).unwrap_or_revert();
@@ -177,30 +106,20 @@ pub extern "C" fn approve() {
 /// `DropLinkedEvent::DisapprovedPublish`
 #[no_mangle]
 pub extern "C" fn disapprove() {
+    when_storage_migrated();
     //check if the caller is the owner of the token
     //define the runtime arguments needed for this entrypoint
     let amount: u64 = runtime::get_named_arg(RUNTIME_ARG_AMOUNT);
     let approved_id: u64 = runtime::get_named_arg(RUNTIME_ARG_APPROVED_ID);
-    let spender_key: Key = runtime::get_named_arg(RUNTIME_ARG_SPENDER); //spender is the publisher
-    let spender_acc = spender_key
-        .into_account()
-        .unwrap_or_revert_with(ApiError::from(Error::NotAccountHash));
-    let spender: String = spender_acc.as_string();
-    //define storages we need to work with
-    let approved_dict = ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_APPROVED_NAME);
-    let publishers_approved_dict =
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PUBAPPROVED_NAME);
-    let producers_approved_dict =
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PRODAPPROVED_NAME);
-    let holders_dict = ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_HOLDERS_NAME);
+    let spender_key: Key = get_party_arg(RUNTIME_ARG_SPENDER); //spender is the publisher
+    let spender: String = spender_key.as_string();
+    //the storages we need to work with are resolved on their first use
+    let mut ctx = StorageContext::new();
 
     //from the approved_id, get the approvednft
-    let mut approved_holder =
-        storage::dictionary_get::<ApprovedNFT>(approved_dict, approved_id.to_string().as_str())
-            .unwrap_or_revert()
-            .unwrap_or_revert_with(ApiError::from(Error::ApprovedHolderDoesentExist));
+    let approved_holder = ctx.approved_nft(approved_id);
     //check if the caller is the owner of the token
-    let caller = runtime::get_caller();
+    let caller = get_immediate_caller();
     if caller != approved_holder.owneraccount {
         //the caller is not the owner of the token
         runtime::revert(ApiError::from(Error::NotOwnerOfHolderId));
@@ -213,76 +132,40 @@ pub extern "C" fn disapprove() {
     }
     //else, approvednft's amount -= amount
     approved_holder.amount -= amount;
+    let remaining_amount = approved_holder.amount;
 
-    if approved_holder.amount == 0 {
+    if remaining_amount == 0 {
         {
-            //remove the approvednft from the u64list of publisher
-            let mut publisher_approved_holders =
-                storage::dictionary_get::<ndpc_types::U64list>(publishers_approved_dict, &spender)
-                    .unwrap_or_revert()
-                    .unwrap_or_revert_with(ApiError::from(Error::PublisherHasNoApprovedHolders));
+            //remove the approvednft from the approved list of publisher
+            let publisher_approved_holders = ctx.publisher_approved(&spender);
+            if publisher_approved_holders.is_empty() {
+                runtime::revert(ApiError::from(Error::PublisherHasNoApprovedHolders));
+            }
             publisher_approved_holders.remove(approved_id);
-            storage::dictionary_put(
-                publishers_approved_dict,
-                &spender,
-                publisher_approved_holders,
-            );
         }
         {
-            //remove the approvednft from the u64list of producer
-            let mut producer_approved_holders = storage::dictionary_get::<ndpc_types::U64list>(
-                producers_approved_dict,
-                caller_string.as_str(),
-            )
-            .unwrap_or_revert()
-            .unwrap_or_revert_with(ApiError::from(Error::ProducerHasNoApprovedHolders));
+            //remove the approvednft from the approved list of producer
+            let producer_approved_holders = ctx.producer_approved(caller_string.as_str());
+            if producer_approved_holders.is_empty() {
+                runtime::revert(ApiError::from(Error::ProducerHasNoApprovedHolders));
+            }
             producer_approved_holders.remove(approved_id);
-            storage::dictionary_put(
-                producers_approved_dict,
-                caller_string.as_str(),
-                producer_approved_holders,
-            );
         }
     }
 
-    let holder_id = approved_holder.holder_id;
-
     //put back approved_holder in the dictionary
-    storage::dictionary_put(
-        approved_dict,
-        approved_id.to_string().as_str(),
-        approved_holder,
-    );
+    ctx.write_approved_nft(approved_id);
 
-    //from the approved holder, get the holder_id and then the nftholder
-    let holder = storage::dictionary_get::<NFTHolder>(holders_dict, holder_id.to_string().as_str())
-        .unwrap_or_revert()
-        .unwrap_or_revert_with(ApiError::from(Error::HolderDoesentExist));
-    //put back holder to the dictionary
-    storage::dictionary_put(holders_dict, holder_id.to_string().as_str(), holder);
-    emit(DropLinkedEvent::DisapprovedPublish { approved_id });
-}
-
-/// Gets the needed dicts from storage, to run the publish_request entrypoint of the contract
-/// 
-/// Needed dicts are : `NAMED_KEY_DICT_HOLDERS_NAME`, `NAMED_KEY_DICT_OWNERS_NAME`, `NAMED_KEY_DICT_REQ_OBJ`, `NAMED_KEY_DICT_PROD_REQS`, `NAMED_KEY_DICT_PUB_REQS`, `NAMED_KEY_REQ_CNT`
-fn get_publish_request_storage() -> (URef, URef, URef, URef, URef, URef) {
-    (
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_HOLDERS_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_OWNERS_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_REQ_OBJ),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PROD_REQS),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PUB_REQS),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_REQ_CNT),
-    )
+    emit(DropLinkedEvent::DisapprovedPublish {
+        approved_id,
+        amount,
+    });
 }
 
 /// Gets the runtime args for the publishRequest in order to run it
-fn get_publish_request_runtime_args() -> (AccountHash, u64, u64) {
+fn get_publish_request_runtime_args() -> (Key, u64, u64) {
     (
-        runtime::get_named_arg::<Key>(RUNTIME_ARG_PRODUCER_ACCOUNT_HASH)
-            .into_account()
-            .unwrap_or_revert(),
+        get_party_arg(RUNTIME_ARG_PRODUCER_ACCOUNT_HASH),
         runtime::get_named_arg::<u64>(RUNTIME_ARG_HOLDER_ID),
         runtime::get_named_arg::<u64>(RUNTIME_ARG_AMOUNT),
     )
@@ -290,26 +173,26 @@ fn get_publish_request_runtime_args() -> (AccountHash, u64, u64) {
 
 /// publish_request entrypoint of the contract
 /// 
-/// Gets `producer_account_hash`, `holder_id` and `amount` from runtime args, and builds a new PublishRequest object, gets a request_id for it, and 
+/// Gets `producer_account_hash` (an account or a contract package), `holder_id` and `amount` from runtime args, and builds a new PublishRequest object, gets a request_id for it, and 
 /// holds it in the request_objects dict and adds the request_id to producer and publisher's request dicts
 #[no_mangle]
 pub extern "C" fn publish_request() {
-    let (holders_dict, owners_dict, requests_dict, prod_reqs_dict, pub_reqs_dict, tokens_cnt_uref) =
-        get_publish_request_storage();
+    when_not_paused(PAUSE_PUBLISH_REQUEST);
+    when_storage_migrated();
+    let mut ctx = StorageContext::new();
     let (producer_account_hash, holder_id, amount) = get_publish_request_runtime_args();
-    let caller = get_caller();
+    let caller = get_immediate_caller();
 
     let producer_string = producer_account_hash.as_string();
-    let holder = get_holder_by_id(holders_dict, holder_id);
-    if holder.amount < amount {
+    if ctx.holder(holder_id).amount < amount {
         runtime::revert(ApiError::from(Error::NotEnoughAmount));
     }
 
     //check if holder_id exists in owners_dict (producer as the key)
-    let prod_list = storage::dictionary_get::<U64list>(owners_dict, producer_string.as_str())
-        .unwrap_or_revert()
-        .unwrap_or_revert_with(ApiError::from(Error::EmptyOwnerShipList));
-
+    let prod_list = ctx.owner_holders(producer_string.as_str());
+    if prod_list.is_empty() {
+        runtime::revert(ApiError::from(Error::EmptyOwnerShipList));
+    }
     if !prod_list.contains(holder_id) {
         runtime::revert(ApiError::from(Error::NotOwnerOfHolderId));
     }
@@ -317,38 +200,23 @@ pub extern "C" fn publish_request() {
     //create publish request
     let publish_request =
         ndpc_types::PublishRequest::new(holder_id, amount, producer_account_hash, caller);
-    let request_cnt = storage::read::<u64>(tokens_cnt_uref)
+    let request_cnt_uref = ctx.request_cnt();
+    let request_cnt = storage::read::<u64>(request_cnt_uref)
         .unwrap_or_revert()
         .unwrap_or_revert_with(ApiError::from(Error::EmptyRequestCnt));
     let request_id = request_cnt + 1;
-    storage::write(tokens_cnt_uref, request_id);
+    storage::write(request_cnt_uref, request_id);
     storage::dictionary_put(
-        requests_dict,
+        ctx.requests_dict(),
         request_id.to_string().as_str(),
         publish_request,
     );
 
     //add request to producer requests
-    let prod_reqs_opt =
-        storage::dictionary_get::<U64list>(prod_reqs_dict, producer_string.as_str())
-            .unwrap_or_revert();
-    let mut prod_reqs = match prod_reqs_opt {
-        Some(reqs) => reqs,
-        None => U64list::new(),
-    };
-    prod_reqs.list.insert(request_id);
-    storage::dictionary_put(prod_reqs_dict, producer_string.as_str(), prod_reqs);
+    ctx.producer_requests(producer_string.as_str()).push(request_id);
 
     //add request to publisher requests
-    let pub_reqs_opt =
-        storage::dictionary_get::<U64list>(pub_reqs_dict, caller.to_string().as_str())
-            .unwrap_or_revert();
-    let mut pub_reqs = match pub_reqs_opt {
-        Some(reqs) => reqs,
-        None => U64list::new(),
-    };
-    pub_reqs.list.insert(request_id);
-    storage::dictionary_put(pub_reqs_dict, caller.to_string().as_str(), pub_reqs);
+    ctx.publisher_requests(caller.as_string().as_str()).push(request_id);
     
     let ret = CLValue::from_t(request_id).unwrap_or_revert();
     emit(DropLinkedEvent::PublishRequest {
@@ -361,57 +229,33 @@ pub extern "C" fn publish_request() {
     runtime::ret(ret);
 }
 
-/// Cancel Request needed URef (dicts) from storage
-fn get_cancel_request_storage() -> (URef, URef, URef) {
-    (
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_REQ_OBJ),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PROD_REQS),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PUB_REQS),
-    )
-}
-
 /// Cancel_request entrypoint of the contract
 /// 
 /// It should be called by the publisher, who sent the publishrequest to the producer,
 /// the publisher should provide the `request_id`, and the request would be cancelled and removed from all dicts of the contract storage
 #[no_mangle]
 pub extern "C" fn cancel_request() {
-    //storages we need to work with
-    let (requests_dict, prod_reqs_dict, pub_reqs_dict) = get_cancel_request_storage();
+    when_storage_migrated();
+    //storages we need to work with are resolved on their first use
+    let mut ctx = StorageContext::new();
 
     let request_id: u64 = runtime::get_named_arg(RUNTIME_ARG_REQUEST_ID);
-    let caller: String = get_caller().as_string();
 
-    let request_obj: PublishRequest = get_request_by_id(requests_dict, request_id);
+    let request_obj = ctx.request(request_id);
+    let (publisher, producer) = (request_obj.publisher, request_obj.producer);
 
     //check if request's publisher is the caller
-    if request_obj.publisher != get_caller() {
+    if publisher != get_immediate_caller() {
         runtime::revert(ApiError::from(Error::AccessDenied));
     }
 
     //remove the request_id from the publisher's requests and from the producer's requests
-    let mut pub_reqs = storage::dictionary_get::<U64list>(
-        pub_reqs_dict,
-        request_obj.publisher.as_string().as_str(),
-    )
-    .unwrap_or_revert()
-    .unwrap_or_revert_with(ApiError::from(Error::EmptyU64List));
-
-    let mut prod_reqs = storage::dictionary_get::<U64list>(
-        prod_reqs_dict,
-        request_obj.producer.as_string().as_str(),
-    )
-    .unwrap_or_revert()
-    .unwrap_or_revert_with(ApiError::from(Error::EmptyU64List));
-
+    let pub_reqs = ctx.publisher_requests(publisher.as_string().as_str());
+    let prod_reqs = ctx.producer_requests(producer.as_string().as_str());
+    if pub_reqs.is_empty() || prod_reqs.is_empty() {
+        runtime::revert(ApiError::from(Error::EmptyU64List));
+    }
     pub_reqs.remove(request_id);
     prod_reqs.remove(request_id);
-
-    storage::dictionary_put(pub_reqs_dict, caller.as_str(), pub_reqs);
-    storage::dictionary_put(
-        prod_reqs_dict,
-        request_obj.producer.as_string().as_str(),
-        prod_reqs,
-    );
     emit(DropLinkedEvent::CancelRequest { request_id });
 }
@@ -0,0 +1,154 @@
+use alloc::{format, string::ToString, vec::Vec};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{CLValue, Key, U256};
+
+use crate::{
+    constants::{
+        MAX_PAGE_SIZE, NAMED_KEY_DICT_BALANCES, NAMED_KEY_DICT_HOLDER_OWNERS,
+        NAMED_KEY_DICT_TOKEN_HOLDERS, RUNTIME_ARG_ID, RUNTIME_ARG_LIMIT, RUNTIME_ARG_OFFSET,
+        RUNTIME_ARG_RECIPIENT, RUNTIME_ARG_TOKEN_ID,
+    },
+    ndpc_types::AsStrized,
+    ndpc_utils::{get_party_arg, when_storage_migrated},
+    storage_context::StorageContext,
+    Error,
+};
+
+/// The key of the balance of the owner in the token, in the `balances` dictionary (hashed, as dictionary keys are limited to 64 bytes)
+fn balance_key(owner: &Key, token_id: u64) -> alloc::string::String {
+    base16::encode_lower(&runtime::blake2b(format!(
+        "{}_{}",
+        owner.as_string(),
+        token_id
+    )))
+}
+
+/// Stores `amount` as the balance of the owner in the token, whose holder is `holder_id`
+///
+/// It is called whenever the amount of a holder changes. The holder is in the `token_holders` list of the token while its amount is not 0, and its owner
+/// is stored in `holder_owners`, so the holders of a token could be listed without scanning the lists of the owners
+pub fn set_balance(
+    ctx: &mut StorageContext,
+    owner: Key,
+    token_id: u64,
+    holder_id: u64,
+    amount: u64,
+) {
+    storage::dictionary_put(
+        ctx.uref(NAMED_KEY_DICT_BALANCES),
+        &balance_key(&owner, token_id),
+        amount,
+    );
+    storage::dictionary_put(
+        ctx.uref(NAMED_KEY_DICT_HOLDER_OWNERS),
+        holder_id.to_string().as_str(),
+        owner,
+    );
+    let token_holders = ctx.list(NAMED_KEY_DICT_TOKEN_HOLDERS, &token_id.to_string());
+    if amount == 0 {
+        token_holders.remove(holder_id);
+    } else {
+        token_holders.push(holder_id);
+    }
+}
+
+/// Converts a token id given as a `U256` (as CEP-85 ids are) to a droplinked token id, it reverts with `InvalidTokenId` if it does not fit in a u64
+pub fn to_token_id(id: U256) -> u64 {
+    if id > U256::from(u64::MAX) {
+        runtime::revert(Error::InvalidTokenId);
+    }
+    id.as_u64()
+}
+
+/// Indexes the holders of the owner which have no `holder_owners` entry yet
+///
+/// Those are the holders that existed before the upgrade to version 8 and are not changed since, so the index is built on the first touch of their owner
+/// instead of by a backfill, as a holder does not store its owner
+pub fn index_owner(ctx: &mut StorageContext, owner: &Key) {
+    let owner_holders = ctx.owner_holders(&owner.as_string());
+    let holder_owners_dict = ctx.uref(NAMED_KEY_DICT_HOLDER_OWNERS);
+    for index in 0..owner_holders.len() {
+        let holder_id = owner_holders
+            .get(index)
+            .unwrap_or_revert_with(Error::HolderDoesentExist);
+        let indexed =
+            storage::dictionary_get::<Key>(holder_owners_dict, holder_id.to_string().as_str())
+                .unwrap_or_revert()
+                .is_some();
+        if !indexed {
+            let holder = ctx.holder(holder_id);
+            let (holder_token_id, amount) = (holder.token_id, holder.amount);
+            set_balance(ctx, *owner, holder_token_id, holder_id, amount);
+        }
+    }
+}
+
+/// Returns the amount of the token that the owner holds, or 0 if the owner has no holder of the token
+///
+/// If the owner has no balance entry for the token, the owner's holders are indexed first (see `index_owner`), so the holders that are not changed
+/// since the upgrade to version 8 are counted, and listed by `holders_of` from then on
+pub fn balance_of_owner(ctx: &mut StorageContext, owner: &Key, token_id: u64) -> u64 {
+    let balances_dict = ctx.uref(NAMED_KEY_DICT_BALANCES);
+    let balance_key = balance_key(owner, token_id);
+    if let Some(balance) =
+        storage::dictionary_get::<u64>(balances_dict, &balance_key).unwrap_or_revert()
+    {
+        return balance;
+    }
+    index_owner(ctx, owner);
+    storage::dictionary_get::<u64>(balances_dict, &balance_key)
+        .unwrap_or_revert()
+        .unwrap_or_default()
+}
+
+/// balance_of entrypoint of the contract
+///
+/// Gets `recipient` (an account or a contract package) and `id`, and returns the amount of the token that the recipient holds.
+/// Its arguments and result are the ones of the CEP-85 `balance_of`, so the id and the balance are `U256`s
+/// # Returns
+/// `balance` : `U256`
+#[no_mangle]
+pub extern "C" fn balance_of() {
+    when_storage_migrated();
+    let recipient = get_party_arg(RUNTIME_ARG_RECIPIENT);
+    let token_id = to_token_id(runtime::get_named_arg(RUNTIME_ARG_ID));
+    let mut ctx = StorageContext::new();
+    let balance = U256::from(balance_of_owner(&mut ctx, &recipient, token_id));
+    runtime::ret(CLValue::from_t(balance).unwrap_or_revert());
+}
+
+/// holders_of entrypoint of the contract
+///
+/// Gets `token_id`, `offset` and `limit` (at most `MAX_PAGE_SIZE`), and returns a page of the holders of the token, as (owner, amount) pairs.
+/// Only the holders whose amount is not 0 are listed, and the holders that are not touched since the upgrade to version 8 are not listed yet (see `balance_of_owner`)
+/// # Returns
+/// `holders` : `Vec<(Key, u64)>`
+#[no_mangle]
+pub extern "C" fn holders_of() {
+    when_storage_migrated();
+    let token_id: u64 = runtime::get_named_arg(RUNTIME_ARG_TOKEN_ID);
+    let offset: u64 = runtime::get_named_arg(RUNTIME_ARG_OFFSET);
+    let limit: u64 = runtime::get_named_arg(RUNTIME_ARG_LIMIT);
+    if limit > MAX_PAGE_SIZE {
+        runtime::revert(Error::PageTooLarge);
+    }
+    let mut ctx = StorageContext::new();
+    let holder_ids = ctx
+        .list(NAMED_KEY_DICT_TOKEN_HOLDERS, &token_id.to_string())
+        .page(offset, limit);
+    let holder_owners_dict = ctx.uref(NAMED_KEY_DICT_HOLDER_OWNERS);
+    let holders: Vec<(Key, u64)> = holder_ids
+        .into_iter()
+        .map(|holder_id| {
+            let owner: Key =
+                storage::dictionary_get(holder_owners_dict, holder_id.to_string().as_str())
+                    .unwrap_or_revert()
+                    .unwrap_or_revert_with(Error::HolderDoesentExist);
+            (owner, ctx.holder(holder_id).amount)
+        })
+        .collect();
+    runtime::ret(CLValue::from_t(holders).unwrap_or_revert());
+}
@@ -0,0 +1,159 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{CLValue, Key, U256};
+
+use crate::{
+    balances::{balance_of_owner, to_token_id},
+    constants::{
+        MAX_PAGE_SIZE, NAMED_KEY_CEP85_URI, RUNTIME_ARG_ID, RUNTIME_ARG_IDS, RUNTIME_ARG_RECIPIENTS,
+    },
+    ndpc_utils::{get_named_key_by_name, when_storage_migrated},
+    storage_context::StorageContext,
+    Error,
+};
+
+/// Gets the `ids` runtime arg, it reverts with `BatchTooLarge` if there are more than `MAX_PAGE_SIZE` ids
+fn get_ids_arg() -> Vec<u64> {
+    let ids: Vec<U256> = runtime::get_named_arg(RUNTIME_ARG_IDS);
+    if ids.len() as u64 > MAX_PAGE_SIZE {
+        runtime::revert(Error::BatchTooLarge);
+    }
+    ids.into_iter().map(to_token_id).collect()
+}
+
+/// Returns the minted amount of the token (the `total_supply` dictionary), or 0 if it is not minted
+fn supply(ctx: &mut StorageContext, token_id: u64) -> u64 {
+    storage::dictionary_get::<u64>(ctx.total_supply_dict(), token_id.to_string().as_str())
+        .unwrap_or_revert()
+        .unwrap_or_default()
+}
+
+/// balance_of_batch entrypoint of the contract (CEP-85)
+///
+/// Gets `recipients` and `ids` (matched by their index, at most `MAX_PAGE_SIZE`), and returns the balance of each recipient in its token.
+/// It reverts with `BatchArgsMismatch` if the recipients and the ids do not match
+/// # Returns
+/// `balances` : `Vec<U256>`
+#[no_mangle]
+pub extern "C" fn balance_of_batch() {
+    when_storage_migrated();
+    let recipients: Vec<Key> = runtime::get_named_arg(RUNTIME_ARG_RECIPIENTS);
+    let token_ids = get_ids_arg();
+    if recipients.len() != token_ids.len() {
+        runtime::revert(Error::BatchArgsMismatch);
+    }
+    let mut ctx = StorageContext::new();
+    let balances: Vec<U256> = recipients
+        .iter()
+        .zip(token_ids)
+        .map(|(recipient, token_id)| U256::from(balance_of_owner(&mut ctx, recipient, token_id)))
+        .collect();
+    runtime::ret(CLValue::from_t(balances).unwrap_or_revert());
+}
+
+/// supply_of entrypoint of the contract (CEP-85)
+///
+/// Gets `id`, and returns the amount of the token in circulation. Tokens are never burnt, so it is the minted amount, the same as `total_supply_of`
+/// # Returns
+/// `supply` : `U256`
+#[no_mangle]
+pub extern "C" fn supply_of() {
+    when_storage_migrated();
+    let token_id = to_token_id(runtime::get_named_arg(RUNTIME_ARG_ID));
+    let mut ctx = StorageContext::new();
+    let supply = U256::from(supply(&mut ctx, token_id));
+    runtime::ret(CLValue::from_t(supply).unwrap_or_revert());
+}
+
+/// supply_of_batch entrypoint of the contract (CEP-85)
+///
+/// Gets `ids` (at most `MAX_PAGE_SIZE`), and returns the supply of each token
+/// # Returns
+/// `supplies` : `Vec<U256>`
+#[no_mangle]
+pub extern "C" fn supply_of_batch() {
+    when_storage_migrated();
+    let token_ids = get_ids_arg();
+    let mut ctx = StorageContext::new();
+    let supplies: Vec<U256> = token_ids
+        .into_iter()
+        .map(|token_id| U256::from(supply(&mut ctx, token_id)))
+        .collect();
+    runtime::ret(CLValue::from_t(supplies).unwrap_or_revert());
+}
+
+/// total_supply_of entrypoint of the contract (CEP-85)
+///
+/// Gets `id`, and returns the total amount of the token that is minted
+/// # Returns
+/// `total_supply` : `U256`
+#[no_mangle]
+pub extern "C" fn total_supply_of() {
+    supply_of();
+}
+
+/// total_supply_of_batch entrypoint of the contract (CEP-85)
+///
+/// Gets `ids` (at most `MAX_PAGE_SIZE`), and returns the total supply of each token
+/// # Returns
+/// `total_supplies` : `Vec<U256>`
+#[no_mangle]
+pub extern "C" fn total_supply_of_batch() {
+    supply_of_batch();
+}
+
+/// is_non_fungible entrypoint of the contract (CEP-85)
+///
+/// Gets `id`, and returns true if exactly one of the token is minted
+/// # Returns
+/// `is_non_fungible` : `bool`
+#[no_mangle]
+pub extern "C" fn is_non_fungible() {
+    when_storage_migrated();
+    let token_id = to_token_id(runtime::get_named_arg(RUNTIME_ARG_ID));
+    let mut ctx = StorageContext::new();
+    let is_non_fungible = supply(&mut ctx, token_id) == 1;
+    runtime::ret(CLValue::from_t(is_non_fungible).unwrap_or_revert());
+}
+
+/// uri entrypoint of the contract (CEP-85)
+///
+/// Gets an optional `id`, and returns the `token_uri` of the token's metadata, or the `uri` of the collection (the `uri` named key) if no id is given.
+/// It reverts with `MetadataDoesentExist` if the token is not minted
+/// # Returns
+/// `uri` : `String`
+#[no_mangle]
+pub extern "C" fn uri() {
+    when_storage_migrated();
+    let id: Option<U256> = runtime::get_named_arg(RUNTIME_ARG_ID);
+    let uri: String = match id {
+        Some(id) => StorageContext::new()
+            .nft_metadata(to_token_id(id))
+            .token_uri
+            .clone(),
+        None => storage::read(get_named_key_by_name(NAMED_KEY_CEP85_URI))
+            .unwrap_or_revert()
+            .unwrap_or_revert(),
+    };
+    runtime::ret(CLValue::from_t(uri).unwrap_or_revert());
+}
+
+/// metadata entrypoint of the contract
+///
+/// Gets `id`, and returns the metadata of the token as a CEP-78 metadata json (`name`, `token_uri` and `checksum`).
+/// It reverts with `MetadataDoesentExist` if the token is not minted
+/// # Returns
+/// `metadata` : `String`
+#[no_mangle]
+pub extern "C" fn metadata() {
+    when_storage_migrated();
+    let token_id = to_token_id(runtime::get_named_arg(RUNTIME_ARG_ID));
+    let metadata = StorageContext::new().nft_metadata(token_id).to_cep78_json();
+    runtime::ret(CLValue::from_t(metadata).unwrap_or_revert());
+}
@@ -0,0 +1,142 @@
+use alloc::{string::ToString, vec::Vec};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{CLValue, Key};
+
+use crate::{
+    constants::{
+        MAX_CLEANUP_BATCH, RUNTIME_ARG_APPROVED_IDS, RUNTIME_ARG_HOLDER_IDS,
+        RUNTIME_ARG_HOLDER_OWNERS, RUNTIME_ARG_REQUEST_IDS,
+    },
+    event::{emit, DropLinkedEvent},
+    ndpc_types::{ApprovedNFT, AsStrized, NFTHolder, PublishRequest},
+    ndpc_utils::when_storage_migrated,
+    storage_context::StorageContext,
+    Error,
+};
+
+/// compact entrypoint of the contract
+///
+/// Removes the records which could not be used anymore, and their ids from the id lists, so the lists only hold live ids. It could be called by anyone, and gets :
+/// - `holder_owners` and `holder_ids` (matched by their index) : a holder is removed if its amount is 0, it is in the `owners` list of the given owner
+///   and no approved listing of the owner with an amount left is on it (approve does not lock the amount, so a listing could outlive its holder's amount)
+/// - `approved_ids` : an approved listing is removed (from `approved`, and from the approved lists of its producer and publisher) if its amount is 0
+/// - `request_ids` : a publish request is removed from `request_objects` if it is not pending anymore (it is approved or cancelled)
+///
+/// At most `MAX_CLEANUP_BATCH` ids could be given in total, and the ids that could not be removed are skipped. A removed entry is overwritten with a unit value,
+/// as an entry could not be removed from a dictionary
+/// # Returns
+/// `compacted` : `u64`, the number of removed records
+/// # Emits
+/// `DropLinkedEvent::Compacted`, with the removed ids, if any record is removed
+#[no_mangle]
+pub extern "C" fn compact() {
+    when_storage_migrated();
+    let holder_owners: Vec<Key> = runtime::get_named_arg(RUNTIME_ARG_HOLDER_OWNERS);
+    let holder_ids: Vec<u64> = runtime::get_named_arg(RUNTIME_ARG_HOLDER_IDS);
+    let approved_ids: Vec<u64> = runtime::get_named_arg(RUNTIME_ARG_APPROVED_IDS);
+    let request_ids: Vec<u64> = runtime::get_named_arg(RUNTIME_ARG_REQUEST_IDS);
+    if holder_owners.len() != holder_ids.len() {
+        runtime::revert(Error::CompactArgsMismatch);
+    }
+    if holder_ids.len() + approved_ids.len() + request_ids.len() > MAX_CLEANUP_BATCH {
+        runtime::revert(Error::BatchTooLarge);
+    }
+    let mut ctx = StorageContext::new();
+    let compacted_holders: Vec<u64> = holder_owners
+        .into_iter()
+        .zip(holder_ids)
+        .filter(|(owner, holder_id)| compact_holder(&mut ctx, owner, *holder_id))
+        .map(|(_, holder_id)| holder_id)
+        .collect();
+    let compacted_approved: Vec<u64> = approved_ids
+        .into_iter()
+        .filter(|approved_id| compact_approved(&mut ctx, *approved_id))
+        .collect();
+    let compacted_requests: Vec<u64> = request_ids
+        .into_iter()
+        .filter(|request_id| compact_request(&mut ctx, *request_id))
+        .collect();
+
+    let compacted =
+        (compacted_holders.len() + compacted_approved.len() + compacted_requests.len()) as u64;
+    if compacted > 0 {
+        emit(DropLinkedEvent::Compacted {
+            holder_ids: compacted_holders,
+            approved_ids: compacted_approved,
+            request_ids: compacted_requests,
+        });
+    }
+    runtime::ret(CLValue::from_t(compacted).unwrap_or_revert());
+}
+
+/// Removes the holder if its amount is 0, the owner has it and it has no live listing, and returns true if it is removed
+fn compact_holder(ctx: &mut StorageContext, owner: &Key, holder_id: u64) -> bool {
+    let holders_dict = ctx.holders_dict();
+    let key = holder_id.to_string();
+    match storage::dictionary_get::<NFTHolder>(holders_dict, &key) {
+        Ok(Some(holder)) if holder.amount == 0 && !has_live_listing(ctx, owner, holder_id) => {
+            // remove returns false if the owner does not have the holder
+            if !ctx.owner_holders(&owner.as_string()).remove(holder_id) {
+                return false;
+            }
+            storage::dictionary_put(holders_dict, &key, ());
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if an approved listing of the owner (as the producer) is on the holder and still has an amount to sell
+fn has_live_listing(ctx: &mut StorageContext, owner: &Key, holder_id: u64) -> bool {
+    let approved_dict = ctx.approved_dict();
+    let producer_approved = ctx.producer_approved(&owner.as_string());
+    (0..producer_approved.len())
+        .filter_map(|index| producer_approved.get(index))
+        .any(|approved_id| {
+            matches!(
+                storage::dictionary_get::<ApprovedNFT>(approved_dict, approved_id.to_string().as_str()),
+                Ok(Some(approved_nft)) if approved_nft.holder_id == holder_id && approved_nft.amount > 0
+            )
+        })
+}
+
+/// Removes the approved listing if its amount is 0, and returns true if it is removed
+fn compact_approved(ctx: &mut StorageContext, approved_id: u64) -> bool {
+    let approved_dict = ctx.approved_dict();
+    let key = approved_id.to_string();
+    match storage::dictionary_get::<ApprovedNFT>(approved_dict, &key) {
+        Ok(Some(approved_nft)) if approved_nft.amount == 0 => {
+            // a listing that is disapproved to 0 is already removed from the lists
+            ctx.producer_approved(&approved_nft.owneraccount.as_string())
+                .remove(approved_id);
+            ctx.publisher_approved(&approved_nft.publisheraccount.as_string())
+                .remove(approved_id);
+            storage::dictionary_put(approved_dict, &key, ());
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Removes the publish request if it is not in the pending lists of its producer and publisher, and returns true if it is removed
+fn compact_request(ctx: &mut StorageContext, request_id: u64) -> bool {
+    let requests_dict = ctx.requests_dict();
+    let key = request_id.to_string();
+    match storage::dictionary_get::<PublishRequest>(requests_dict, &key) {
+        Ok(Some(request))
+            if !ctx
+                .producer_requests(&request.producer.as_string())
+                .contains(request_id)
+                && !ctx
+                    .publisher_requests(&request.publisher.as_string())
+                    .contains(request_id) =>
+        {
+            storage::dictionary_put(requests_dict, &key, ());
+            true
+        }
+        _ => false,
+    }
+}
@@ -1,10 +1,12 @@
-use alloc::{string::ToString, vec};
+use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
 use casper_contract::contract_api::storage;
 use casper_types::{
     contracts::{NamedKeys, Parameters},
-    EntryPoint, EntryPoints, Group, Parameter, PublicKey,
+    EntryPoint, EntryPoints, Group, Key, Parameter, PublicKey,
 };
 
+use crate::event::{get_schemas, CES_VERSION};
+
 pub const RUNTIME_ARG_PRODUCER_ACCOUNT_HASH: &str = "producer-account";
 pub const NAMED_KEY_DICT_APPROVED_NAME: &str = "approved";
 pub const NAMED_KEY_DICT_HOLDERS_NAME: &str = "holders";
@@ -21,7 +23,47 @@ pub const NAMED_KEY_DICT_REQ_OBJ: &str = "request_objects";
 pub const NAMED_KEY_DICT_PROD_REQS: &str = "producer_requests";
 pub const NAMED_KEY_DICT_PUB_REQS: &str = "publiser_requests";
 pub const NAMED_KEY_DICT_TOTAL_SUPPLY: &str = "total_supply";
-pub const NAMED_KEY_RATIO_VERIFIER: &str = "ratio_verifier";
+pub const NAMED_KEY_TREASURY: &str = "treasury";
+pub const NAMED_KEY_PENDING_FEE: &str = "pending_fee";
+pub const NAMED_KEY_QUOTE_FRESHNESS: &str = "quote_freshness";
+pub const NAMED_KEY_OWNER: &str = "owner";
+pub const NAMED_KEY_PENDING_OWNER: &str = "pending_owner";
+pub const NAMED_KEY_DICT_OPERATORS: &str = "operators";
+pub const NAMED_KEY_ORACLE_SIGNERS: &str = "oracle_signers";
+pub const NAMED_KEY_ORACLE_THRESHOLD: &str = "oracle_threshold";
+pub const NAMED_KEY_RETIRED_ORACLE_SIGNERS: &str = "retired_oracle_signers";
+pub const NAMED_KEY_PAUSED: &str = "paused";
+pub const NAMED_KEY_CONTRACT_VERSION: &str = "contract_version";
+pub const NAMED_KEY_CONTRACT: &str = "droplinked_contract";
+pub const NAMED_KEY_ACCESS_UREF: &str = "droplinked_access_uref";
+pub const NAMED_KEY_GOVERNORS: &str = "governors";
+pub const NAMED_KEY_GOVERNANCE_THRESHOLD: &str = "governance_threshold";
+pub const NAMED_KEY_GOVERNANCE_DELAY: &str = "governance_delay";
+pub const NAMED_KEY_DICT_PROPOSALS: &str = "proposals";
+pub const NAMED_KEY_PROPOSALS_CNT: &str = "proposals_cnt";
+pub const NAMED_KEY_UPGRADE_AUTHORIZED: &str = "upgrade_authorized";
+pub const NAMED_KEY_DICT_EVENTS: &str = "__events";
+pub const NAMED_KEY_EVENTS_LENGTH: &str = "__events_length";
+pub const NAMED_KEY_EVENTS_SCHEMA: &str = "__events_schema";
+pub const NAMED_KEY_EVENTS_CES_VERSION: &str = "__events_ces_version";
+pub const NAMED_KEY_DICT_ORDERS: &str = "orders";
+pub const NAMED_KEY_ORDERS_CNT: &str = "orders_cnt";
+pub const NAMED_KEY_DICT_BUYER_ORDERS: &str = "buyer_orders";
+pub const NAMED_KEY_DICT_PROVENANCE: &str = "provenance";
+pub const NAMED_KEY_PROVENANCE_CNT: &str = "provenance_cnt";
+pub const NAMED_KEY_CEP85_NAME: &str = "name";
+pub const NAMED_KEY_CEP85_URI: &str = "uri";
+pub const CEP85_COLLECTION_NAME: &str = "droplinked";
+pub const NAMED_KEY_DICT_TOKEN_PROVENANCE: &str = "token_provenance";
+pub const NAMED_KEY_DICT_BALANCES: &str = "balances";
+pub const NAMED_KEY_DICT_TOKEN_HOLDERS: &str = "token_holders";
+pub const NAMED_KEY_DICT_HOLDER_OWNERS: &str = "holder_owners";
+pub const NAMED_KEY_DICT_PRODUCER_ORDERS: &str = "producer_orders";
+pub const NAMED_KEY_DICT_PUBLISHER_ORDERS: &str = "publisher_orders";
+pub const NAMED_KEY_SCHEMA_VERSION: &str = "schema_version";
+pub const NAMED_KEY_MIGRATION_CURSOR: &str = "migration_cursor";
+pub const NAMED_KEY_ESCROW_PURSE: &str = "escrow_purse";
+pub const NAMED_KEY_DICT_ESCROW_BALANCES: &str = "escrow_balances";
 pub const RUNTIME_ARG_METADATA: &str = "metadata";
 pub const RUNTIME_ARG_AMOUNT: &str = "amount";
 pub const RUNTIME_ARG_RECIPIENT: &str = "recipient";
@@ -30,15 +72,69 @@ pub const RUNTIME_ARG_SPENDER: &str = "publisher-account";
 pub const RUNTIME_ARG_APPROVED_ID: &str = "approved_id";
 pub const RUNTIME_ARG_COMISSION: &str = "comission";
 pub const RUNTIME_ARG_REQUEST_ID: &str = "request_id";
-pub const RUNTIME_ARG_CURRENT_PRICE_TIMESTAMP: &str = "current_price_timestamp";
-pub const RUNTIME_ARG_SIGNATURE: &str = "signature";
+pub const RUNTIME_ARG_QUOTE: &str = "quote";
+pub const RUNTIME_ARG_SIGNERS: &str = "signers";
+pub const RUNTIME_ARG_SIGNATURES: &str = "signatures";
+pub const RUNTIME_ARG_PUBLIC_KEY: &str = "public_key";
+pub const RUNTIME_ARG_THRESHOLD: &str = "threshold";
 pub const RUNTIME_ARG_PURSE_ADDR: &str = "purse_addr";
 pub const RUNTIME_ARG_PRICE: &str = "price";
 pub const RUNTIME_ARG_SHIPPING_PRICE: &str = "shipping_price";
 pub const RUNTIME_ARG_TAX_PRICE: &str = "tax_price";
 pub const RUNTIME_PRODUCT_PRICE: &str = "product_price";
 pub const RUNTIME_FEE: &str = "fee";
+pub const RUNTIME_ARG_RATIO_VERIFIER: &str = "ratio_verifier";
+pub const RUNTIME_ARG_TREASURY: &str = "treasury";
+pub const RUNTIME_ARG_NEW_OWNER: &str = "new_owner";
+pub const RUNTIME_ARG_ACCOUNT: &str = "account";
+pub const RUNTIME_ARG_EFFECTIVE_AT: &str = "effective_at";
+pub const RUNTIME_ARG_NEW_PUBLIC_KEY: &str = "new_public_key";
+pub const RUNTIME_ARG_GRACE_PERIOD: &str = "grace_period";
+pub const RUNTIME_ARG_PAUSED: &str = "paused";
+pub const RUNTIME_ARG_GOVERNORS: &str = "governors";
+pub const RUNTIME_ARG_DELAY: &str = "delay";
+pub const RUNTIME_ARG_ACTION: &str = "action";
+pub const RUNTIME_ARG_PROPOSAL_ID: &str = "proposal_id";
+pub const RUNTIME_ARG_ORDER_REFERENCE: &str = "order_reference";
+pub const RUNTIME_ARG_ORDER_ID: &str = "order_id";
+pub const RUNTIME_ARG_ACCOUNTS: &str = "accounts";
+pub const RUNTIME_ARG_HOLDER_OWNERS: &str = "holder_owners";
+pub const RUNTIME_ARG_HOLDER_IDS: &str = "holder_ids";
+pub const RUNTIME_ARG_APPROVED_IDS: &str = "approved_ids";
+pub const RUNTIME_ARG_REQUEST_IDS: &str = "request_ids";
+pub const RUNTIME_ARG_TOKEN_ID: &str = "token_id";
+pub const RUNTIME_ARG_OFFSET: &str = "offset";
+pub const RUNTIME_ARG_LIMIT: &str = "limit";
+pub const RUNTIME_ARG_ID: &str = "id";
+pub const RUNTIME_ARG_IDS: &str = "ids";
+pub const RUNTIME_ARG_RECIPIENTS: &str = "recipients";
+pub const RUNTIME_ARG_QUOTE_FRESHNESS: &str = "quote_freshness";
 pub const CONTRACTPACKAGEHASH: &str = "droplinked_package_hash";
+pub const QUOTE_CURRENCY_PAIR: &str = "CSPR/USD";
+pub const QUOTE_MAX_DECIMALS: u8 = 9;
+pub const MIN_QUOTE_FRESHNESS: u64 = 10000;
+pub const MAX_QUOTE_FRESHNESS: u64 = 3600000;
+pub const MAX_ORACLE_SIGNERS: usize = 10;
+pub const MAX_FEE: u64 = 1000;
+pub const MAX_ROTATION_GRACE_PERIOD: u64 = 3600000;
+pub const CONTRACT_VERSION: u32 = 11;
+pub const SCHEMA_VERSION: u32 = 1;
+pub const CONSTRUCTOR_GROUP: &str = "constructor";
+pub const MIGRATOR_GROUP: &str = "migrator";
+pub const MAX_ORDER_REFERENCE_LENGTH: usize = 64;
+pub const MAX_CLEANUP_BATCH: usize = 50;
+pub const MAX_PAGE_SIZE: u64 = 100;
+pub const MAX_MIGRATION_BATCH: u64 = 50;
+pub const MAX_GOVERNORS: usize = 10;
+pub const MIN_GOVERNANCE_DELAY: u64 = 3600000;
+pub const MAX_GOVERNANCE_DELAY: u64 = 2592000000;
+pub const PAUSE_BUY: u8 = 1;
+pub const PAUSE_DIRECT_PAY: u8 = 2;
+pub const PAUSE_MINT: u8 = 4;
+pub const PAUSE_APPROVE: u8 = 8;
+pub const PAUSE_PUBLISH_REQUEST: u8 = 16;
+pub const PAUSE_ALL: u8 =
+    PAUSE_BUY | PAUSE_DIRECT_PAY | PAUSE_MINT | PAUSE_APPROVE | PAUSE_PUBLISH_REQUEST;
 
 /// Returns all the entrypoints that the contract has
 /// 
@@ -53,6 +149,116 @@ pub const CONTRACTPACKAGEHASH: &str = "droplinked_package_hash";
 /// 6. publish_request
 /// 7. cancel_request
 /// 8. direct_pay
+///     Gets : `product_price`, `shipping_price`, `tax_price` : `U512`, `recipient` : `String`, `purse_addr` : `Key`, and optionally `order_reference` : `String` (at most 64 bytes)
+/// 9. set_quote_freshness
+///     Gets : `quote_freshness` : `u64` (only callable by the owner or an operator)
+/// 10. add_oracle_signer
+///     Gets : `public_key` : `PublicKey` (only callable by the owner)
+/// 11. remove_oracle_signer
+///     Gets : `public_key` : `PublicKey` (only callable by the owner)
+/// 12. set_oracle_threshold
+///     Gets : `threshold` : `u8` (only callable by the owner)
+/// 13. set_treasury
+///     Gets : `treasury` : `Key` (only callable by the owner)
+/// 14. transfer_ownership
+///     Gets : `new_owner` : `Key` (only callable by the owner)
+/// 15. accept_ownership (only callable by the pending owner)
+/// 16. grant_operator
+///     Gets : `account` : `Key` (only callable by the owner)
+/// 17. revoke_operator
+///     Gets : `account` : `Key` (only callable by the owner)
+/// 18. set_fee
+///     Gets : `fee` : `u64` , `effective_at` : `Option<u64>` (only callable by the owner)
+/// 19. rotate_oracle_signer
+///     Gets : `public_key` : `PublicKey` , `new_public_key` : `PublicKey` , `grace_period` : `u64` (only callable by the owner)
+/// 20. set_paused
+///     Gets : `paused` : `u8` (operators could only pause, unpausing is only callable by the owner)
+/// 21. migrate
+///     Gets : Nothing (only callable by the migrator group, while a new version is being added to the package)
+/// 22. set_governors
+///     Gets : `governors` : `List<Key>` , `threshold` : `u8` , `delay` : `u64` (only callable by the owner, before governance is configured)
+/// 23. propose
+///     Gets : `action` : `String` (only callable by the governors)
+/// 24. approve_proposal
+///     Gets : `proposal_id` : `u64` (only callable by the governors)
+/// 25. execute_proposal
+///     Gets : `proposal_id` : `u64` (only callable by the governors)
+/// 26. cancel_proposal
+///     Gets : `proposal_id` : `u64` (only callable by the governors)
+/// 27. get_order
+///     Gets : `order_id` : `u64`
+///     Returns : `order` : `Order`
+/// 28. clean_holders
+///     Gets : `accounts` : `Vec<Key>` (only callable by the owner or an operator)
+///     Returns : `cleaned` : `u64`
+/// 29. get_owner_holders
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `owner_holders` : `Vec<u64>`, the page of the holder ids that the account owns
+/// 30. get_producer_requests
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `producer_requests` : `Vec<u64>`, the page of the pending publish request ids that are made to the producer
+/// 31. get_publisher_requests
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `publisher_requests` : `Vec<u64>`, the page of the pending publish request ids that the publisher made
+/// 32. get_producer_approved
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `producer_approved` : `Vec<u64>`, the page of the approved ids of the producer
+/// 33. get_publisher_approved
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `publisher_approved` : `Vec<u64>`, the page of the approved ids of the publisher
+/// 34. get_buyer_orders
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `buyer_orders` : `Vec<u64>`, the page of the order ids of the buyer
+/// 35. get_producer_orders
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `producer_orders` : `Vec<u64>`, the page of the order ids of the producer
+/// 36. get_publisher_orders
+///     Gets : `account` : `Key`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `publisher_orders` : `Vec<u64>`, the page of the order ids of the publisher
+/// 37. migrate_storage
+///     Gets : nothing (only callable by the owner or an operator)
+///     Returns : `migrated` : `bool`, true when every record is stored in its current layout
+/// 38. withdraw
+///     Gets : `purse_addr` : `Key` (the escrowed balance of the calling contract package is transferred to this purse)
+/// 39. compact
+///     Gets : `holder_owners` : `Vec<Key>`, `holder_ids` : `Vec<u64>`, `approved_ids` : `Vec<u64>`, `request_ids` : `Vec<u64>` (at most 50 ids in total)
+///     Returns : `compacted` : `u64`, the number of removed records
+/// 40. get_token_provenance
+///     Gets : `token_id` : `u64`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `token_provenance` : `Vec<ProvenanceEntry>`, the page of the movements of the token, in the order they are made
+/// 41. balance_of (with the CEP-85 arguments)
+///     Gets : `recipient` : `Key`, `id` : `U256`
+///     Returns : `balance` : `U256`, the amount of the token that the recipient holds
+/// 42. holders_of
+///     Gets : `token_id` : `u64`, `offset` : `u64`, `limit` : `u64` (at most 100)
+///     Returns : `holders` : `Vec<(Key, u64)>`, the page of the owners of the token, with their amounts
+/// 43. balance_of_batch (CEP-85)
+///     Gets : `recipients` : `Vec<Key>`, `ids` : `Vec<U256>` (matched by their index, at most 100)
+///     Returns : `balances` : `Vec<U256>`
+/// 44. supply_of (CEP-85)
+///     Gets : `id` : `U256`
+///     Returns : `supply` : `U256`, the minted amount of the token
+/// 45. supply_of_batch (CEP-85)
+///     Gets : `ids` : `Vec<U256>` (at most 100)
+///     Returns : `supplies` : `Vec<U256>`
+/// 46. total_supply_of (CEP-85)
+///     Gets : `id` : `U256`
+///     Returns : `total_supply` : `U256`, the same as `supply_of`, as tokens are never burnt
+/// 47. total_supply_of_batch (CEP-85)
+///     Gets : `ids` : `Vec<U256>` (at most 100)
+///     Returns : `total_supplies` : `Vec<U256>`
+/// 48. is_non_fungible (CEP-85)
+///     Gets : `id` : `U256`
+///     Returns : `is_non_fungible` : `bool`, true if exactly one of the token is minted
+/// 49. uri (CEP-85)
+///     Gets : `id` : `Option<U256>`
+///     Returns : `uri` : `String`, the `token_uri` of the token, or the `uri` of the collection
+/// 50. metadata
+///     Gets : `id` : `U256`
+///     Returns : `metadata` : `String`, the metadata of the token as a CEP-78 metadata json
+/// 51. upgrade_access
+///     Gets : Nothing (only callable by the owner, and once governance is configured, only once per executed `AuthorizeUpgrade` proposal)
+///     Returns : `access_uref` : `URef`, the access uref of the contract package, which the upgrade session needs to add and disable versions
 pub fn get_entrypoints() -> EntryPoints {
     let mut result = EntryPoints::new();
     let mint_parameters: Parameters = vec![
@@ -78,11 +284,15 @@ pub fn get_entrypoints() -> EntryPoints {
         Parameter::new(RUNTIME_ARG_AMOUNT, casper_types::CLType::U64),
         Parameter::new(RUNTIME_ARG_PURSE_ADDR, casper_types::CLType::Key),
         Parameter::new(RUNTIME_ARG_APPROVED_ID, casper_types::CLType::U64),
+        Parameter::new(RUNTIME_ARG_QUOTE, casper_types::CLType::String),
         Parameter::new(
-            RUNTIME_ARG_CURRENT_PRICE_TIMESTAMP,
-            casper_types::CLType::String,
+            RUNTIME_ARG_SIGNERS,
+            casper_types::CLType::List(Box::new(casper_types::CLType::PublicKey)),
+        ),
+        Parameter::new(
+            RUNTIME_ARG_SIGNATURES,
+            casper_types::CLType::List(Box::new(casper_types::CLType::String)),
         ),
-        Parameter::new(RUNTIME_ARG_SIGNATURE, casper_types::CLType::String),
         Parameter::new(RUNTIME_ARG_SHIPPING_PRICE, casper_types::CLType::U512),
         Parameter::new(RUNTIME_ARG_TAX_PRICE, casper_types::CLType::U512),
     ];
@@ -103,7 +313,65 @@ pub fn get_entrypoints() -> EntryPoints {
         Parameter::new(RUNTIME_ARG_TAX_PRICE, casper_types::CLType::U512),
         Parameter::new(RUNTIME_ARG_RECIPIENT, casper_types::CLType::String),
         Parameter::new(RUNTIME_ARG_PURSE_ADDR, casper_types::CLType::Key),
+        Parameter::new(RUNTIME_ARG_ORDER_REFERENCE, casper_types::CLType::String),
+    ];
+
+    let set_quote_freshness_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_QUOTE_FRESHNESS,
+        casper_types::CLType::U64,
+    )];
+    let oracle_signer_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_PUBLIC_KEY,
+        casper_types::CLType::PublicKey,
+    )];
+    let set_oracle_threshold_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_THRESHOLD,
+        casper_types::CLType::U8,
+    )];
+    let set_treasury_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_TREASURY,
+        casper_types::CLType::Key,
+    )];
+    let transfer_ownership_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_NEW_OWNER,
+        casper_types::CLType::Key,
+    )];
+    let operator_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_ACCOUNT,
+        casper_types::CLType::Key,
+    )];
+    let set_fee_parameters: Parameters = vec![
+        Parameter::new(RUNTIME_FEE, casper_types::CLType::U64),
+        Parameter::new(
+            RUNTIME_ARG_EFFECTIVE_AT,
+            casper_types::CLType::Option(Box::new(casper_types::CLType::U64)),
+        ),
+    ];
+    let rotate_oracle_signer_parameters: Parameters = vec![
+        Parameter::new(RUNTIME_ARG_PUBLIC_KEY, casper_types::CLType::PublicKey),
+        Parameter::new(RUNTIME_ARG_NEW_PUBLIC_KEY, casper_types::CLType::PublicKey),
+        Parameter::new(RUNTIME_ARG_GRACE_PERIOD, casper_types::CLType::U64),
     ];
+    let set_paused_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_PAUSED,
+        casper_types::CLType::U8,
+    )];
+    let set_governors_parameters: Parameters = vec![
+        Parameter::new(
+            RUNTIME_ARG_GOVERNORS,
+            casper_types::CLType::List(Box::new(casper_types::CLType::Key)),
+        ),
+        Parameter::new(RUNTIME_ARG_THRESHOLD, casper_types::CLType::U8),
+        Parameter::new(RUNTIME_ARG_DELAY, casper_types::CLType::U64),
+    ];
+    let propose_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_ACTION,
+        casper_types::CLType::String,
+    )];
+    let proposal_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_PROPOSAL_ID,
+        casper_types::CLType::U64,
+    )];
 
     let entry_point_mint = EntryPoint::new(
         "mint",
@@ -138,7 +406,7 @@ pub fn get_entrypoints() -> EntryPoints {
         "init",
         Parameters::new(),
         casper_types::CLType::Unit,
-        casper_types::EntryPointAccess::Groups(vec![Group::new("constructor")]),
+        casper_types::EntryPointAccess::Groups(vec![Group::new(CONSTRUCTOR_GROUP)]),
         casper_types::EntryPointType::Contract,
     );
     let entry_point_publish_request = EntryPoint::new(
@@ -162,6 +430,361 @@ pub fn get_entrypoints() -> EntryPoints {
         casper_types::EntryPointAccess::Public,
         casper_types::EntryPointType::Contract,
     );
+    let entry_point_set_quote_freshness = EntryPoint::new(
+        "set_quote_freshness",
+        set_quote_freshness_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_add_oracle_signer = EntryPoint::new(
+        "add_oracle_signer",
+        oracle_signer_parameters.clone(),
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_remove_oracle_signer = EntryPoint::new(
+        "remove_oracle_signer",
+        oracle_signer_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_set_oracle_threshold = EntryPoint::new(
+        "set_oracle_threshold",
+        set_oracle_threshold_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_set_treasury = EntryPoint::new(
+        "set_treasury",
+        set_treasury_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_transfer_ownership = EntryPoint::new(
+        "transfer_ownership",
+        transfer_ownership_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_accept_ownership = EntryPoint::new(
+        "accept_ownership",
+        Parameters::new(),
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_grant_operator = EntryPoint::new(
+        "grant_operator",
+        operator_parameters.clone(),
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_revoke_operator = EntryPoint::new(
+        "revoke_operator",
+        operator_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_set_fee = EntryPoint::new(
+        "set_fee",
+        set_fee_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_rotate_oracle_signer = EntryPoint::new(
+        "rotate_oracle_signer",
+        rotate_oracle_signer_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_set_paused = EntryPoint::new(
+        "set_paused",
+        set_paused_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_migrate = EntryPoint::new(
+        "migrate",
+        Parameters::new(),
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Groups(vec![Group::new(MIGRATOR_GROUP)]),
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_set_governors = EntryPoint::new(
+        "set_governors",
+        set_governors_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_propose = EntryPoint::new(
+        "propose",
+        propose_parameters,
+        casper_types::CLType::U64,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_approve_proposal = EntryPoint::new(
+        "approve_proposal",
+        proposal_parameters.clone(),
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_execute_proposal = EntryPoint::new(
+        "execute_proposal",
+        proposal_parameters.clone(),
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_cancel_proposal = EntryPoint::new(
+        "cancel_proposal",
+        proposal_parameters,
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_order = EntryPoint::new(
+        "get_order",
+        vec![Parameter::new(RUNTIME_ARG_ORDER_ID, casper_types::CLType::U64)],
+        casper_types::CLType::Any,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_clean_holders = EntryPoint::new(
+        "clean_holders",
+        vec![Parameter::new(
+            RUNTIME_ARG_ACCOUNTS,
+            casper_types::CLType::List(Box::new(casper_types::CLType::Key)),
+        )],
+        casper_types::CLType::U64,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_migrate_storage = EntryPoint::new(
+        "migrate_storage",
+        vec![],
+        casper_types::CLType::Bool,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_withdraw = EntryPoint::new(
+        "withdraw",
+        vec![Parameter::new(
+            RUNTIME_ARG_PURSE_ADDR,
+            casper_types::CLType::Key,
+        )],
+        casper_types::CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let u64_list = || casper_types::CLType::List(Box::new(casper_types::CLType::U64));
+    let entry_point_compact = EntryPoint::new(
+        "compact",
+        vec![
+            Parameter::new(
+                RUNTIME_ARG_HOLDER_OWNERS,
+                casper_types::CLType::List(Box::new(casper_types::CLType::Key)),
+            ),
+            Parameter::new(RUNTIME_ARG_HOLDER_IDS, u64_list()),
+            Parameter::new(RUNTIME_ARG_APPROVED_IDS, u64_list()),
+            Parameter::new(RUNTIME_ARG_REQUEST_IDS, u64_list()),
+        ],
+        casper_types::CLType::U64,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_token_provenance = EntryPoint::new(
+        "get_token_provenance",
+        vec![
+            Parameter::new(RUNTIME_ARG_TOKEN_ID, casper_types::CLType::U64),
+            Parameter::new(RUNTIME_ARG_OFFSET, casper_types::CLType::U64),
+            Parameter::new(RUNTIME_ARG_LIMIT, casper_types::CLType::U64),
+        ],
+        casper_types::CLType::List(Box::new(casper_types::CLType::Any)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_balance_of = EntryPoint::new(
+        "balance_of",
+        vec![
+            Parameter::new(RUNTIME_ARG_RECIPIENT, casper_types::CLType::Key),
+            Parameter::new(RUNTIME_ARG_ID, casper_types::CLType::U256),
+        ],
+        casper_types::CLType::U256,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_balance_of_batch = EntryPoint::new(
+        "balance_of_batch",
+        vec![
+            Parameter::new(
+                RUNTIME_ARG_RECIPIENTS,
+                casper_types::CLType::List(Box::new(casper_types::CLType::Key)),
+            ),
+            Parameter::new(
+                RUNTIME_ARG_IDS,
+                casper_types::CLType::List(Box::new(casper_types::CLType::U256)),
+            ),
+        ],
+        casper_types::CLType::List(Box::new(casper_types::CLType::U256)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let id_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_ID,
+        casper_types::CLType::U256,
+    )];
+    let ids_parameters: Parameters = vec![Parameter::new(
+        RUNTIME_ARG_IDS,
+        casper_types::CLType::List(Box::new(casper_types::CLType::U256)),
+    )];
+    let entry_point_supply_of = EntryPoint::new(
+        "supply_of",
+        id_parameters.clone(),
+        casper_types::CLType::U256,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_supply_of_batch = EntryPoint::new(
+        "supply_of_batch",
+        ids_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U256)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_total_supply_of = EntryPoint::new(
+        "total_supply_of",
+        id_parameters.clone(),
+        casper_types::CLType::U256,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_total_supply_of_batch = EntryPoint::new(
+        "total_supply_of_batch",
+        ids_parameters,
+        casper_types::CLType::List(Box::new(casper_types::CLType::U256)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_is_non_fungible = EntryPoint::new(
+        "is_non_fungible",
+        id_parameters.clone(),
+        casper_types::CLType::Bool,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_uri = EntryPoint::new(
+        "uri",
+        vec![Parameter::new(
+            RUNTIME_ARG_ID,
+            casper_types::CLType::Option(Box::new(casper_types::CLType::U256)),
+        )],
+        casper_types::CLType::String,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_metadata = EntryPoint::new(
+        "metadata",
+        id_parameters,
+        casper_types::CLType::String,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_upgrade_access = EntryPoint::new(
+        "upgrade_access",
+        Parameters::new(),
+        casper_types::CLType::URef,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_holders_of = EntryPoint::new(
+        "holders_of",
+        vec![
+            Parameter::new(RUNTIME_ARG_TOKEN_ID, casper_types::CLType::U64),
+            Parameter::new(RUNTIME_ARG_OFFSET, casper_types::CLType::U64),
+            Parameter::new(RUNTIME_ARG_LIMIT, casper_types::CLType::U64),
+        ],
+        casper_types::CLType::List(Box::new(casper_types::CLType::Tuple2([
+            Box::new(casper_types::CLType::Key),
+            Box::new(casper_types::CLType::U64),
+        ]))),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let page_parameters: Parameters = vec![
+        Parameter::new(RUNTIME_ARG_ACCOUNT, casper_types::CLType::Key),
+        Parameter::new(RUNTIME_ARG_OFFSET, casper_types::CLType::U64),
+        Parameter::new(RUNTIME_ARG_LIMIT, casper_types::CLType::U64),
+    ];
+    let entry_point_get_owner_holders = EntryPoint::new(
+        "get_owner_holders",
+        page_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_producer_requests = EntryPoint::new(
+        "get_producer_requests",
+        page_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_publisher_requests = EntryPoint::new(
+        "get_publisher_requests",
+        page_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_producer_approved = EntryPoint::new(
+        "get_producer_approved",
+        page_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_publisher_approved = EntryPoint::new(
+        "get_publisher_approved",
+        page_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_buyer_orders = EntryPoint::new(
+        "get_buyer_orders",
+        page_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_producer_orders = EntryPoint::new(
+        "get_producer_orders",
+        page_parameters.clone(),
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
+    let entry_point_get_publisher_orders = EntryPoint::new(
+        "get_publisher_orders",
+        page_parameters,
+        casper_types::CLType::List(Box::new(casper_types::CLType::U64)),
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    );
 
     result.add_entry_point(entry_point_mint);
     result.add_entry_point(entry_point_approve);
@@ -171,15 +794,67 @@ pub fn get_entrypoints() -> EntryPoints {
     result.add_entry_point(entry_point_publish_request);
     result.add_entry_point(entry_point_cancel_request);
     result.add_entry_point(entry_point_direct_pay);
+    result.add_entry_point(entry_point_set_quote_freshness);
+    result.add_entry_point(entry_point_add_oracle_signer);
+    result.add_entry_point(entry_point_remove_oracle_signer);
+    result.add_entry_point(entry_point_set_oracle_threshold);
+    result.add_entry_point(entry_point_set_treasury);
+    result.add_entry_point(entry_point_transfer_ownership);
+    result.add_entry_point(entry_point_accept_ownership);
+    result.add_entry_point(entry_point_grant_operator);
+    result.add_entry_point(entry_point_revoke_operator);
+    result.add_entry_point(entry_point_set_fee);
+    result.add_entry_point(entry_point_rotate_oracle_signer);
+    result.add_entry_point(entry_point_set_paused);
+    result.add_entry_point(entry_point_migrate);
+    result.add_entry_point(entry_point_set_governors);
+    result.add_entry_point(entry_point_propose);
+    result.add_entry_point(entry_point_approve_proposal);
+    result.add_entry_point(entry_point_execute_proposal);
+    result.add_entry_point(entry_point_cancel_proposal);
+    result.add_entry_point(entry_point_get_order);
+    result.add_entry_point(entry_point_clean_holders);
+    result.add_entry_point(entry_point_get_owner_holders);
+    result.add_entry_point(entry_point_get_producer_requests);
+    result.add_entry_point(entry_point_get_publisher_requests);
+    result.add_entry_point(entry_point_get_producer_approved);
+    result.add_entry_point(entry_point_get_publisher_approved);
+    result.add_entry_point(entry_point_get_buyer_orders);
+    result.add_entry_point(entry_point_get_producer_orders);
+    result.add_entry_point(entry_point_get_publisher_orders);
+    result.add_entry_point(entry_point_migrate_storage);
+    result.add_entry_point(entry_point_withdraw);
+    result.add_entry_point(entry_point_compact);
+    result.add_entry_point(entry_point_get_token_provenance);
+    result.add_entry_point(entry_point_balance_of);
+    result.add_entry_point(entry_point_holders_of);
+    result.add_entry_point(entry_point_balance_of_batch);
+    result.add_entry_point(entry_point_supply_of);
+    result.add_entry_point(entry_point_supply_of_batch);
+    result.add_entry_point(entry_point_total_supply_of);
+    result.add_entry_point(entry_point_total_supply_of_batch);
+    result.add_entry_point(entry_point_is_non_fungible);
+    result.add_entry_point(entry_point_uri);
+    result.add_entry_point(entry_point_metadata);
+    result.add_entry_point(entry_point_upgrade_access);
     result
 }
 
 /// Gets the namedkeys of the contract and returns them as a BTreeMap<String,Key>
 /// 
-/// It contains NamedKeys for : `NAMED_KEY_APPROVED_CNT`, `NAMED_KEY_HOLDERSCNT`, `NAMED_KEY_TOKENSCNT`, `NAMED_KEY_REQ_CNT`, `NAMED_KEY_RATIO_VERIFIER`, `RUNTIME_FEE` 
+/// It contains NamedKeys for : `NAMED_KEY_APPROVED_CNT`, `NAMED_KEY_HOLDERSCNT`, `NAMED_KEY_TOKENSCNT`, `NAMED_KEY_REQ_CNT`, `NAMED_KEY_TREASURY`, `RUNTIME_FEE`, `NAMED_KEY_PENDING_FEE`,
+/// `NAMED_KEY_QUOTE_FRESHNESS`, `NAMED_KEY_OWNER`, `NAMED_KEY_PENDING_OWNER`, `NAMED_KEY_ORACLE_SIGNERS` (which starts with the ratio verifier as its only signer), `NAMED_KEY_ORACLE_THRESHOLD`
+/// `NAMED_KEY_RETIRED_ORACLE_SIGNERS`, `NAMED_KEY_PAUSED` (nothing is paused at first), `NAMED_KEY_CONTRACT_VERSION`, and the governance keys
+/// (`NAMED_KEY_GOVERNORS` which is empty until governance is configured, `NAMED_KEY_GOVERNANCE_THRESHOLD`, `NAMED_KEY_GOVERNANCE_DELAY`, `NAMED_KEY_PROPOSALS_CNT`
+/// and `NAMED_KEY_UPGRADE_AUTHORIZED`), the Casper Event Standard keys (`NAMED_KEY_EVENTS_LENGTH`, `NAMED_KEY_EVENTS_SCHEMA` and `NAMED_KEY_EVENTS_CES_VERSION`), `NAMED_KEY_ORDERS_CNT`
+/// `NAMED_KEY_PROVENANCE_CNT`, `NAMED_KEY_SCHEMA_VERSION` (a new contract stores its records in the current layouts), and the CEP-85 keys `NAMED_KEY_CEP85_NAME`
+/// and `NAMED_KEY_CEP85_URI` (the collection has no uri of its own, each token has its `token_uri`)
 pub fn get_named_keys(
     ratio_verifier: PublicKey,
+    treasury: Key,
     fee: u64,
+    quote_freshness: u64,
+    owner: Key,
 ) -> alloc::collections::BTreeMap<alloc::string::String, casper_types::Key> {
     let mut named_keys: NamedKeys = NamedKeys::new();
     named_keys.insert(
@@ -199,10 +874,95 @@ pub fn get_named_keys(
         storage::new_uref(0u64).into(),
     );
     named_keys.insert(
-        NAMED_KEY_RATIO_VERIFIER.to_string(),
-        storage::new_uref(ratio_verifier).into(),
+        NAMED_KEY_ORACLE_SIGNERS.to_string(),
+        storage::new_uref(vec![ratio_verifier]).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_ORACLE_THRESHOLD.to_string(),
+        storage::new_uref(1u8).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_RETIRED_ORACLE_SIGNERS.to_string(),
+        storage::new_uref(Vec::<(PublicKey, PublicKey, u64)>::new()).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_PAUSED.to_string(),
+        storage::new_uref(0u8).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_CONTRACT_VERSION.to_string(),
+        storage::new_uref(CONTRACT_VERSION).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_GOVERNORS.to_string(),
+        storage::new_uref(Vec::<Key>::new()).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_GOVERNANCE_THRESHOLD.to_string(),
+        storage::new_uref(0u8).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_GOVERNANCE_DELAY.to_string(),
+        storage::new_uref(0u64).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_PROPOSALS_CNT.to_string(),
+        storage::new_uref(0u64).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_UPGRADE_AUTHORIZED.to_string(),
+        storage::new_uref(false).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_EVENTS_LENGTH.to_string(),
+        storage::new_uref(0u32).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_EVENTS_SCHEMA.to_string(),
+        storage::new_uref(get_schemas()).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_EVENTS_CES_VERSION.to_string(),
+        storage::new_uref(CES_VERSION.to_string()).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_ORDERS_CNT.to_string(),
+        storage::new_uref(0u64).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_PROVENANCE_CNT.to_string(),
+        storage::new_uref(0u64).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_CEP85_NAME.to_string(),
+        storage::new_uref(CEP85_COLLECTION_NAME.to_string()).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_CEP85_URI.to_string(),
+        storage::new_uref(alloc::string::String::new()).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_SCHEMA_VERSION.to_string(),
+        storage::new_uref(SCHEMA_VERSION).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_TREASURY.to_string(),
+        storage::new_uref(treasury).into(),
     );
     named_keys.insert(RUNTIME_FEE.to_string(), storage::new_uref(fee).into());
+    named_keys.insert(
+        NAMED_KEY_PENDING_FEE.to_string(),
+        storage::new_uref(Option::<(u64, u64)>::None).into(),
+    );
+    named_keys.insert(
+        NAMED_KEY_QUOTE_FRESHNESS.to_string(),
+        storage::new_uref(quote_freshness).into(),
+    );
+    named_keys.insert(NAMED_KEY_OWNER.to_string(), storage::new_uref(owner).into());
+    named_keys.insert(
+        NAMED_KEY_PENDING_OWNER.to_string(),
+        storage::new_uref(Option::<Key>::None).into(),
+    );
 
     named_keys
 }
@@ -1,16 +1,37 @@
 use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
     format,
     string::{String, ToString},
     vec::Vec,
 };
-use casper_contract::contract_api::storage;
-use casper_types::{account::AccountHash, URef, U512};
+use casper_contract::{contract_api::storage, unwrap_or_revert::UnwrapOrRevert};
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{self, Bytes, ToBytes},
+    CLType, CLTyped, Key, PublicKey, U512,
+};
+
+use crate::{
+    constants::{NAMED_KEY_DICT_EVENTS, NAMED_KEY_EVENTS_LENGTH},
+    ndpc_utils::get_named_key_by_name,
+    Error,
+};
+
+/// Version of the Casper Event Standard that the events are emitted with
+pub const CES_VERSION: &str = "0.1.0";
+/// Prefix of the event names in the serialized events
+const EVENT_PREFIX: &str = "event_";
+/// Suffix of the names of the events whose parties are keys (from version 6 for the marketplace events, and from version 10 for the roles).
+/// The events emitted before, with account hashes, keep their names without it, so both layouts could be decoded
+const KEY_LAYOUT_SUFFIX: &str = "V2";
 
-use crate::{constants, ndpc_utils::contract_package_hash};
-/// Droplinked Events, Including Mint(Record), PublishRequest, ApprovedPublish, DisapprovedPublish, CancelRequest, Buy, and Payment
+/// Droplinked Events, Including Mint(Record), PublishRequest, ApprovedPublish, DisapprovedPublish, CancelRequest, Buy, Settlement, Payment, ConfigChanged, FeeChanged, OracleSignerAdded, OracleSignerRemoved, OracleSignerRotated, PausedChanged, ContractMigrated, StorageMigrated, Withdrawn, Compacted
+/// the access control events (OwnershipTransferStarted, OwnershipTransferred, OperatorGranted, OperatorRevoked)
+/// and the governance events (GovernanceChanged, ProposalCreated, ProposalApproved, ProposalExecuted, ProposalCancelled)
 pub enum DropLinkedEvent {
     Mint {
-        recipient: AccountHash,
+        recipient: Key,
         token_id: u64,
         holder_id: u64,
         amount: u64,
@@ -18,8 +39,8 @@ pub enum DropLinkedEvent {
         price: u64,
     },
     PublishRequest {
-        owner: AccountHash,
-        publisher: AccountHash,
+        owner: Key,
+        publisher: Key,
         amount: u64,
         holder_id: u64,
         request_id: u64,
@@ -30,6 +51,7 @@ pub enum DropLinkedEvent {
     },
     DisapprovedPublish {
         approved_id: u64,
+        amount: u64,
     },
     CancelRequest {
         request_id: u64,
@@ -37,109 +59,628 @@ pub enum DropLinkedEvent {
     Buy {
         amount: u64,
         approved_id: u64,
-        buyer: AccountHash,
+        buyer: Key,
+    },
+    Settlement {
+        order_id: u64,
+        buyer: Key,
+        approved_id: u64,
+        token_id: u64,
+        amount: u64,
+        product_price: u64,
+        ratio: u64,
+        ratio_decimals: u8,
+        shipping_price: u64,
+        tax_price: u64,
+        fee: u64,
+        producer: Key,
+        producer_amount: u64,
+        publisher: Key,
+        publisher_amount: u64,
+        treasury_amount: u64,
+        refund: U512,
     },
     Payment {
-        recipient: String,
-        amounts: Vec<U512>,
+        payer: Key,
+        recipient: PublicKey,
+        product_price: U512,
+        shipping_price: U512,
+        tax_price: U512,
+        fee: u64,
+        treasury_amount: U512,
+        recipient_amount: U512,
+        order_reference: Option<String>,
+    },
+    ConfigChanged {
+        name: String,
+        old_value: String,
+        new_value: String,
+    },
+    FeeChanged {
+        old_fee: u64,
+        new_fee: u64,
+        effective_at: u64,
+    },
+    OracleSignerAdded {
+        public_key: PublicKey,
+    },
+    OracleSignerRemoved {
+        public_key: PublicKey,
+    },
+    OracleSignerRotated {
+        old_public_key: PublicKey,
+        new_public_key: PublicKey,
+        grace_until: u64,
+    },
+    PausedChanged {
+        old_paused: u8,
+        new_paused: u8,
+    },
+    ContractMigrated {
+        old_version: u32,
+        new_version: u32,
+    },
+    StorageMigrated {
+        old_version: u32,
+        new_version: u32,
+    },
+    Withdrawn {
+        recipient: Key,
+        amount: U512,
+    },
+    Compacted {
+        holder_ids: Vec<u64>,
+        approved_ids: Vec<u64>,
+        request_ids: Vec<u64>,
+    },
+    OwnershipTransferStarted {
+        previous_owner: Key,
+        new_owner: Key,
+    },
+    OwnershipTransferred {
+        previous_owner: Key,
+        new_owner: Key,
+    },
+    OperatorGranted {
+        account: Key,
+    },
+    OperatorRevoked {
+        account: Key,
+    },
+    GovernanceChanged {
+        governors: Vec<Key>,
+        threshold: u8,
+        delay: u64,
+    },
+    ProposalCreated {
+        proposal_id: u64,
+        proposer: Key,
+        action: String,
+    },
+    ProposalApproved {
+        proposal_id: u64,
+        governor: Key,
+        executable_at: Option<u64>,
+    },
+    ProposalExecuted {
+        proposal_id: u64,
+    },
+    ProposalCancelled {
+        proposal_id: u64,
+        governor: Key,
     },
 }
 
-/// Emits the given event into the urefs that contract creates, and would be detected by droplinked's Qserver when the transaction is done
-/// 
-/// It gets a DroplinkedEvent, and depending on its type, puts some key-value variables into the urefs that contract creates, these then 
-/// woule be detected by droplinked's Qserver (using the contractpackagehash on each dictionary)
-pub fn emit(event: DropLinkedEvent) {
-    let mut events = Vec::new();
-    let package = contract_package_hash();
-    match event {
-        DropLinkedEvent::Mint {
-            recipient,
-            token_id,
-            holder_id,
-            amount,
-            comission,
-            price,
-        } => {
-            let mut param = alloc::collections::BTreeMap::new();
-            param.insert(constants::CONTRACTPACKAGEHASH, package.to_string());
-            param.insert("event_type", "droplinked_mint".to_string());
-            param.insert("recipient", recipient.to_string());
-            param.insert("token_id", token_id.to_string());
-            param.insert("holder_id", holder_id.to_string());
-            param.insert("amount", amount.to_string());
-            param.insert("comission", comission.to_string());
-            param.insert("price", price.to_string());
-            events.push(param);
-        }
-        DropLinkedEvent::PublishRequest {
-            owner,
-            publisher,
-            amount,
-            holder_id,
-            request_id,
-        } => {
-            let mut param = alloc::collections::BTreeMap::new();
-            param.insert(constants::CONTRACTPACKAGEHASH, package.to_string());
-            param.insert("event_type", "droplinked_publish_request".to_string());
-            param.insert("owner", owner.to_string());
-            param.insert("publisher", publisher.to_string());
-            param.insert("amount", amount.to_string());
-            param.insert("holder_id", holder_id.to_string());
-            param.insert("request_id", request_id.to_string());
-            events.push(param);
-        }
-        DropLinkedEvent::DisapprovedPublish { approved_id } => {
-            let mut param = alloc::collections::BTreeMap::new();
-            param.insert(constants::CONTRACTPACKAGEHASH, package.to_string());
-            param.insert("event_type", "droplinked_disapproved_publish".to_string());
-            param.insert("approved_id", approved_id.to_string());
-            events.push(param);
-        }
-        DropLinkedEvent::CancelRequest { request_id } => {
-            let mut param = alloc::collections::BTreeMap::new();
-            param.insert(constants::CONTRACTPACKAGEHASH, package.to_string());
-            param.insert("event_type", "droplinked_cancel_request".to_string());
-            param.insert("request_id", request_id.to_string());
-            events.push(param);
-        }
-        DropLinkedEvent::ApprovedPublish {
-            request_id,
-            approved_id,
-        } => {
-            let mut param = alloc::collections::BTreeMap::new();
-            param.insert(constants::CONTRACTPACKAGEHASH, package.to_string());
-            param.insert("event_type", "droplinked_approved_publish".to_string());
-            param.insert("request_id", request_id.to_string());
-            param.insert("approved_id", approved_id.to_string());
-            events.push(param);
+impl DropLinkedEvent {
+    /// Returns the name of the event, as it is declared in the event schemas (with `KEY_LAYOUT_SUFFIX` if it has parties)
+    pub fn name(&self) -> &str {
+        match self {
+            DropLinkedEvent::Mint { .. } => "MintV2",
+            DropLinkedEvent::PublishRequest { .. } => "PublishRequestV2",
+            DropLinkedEvent::ApprovedPublish { .. } => "ApprovedPublish",
+            DropLinkedEvent::DisapprovedPublish { .. } => "DisapprovedPublish",
+            DropLinkedEvent::CancelRequest { .. } => "CancelRequest",
+            DropLinkedEvent::Buy { .. } => "BuyV2",
+            DropLinkedEvent::Settlement { .. } => "SettlementV2",
+            DropLinkedEvent::Payment { .. } => "PaymentV2",
+            DropLinkedEvent::ConfigChanged { .. } => "ConfigChanged",
+            DropLinkedEvent::FeeChanged { .. } => "FeeChanged",
+            DropLinkedEvent::OracleSignerAdded { .. } => "OracleSignerAdded",
+            DropLinkedEvent::OracleSignerRemoved { .. } => "OracleSignerRemoved",
+            DropLinkedEvent::OracleSignerRotated { .. } => "OracleSignerRotated",
+            DropLinkedEvent::PausedChanged { .. } => "PausedChanged",
+            DropLinkedEvent::ContractMigrated { .. } => "ContractMigrated",
+            DropLinkedEvent::StorageMigrated { .. } => "StorageMigrated",
+            DropLinkedEvent::Withdrawn { .. } => "Withdrawn",
+            DropLinkedEvent::Compacted { .. } => "Compacted",
+            DropLinkedEvent::OwnershipTransferStarted { .. } => "OwnershipTransferStartedV2",
+            DropLinkedEvent::OwnershipTransferred { .. } => "OwnershipTransferredV2",
+            DropLinkedEvent::OperatorGranted { .. } => "OperatorGrantedV2",
+            DropLinkedEvent::OperatorRevoked { .. } => "OperatorRevokedV2",
+            DropLinkedEvent::GovernanceChanged { .. } => "GovernanceChangedV2",
+            DropLinkedEvent::ProposalCreated { .. } => "ProposalCreatedV2",
+            DropLinkedEvent::ProposalApproved { .. } => "ProposalApprovedV2",
+            DropLinkedEvent::ProposalExecuted { .. } => "ProposalExecuted",
+            DropLinkedEvent::ProposalCancelled { .. } => "ProposalCancelledV2",
         }
-        DropLinkedEvent::Buy {
-            amount,
-            approved_id,
-            buyer,
-        } => {
-            let mut param = alloc::collections::BTreeMap::new();
-            param.insert(constants::CONTRACTPACKAGEHASH, package.to_string());
-            param.insert("event_type", "droplinked_buy".to_string());
-            param.insert("amount", amount.to_string());
-            param.insert("approved_id", approved_id.to_string());
-            param.insert("buyer", buyer.to_string());
-            events.push(param);
-        }
-        DropLinkedEvent::Payment { recipient, amounts } => {
-            let mut param = alloc::collections::BTreeMap::new();
-            param.insert(constants::CONTRACTPACKAGEHASH, package.to_string());
-            param.insert("event_type", "droplinked_direct_pay".to_string());
-            param.insert(
-                "amounts",
-                format!("{},{},{}", amounts[0], amounts[1], amounts[2]),
-            );
-            param.insert("recipient", recipient);
-            events.push(param);
+    }
+}
+
+/// Serializes the event as the Casper Event Standard does : the prefixed name of the event (`event_<Name>`) followed by its fields, in the order of its schema
+impl ToBytes for DropLinkedEvent {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut result = Vec::new();
+        result.append(&mut format!("{}{}", EVENT_PREFIX, self.name()).to_bytes()?);
+        match self {
+            DropLinkedEvent::Mint {
+                recipient,
+                token_id,
+                holder_id,
+                amount,
+                comission,
+                price,
+            } => {
+                result.append(&mut recipient.to_bytes()?);
+                result.append(&mut token_id.to_bytes()?);
+                result.append(&mut holder_id.to_bytes()?);
+                result.append(&mut amount.to_bytes()?);
+                result.append(&mut comission.to_bytes()?);
+                result.append(&mut price.to_bytes()?);
+            }
+            DropLinkedEvent::PublishRequest {
+                owner,
+                publisher,
+                amount,
+                holder_id,
+                request_id,
+            } => {
+                result.append(&mut owner.to_bytes()?);
+                result.append(&mut publisher.to_bytes()?);
+                result.append(&mut amount.to_bytes()?);
+                result.append(&mut holder_id.to_bytes()?);
+                result.append(&mut request_id.to_bytes()?);
+            }
+            DropLinkedEvent::ApprovedPublish {
+                request_id,
+                approved_id,
+            } => {
+                result.append(&mut request_id.to_bytes()?);
+                result.append(&mut approved_id.to_bytes()?);
+            }
+            DropLinkedEvent::DisapprovedPublish {
+                approved_id,
+                amount,
+            } => {
+                result.append(&mut approved_id.to_bytes()?);
+                result.append(&mut amount.to_bytes()?);
+            }
+            DropLinkedEvent::CancelRequest { request_id } => {
+                result.append(&mut request_id.to_bytes()?);
+            }
+            DropLinkedEvent::Buy {
+                amount,
+                approved_id,
+                buyer,
+            } => {
+                result.append(&mut amount.to_bytes()?);
+                result.append(&mut approved_id.to_bytes()?);
+                result.append(&mut buyer.to_bytes()?);
+            }
+            DropLinkedEvent::Settlement {
+                order_id,
+                buyer,
+                approved_id,
+                token_id,
+                amount,
+                product_price,
+                ratio,
+                ratio_decimals,
+                shipping_price,
+                tax_price,
+                fee,
+                producer,
+                producer_amount,
+                publisher,
+                publisher_amount,
+                treasury_amount,
+                refund,
+            } => {
+                result.append(&mut order_id.to_bytes()?);
+                result.append(&mut buyer.to_bytes()?);
+                result.append(&mut approved_id.to_bytes()?);
+                result.append(&mut token_id.to_bytes()?);
+                result.append(&mut amount.to_bytes()?);
+                result.append(&mut product_price.to_bytes()?);
+                result.append(&mut ratio.to_bytes()?);
+                result.append(&mut ratio_decimals.to_bytes()?);
+                result.append(&mut shipping_price.to_bytes()?);
+                result.append(&mut tax_price.to_bytes()?);
+                result.append(&mut fee.to_bytes()?);
+                result.append(&mut producer.to_bytes()?);
+                result.append(&mut producer_amount.to_bytes()?);
+                result.append(&mut publisher.to_bytes()?);
+                result.append(&mut publisher_amount.to_bytes()?);
+                result.append(&mut treasury_amount.to_bytes()?);
+                result.append(&mut refund.to_bytes()?);
+            }
+            DropLinkedEvent::Payment {
+                payer,
+                recipient,
+                product_price,
+                shipping_price,
+                tax_price,
+                fee,
+                treasury_amount,
+                recipient_amount,
+                order_reference,
+            } => {
+                result.append(&mut payer.to_bytes()?);
+                result.append(&mut recipient.to_bytes()?);
+                result.append(&mut product_price.to_bytes()?);
+                result.append(&mut shipping_price.to_bytes()?);
+                result.append(&mut tax_price.to_bytes()?);
+                result.append(&mut fee.to_bytes()?);
+                result.append(&mut treasury_amount.to_bytes()?);
+                result.append(&mut recipient_amount.to_bytes()?);
+                result.append(&mut order_reference.to_bytes()?);
+            }
+            DropLinkedEvent::ConfigChanged {
+                name,
+                old_value,
+                new_value,
+            } => {
+                result.append(&mut name.to_bytes()?);
+                result.append(&mut old_value.to_bytes()?);
+                result.append(&mut new_value.to_bytes()?);
+            }
+            DropLinkedEvent::FeeChanged {
+                old_fee,
+                new_fee,
+                effective_at,
+            } => {
+                result.append(&mut old_fee.to_bytes()?);
+                result.append(&mut new_fee.to_bytes()?);
+                result.append(&mut effective_at.to_bytes()?);
+            }
+            DropLinkedEvent::OracleSignerAdded { public_key }
+            | DropLinkedEvent::OracleSignerRemoved { public_key } => {
+                result.append(&mut public_key.to_bytes()?);
+            }
+            DropLinkedEvent::OracleSignerRotated {
+                old_public_key,
+                new_public_key,
+                grace_until,
+            } => {
+                result.append(&mut old_public_key.to_bytes()?);
+                result.append(&mut new_public_key.to_bytes()?);
+                result.append(&mut grace_until.to_bytes()?);
+            }
+            DropLinkedEvent::PausedChanged {
+                old_paused,
+                new_paused,
+            } => {
+                result.append(&mut old_paused.to_bytes()?);
+                result.append(&mut new_paused.to_bytes()?);
+            }
+            DropLinkedEvent::ContractMigrated {
+                old_version,
+                new_version,
+            }
+            | DropLinkedEvent::StorageMigrated {
+                old_version,
+                new_version,
+            } => {
+                result.append(&mut old_version.to_bytes()?);
+                result.append(&mut new_version.to_bytes()?);
+            }
+            DropLinkedEvent::Withdrawn { recipient, amount } => {
+                result.append(&mut recipient.to_bytes()?);
+                result.append(&mut amount.to_bytes()?);
+            }
+            DropLinkedEvent::Compacted {
+                holder_ids,
+                approved_ids,
+                request_ids,
+            } => {
+                result.append(&mut holder_ids.to_bytes()?);
+                result.append(&mut approved_ids.to_bytes()?);
+                result.append(&mut request_ids.to_bytes()?);
+            }
+            DropLinkedEvent::OwnershipTransferStarted {
+                previous_owner,
+                new_owner,
+            }
+            | DropLinkedEvent::OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            } => {
+                result.append(&mut previous_owner.to_bytes()?);
+                result.append(&mut new_owner.to_bytes()?);
+            }
+            DropLinkedEvent::OperatorGranted { account }
+            | DropLinkedEvent::OperatorRevoked { account } => {
+                result.append(&mut account.to_bytes()?);
+            }
+            DropLinkedEvent::GovernanceChanged {
+                governors,
+                threshold,
+                delay,
+            } => {
+                result.append(&mut governors.to_bytes()?);
+                result.append(&mut threshold.to_bytes()?);
+                result.append(&mut delay.to_bytes()?);
+            }
+            DropLinkedEvent::ProposalCreated {
+                proposal_id,
+                proposer,
+                action,
+            } => {
+                result.append(&mut proposal_id.to_bytes()?);
+                result.append(&mut proposer.to_bytes()?);
+                result.append(&mut action.to_bytes()?);
+            }
+            DropLinkedEvent::ProposalApproved {
+                proposal_id,
+                governor,
+                executable_at,
+            } => {
+                result.append(&mut proposal_id.to_bytes()?);
+                result.append(&mut governor.to_bytes()?);
+                result.append(&mut executable_at.to_bytes()?);
+            }
+            DropLinkedEvent::ProposalExecuted { proposal_id } => {
+                result.append(&mut proposal_id.to_bytes()?);
+            }
+            DropLinkedEvent::ProposalCancelled {
+                proposal_id,
+                governor,
+            } => {
+                result.append(&mut proposal_id.to_bytes()?);
+                result.append(&mut governor.to_bytes()?);
+            }
         }
+        Ok(result)
+    }
+    fn serialized_length(&self) -> usize {
+        self.to_bytes().map(|bytes| bytes.len()).unwrap_or_default()
+    }
+}
+
+/// The schemas of the events, a map from the event name to its (field name, field type) list, stored in `__events_schema`
+pub struct Schemas(pub BTreeMap<String, Vec<(String, CLType)>>);
+
+impl ToBytes for Schemas {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
     }
-    for param in events {
-        let _: URef = storage::new_uref(param);
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
     }
 }
+impl CLTyped for Schemas {
+    fn cl_type() -> CLType {
+        CLType::Any
+    }
+}
+
+/// Returns the schemas of all the droplinked events, which are stored at install (and rewritten on upgrade) so that the indexers could decode the events
+///
+/// The events whose parties are keys are declared under their names with `KEY_LAYOUT_SUFFIX`, and under their names without it with account hashes
+/// in place of the keys, as the older versions emitted them, so the schemas cover every event of the `__events` dictionary
+pub fn get_schemas() -> Schemas {
+    let key = Key::cl_type;
+    let events: Vec<(&str, Vec<(&str, CLType)>)> = alloc::vec![
+        (
+            "MintV2",
+            alloc::vec![
+                ("recipient", key()),
+                ("token_id", u64::cl_type()),
+                ("holder_id", u64::cl_type()),
+                ("amount", u64::cl_type()),
+                ("comission", u64::cl_type()),
+                ("price", u64::cl_type()),
+            ],
+        ),
+        (
+            "PublishRequestV2",
+            alloc::vec![
+                ("owner", key()),
+                ("publisher", key()),
+                ("amount", u64::cl_type()),
+                ("holder_id", u64::cl_type()),
+                ("request_id", u64::cl_type()),
+            ],
+        ),
+        (
+            "ApprovedPublish",
+            alloc::vec![("request_id", u64::cl_type()), ("approved_id", u64::cl_type())],
+        ),
+        (
+            "DisapprovedPublish",
+            alloc::vec![("approved_id", u64::cl_type()), ("amount", u64::cl_type())],
+        ),
+        ("CancelRequest", alloc::vec![("request_id", u64::cl_type())]),
+        (
+            "BuyV2",
+            alloc::vec![
+                ("amount", u64::cl_type()),
+                ("approved_id", u64::cl_type()),
+                ("buyer", key()),
+            ],
+        ),
+        (
+            "SettlementV2",
+            alloc::vec![
+                ("order_id", u64::cl_type()),
+                ("buyer", key()),
+                ("approved_id", u64::cl_type()),
+                ("token_id", u64::cl_type()),
+                ("amount", u64::cl_type()),
+                ("product_price", u64::cl_type()),
+                ("ratio", u64::cl_type()),
+                ("ratio_decimals", u8::cl_type()),
+                ("shipping_price", u64::cl_type()),
+                ("tax_price", u64::cl_type()),
+                ("fee", u64::cl_type()),
+                ("producer", key()),
+                ("producer_amount", u64::cl_type()),
+                ("publisher", key()),
+                ("publisher_amount", u64::cl_type()),
+                ("treasury_amount", u64::cl_type()),
+                ("refund", U512::cl_type()),
+            ],
+        ),
+        (
+            "PaymentV2",
+            alloc::vec![
+                ("payer", key()),
+                ("recipient", PublicKey::cl_type()),
+                ("product_price", U512::cl_type()),
+                ("shipping_price", U512::cl_type()),
+                ("tax_price", U512::cl_type()),
+                ("fee", u64::cl_type()),
+                ("treasury_amount", U512::cl_type()),
+                ("recipient_amount", U512::cl_type()),
+                ("order_reference", Option::<String>::cl_type()),
+            ],
+        ),
+        (
+            "ConfigChanged",
+            alloc::vec![
+                ("name", String::cl_type()),
+                ("old_value", String::cl_type()),
+                ("new_value", String::cl_type()),
+            ],
+        ),
+        (
+            "FeeChanged",
+            alloc::vec![
+                ("old_fee", u64::cl_type()),
+                ("new_fee", u64::cl_type()),
+                ("effective_at", u64::cl_type()),
+            ],
+        ),
+        ("OracleSignerAdded", alloc::vec![("public_key", PublicKey::cl_type())]),
+        ("OracleSignerRemoved", alloc::vec![("public_key", PublicKey::cl_type())]),
+        (
+            "OracleSignerRotated",
+            alloc::vec![
+                ("old_public_key", PublicKey::cl_type()),
+                ("new_public_key", PublicKey::cl_type()),
+                ("grace_until", u64::cl_type()),
+            ],
+        ),
+        (
+            "PausedChanged",
+            alloc::vec![("old_paused", u8::cl_type()), ("new_paused", u8::cl_type())],
+        ),
+        (
+            "ContractMigrated",
+            alloc::vec![("old_version", u32::cl_type()), ("new_version", u32::cl_type())],
+        ),
+        (
+            "StorageMigrated",
+            alloc::vec![("old_version", u32::cl_type()), ("new_version", u32::cl_type())],
+        ),
+        ("Withdrawn", alloc::vec![("recipient", key()), ("amount", U512::cl_type())]),
+        (
+            "Compacted",
+            alloc::vec![
+                ("holder_ids", Vec::<u64>::cl_type()),
+                ("approved_ids", Vec::<u64>::cl_type()),
+                ("request_ids", Vec::<u64>::cl_type()),
+            ],
+        ),
+        (
+            "OwnershipTransferStartedV2",
+            alloc::vec![("previous_owner", key()), ("new_owner", key())],
+        ),
+        (
+            "OwnershipTransferredV2",
+            alloc::vec![("previous_owner", key()), ("new_owner", key())],
+        ),
+        ("OperatorGrantedV2", alloc::vec![("account", key())]),
+        ("OperatorRevokedV2", alloc::vec![("account", key())]),
+        (
+            "GovernanceChangedV2",
+            alloc::vec![
+                ("governors", Vec::<Key>::cl_type()),
+                ("threshold", u8::cl_type()),
+                ("delay", u64::cl_type()),
+            ],
+        ),
+        (
+            "ProposalCreatedV2",
+            alloc::vec![
+                ("proposal_id", u64::cl_type()),
+                ("proposer", key()),
+                ("action", String::cl_type()),
+            ],
+        ),
+        (
+            "ProposalApprovedV2",
+            alloc::vec![
+                ("proposal_id", u64::cl_type()),
+                ("governor", key()),
+                ("executable_at", Option::<u64>::cl_type()),
+            ],
+        ),
+        ("ProposalExecuted", alloc::vec![("proposal_id", u64::cl_type())]),
+        (
+            "ProposalCancelledV2",
+            alloc::vec![("proposal_id", u64::cl_type()), ("governor", key())],
+        ),
+    ];
+    let mut schemas: BTreeMap<String, Vec<(String, CLType)>> = events
+        .into_iter()
+        .map(|(name, fields)| {
+            (
+                name.to_string(),
+                fields
+                    .into_iter()
+                    .map(|(field, cl_type)| (field.to_string(), cl_type))
+                    .collect(),
+            )
+        })
+        .collect();
+    let legacy_schemas: Vec<(String, Vec<(String, CLType)>)> = schemas
+        .iter()
+        .filter_map(|(name, fields)| {
+            let legacy_name = name.strip_suffix(KEY_LAYOUT_SUFFIX)?;
+            let legacy_fields = fields
+                .iter()
+                .map(|(field, cl_type)| (field.clone(), account_layout(cl_type)))
+                .collect();
+            Some((legacy_name.to_string(), legacy_fields))
+        })
+        .collect();
+    schemas.extend(legacy_schemas);
+    Schemas(schemas)
+}
+
+/// Returns the type of a field as the events emitted before `KEY_LAYOUT_SUFFIX` had it : an account hash in place of a key
+fn account_layout(cl_type: &CLType) -> CLType {
+    match cl_type {
+        CLType::Key => AccountHash::cl_type(),
+        CLType::List(item) => CLType::List(Box::new(account_layout(item))),
+        other => other.clone(),
+    }
+}
+
+/// Emits the given event as a Casper Event Standard event
+///
+/// It serializes the event, puts it into the `__events` dictionary with the next sequential index (`__events_length`) as its key, and increments `__events_length`.
+/// The indexers (like droplinked's Qserver) could decode it with the schemas that are stored in `__events_schema`
+pub fn emit(event: DropLinkedEvent) {
+    let events_length_uref = get_named_key_by_name(NAMED_KEY_EVENTS_LENGTH);
+    let events_length: u32 = storage::read(events_length_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    let event_bytes: Bytes = event.to_bytes().unwrap_or_revert().into();
+    storage::dictionary_put(
+        get_named_key_by_name(NAMED_KEY_DICT_EVENTS),
+        events_length.to_string().as_str(),
+        event_bytes,
+    );
+    storage::write(events_length_uref, events_length + 1);
+}
@@ -0,0 +1,325 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use casper_contract::{
+    contract_api::{
+        runtime::{self, get_blocktime, revert},
+        storage,
+    },
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{bytesrepr::FromBytes, CLValue, Key};
+
+use crate::{
+    access_control::only_owner,
+    admin::{
+        apply_add_oracle_signer, apply_remove_oracle_signer, apply_rotate_oracle_signer,
+        apply_set_fee, apply_set_oracle_threshold, apply_set_treasury,
+    },
+    constants::{
+        MAX_GOVERNANCE_DELAY, MAX_GOVERNORS, MIN_GOVERNANCE_DELAY, NAMED_KEY_DICT_PROPOSALS,
+        NAMED_KEY_GOVERNANCE_DELAY, NAMED_KEY_GOVERNANCE_THRESHOLD, NAMED_KEY_GOVERNORS,
+        NAMED_KEY_PROPOSALS_CNT, NAMED_KEY_UPGRADE_AUTHORIZED, RUNTIME_ARG_ACTION,
+        RUNTIME_ARG_DELAY, RUNTIME_ARG_GOVERNORS, RUNTIME_ARG_PROPOSAL_ID, RUNTIME_ARG_THRESHOLD,
+    },
+    event::{emit, DropLinkedEvent},
+    ndpc_types::{
+        GovernanceAction, Proposal, PROPOSAL_CANCELLED, PROPOSAL_EXECUTED, PROPOSAL_PENDING,
+    },
+    ndpc_utils::{get_immediate_caller, get_named_key_by_name},
+    Error,
+};
+
+/// A getter function, which returns the governors, accounts or contract packages (empty while governance is not configured)
+pub fn get_governors() -> Vec<Key> {
+    storage::read(get_named_key_by_name(NAMED_KEY_GOVERNORS))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// A getter function, which returns the number of governors that should approve a proposal
+pub fn get_governance_threshold() -> u8 {
+    storage::read(get_named_key_by_name(NAMED_KEY_GOVERNANCE_THRESHOLD))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// A getter function, which returns the time (in ms) that an approved proposal should wait before it could be executed
+pub fn get_governance_delay() -> u64 {
+    storage::read(get_named_key_by_name(NAMED_KEY_GOVERNANCE_DELAY))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// Returns true if governors are set, in which case the sensitive settings could only be changed by the executed proposals
+pub fn is_governance_configured() -> bool {
+    !get_governors().is_empty()
+}
+
+/// Reverts with `GovernanceRequired` if governance is configured, used by the sensitive admin entrypoints
+pub fn without_governance() {
+    if is_governance_configured() {
+        revert(Error::GovernanceRequired);
+    }
+}
+
+/// Reverts with `NotGovernor` if the immediate caller is not a governor, and returns the caller
+fn only_governor() -> Key {
+    let caller = get_immediate_caller();
+    if !get_governors().contains(&caller) {
+        revert(Error::NotGovernor);
+    }
+    caller
+}
+
+/// Consumes the upgrade authorization of an executed `AuthorizeUpgrade` proposal
+///
+/// It does nothing if governance is not configured, otherwise it reverts with `UpgradeNotAuthorized` if there is no authorization to consume
+pub(crate) fn consume_upgrade_authorization() {
+    if !is_governance_configured() {
+        return;
+    }
+    let upgrade_authorized_uref = get_named_key_by_name(NAMED_KEY_UPGRADE_AUTHORIZED);
+    let upgrade_authorized: bool = storage::read(upgrade_authorized_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    if !upgrade_authorized {
+        revert(Error::UpgradeNotAuthorized);
+    }
+    storage::write(upgrade_authorized_uref, false);
+}
+
+/// Gets the proposal with the given id, reverts with `ProposalNotFound` if it does not exist
+fn get_proposal(proposal_id: u64) -> Proposal {
+    storage::dictionary_get(
+        get_named_key_by_name(NAMED_KEY_DICT_PROPOSALS),
+        proposal_id.to_string().as_str(),
+    )
+    .unwrap_or_revert()
+    .unwrap_or_revert_with(Error::ProposalNotFound)
+}
+
+/// Gets the pending proposal with the given id, reverts with `ProposalNotPending` if it is executed or cancelled
+fn get_pending_proposal(proposal_id: u64) -> Proposal {
+    let proposal = get_proposal(proposal_id);
+    if proposal.status != PROPOSAL_PENDING {
+        revert(Error::ProposalNotPending);
+    }
+    proposal
+}
+
+fn put_proposal(proposal_id: u64, proposal: Proposal) {
+    storage::dictionary_put(
+        get_named_key_by_name(NAMED_KEY_DICT_PROPOSALS),
+        proposal_id.to_string().as_str(),
+        proposal,
+    );
+}
+
+/// Returns the number of approvals of the proposal that are from the current governors
+fn count_approvals(proposal: &Proposal, governors: &[Key]) -> usize {
+    proposal
+        .approvals
+        .iter()
+        .filter(|approval| governors.contains(approval))
+        .count()
+}
+
+/// Sets `executable_at` of the proposal when its approvals reach the threshold, and returns it
+fn queue_if_approved(proposal: &mut Proposal) -> Option<u64> {
+    if proposal.executable_at.is_none()
+        && count_approvals(proposal, &get_governors()) >= get_governance_threshold() as usize
+    {
+        proposal.executable_at = Some(u64::from(get_blocktime()) + get_governance_delay());
+    }
+    proposal.executable_at
+}
+
+/// Validates and stores the governors, the threshold and the delay
+///
+/// It reverts with `InvalidGovernance` if there are no governors, more than `MAX_GOVERNORS`, repeated ones or ones that are neither an account nor a contract package,
+/// if the threshold is 0 or more than the governors, or if the delay is less than `MIN_GOVERNANCE_DELAY` or more than `MAX_GOVERNANCE_DELAY` (so an approved proposal always waits before it is executed)
+fn apply_set_governance(governors: Vec<Key>, threshold: u8, delay: u64) {
+    if governors.is_empty() || governors.len() > MAX_GOVERNORS {
+        revert(Error::InvalidGovernance);
+    }
+    for (index, governor) in governors.iter().enumerate() {
+        if !matches!(governor, Key::Account(_) | Key::Hash(_))
+            || governors[..index].contains(governor)
+        {
+            revert(Error::InvalidGovernance);
+        }
+    }
+    if threshold == 0
+        || threshold as usize > governors.len()
+        || !(MIN_GOVERNANCE_DELAY..=MAX_GOVERNANCE_DELAY).contains(&delay)
+    {
+        revert(Error::InvalidGovernance);
+    }
+    storage::write(get_named_key_by_name(NAMED_KEY_GOVERNORS), governors.clone());
+    storage::write(get_named_key_by_name(NAMED_KEY_GOVERNANCE_THRESHOLD), threshold);
+    storage::write(get_named_key_by_name(NAMED_KEY_GOVERNANCE_DELAY), delay);
+    emit(DropLinkedEvent::GovernanceChanged {
+        governors,
+        threshold,
+        delay,
+    });
+}
+
+/// set_governors entrypoint of the contract
+///
+/// Gets `governors` (a list of account or contract package keys), `threshold` and `delay` (in ms) from the runtime args and configures governance with them.
+/// From then on `set_fee`, `set_treasury`, the oracle signer entrypoints and contract upgrades need an executed proposal, and the governors could only be changed by a proposal.
+/// It would revert if the caller is not the owner, if governance is already configured, or if the governance is not valid
+/// # Emits
+/// `DropLinkedEvent::GovernanceChanged`
+#[no_mangle]
+pub extern "C" fn set_governors() {
+    only_owner();
+    without_governance();
+    apply_set_governance(
+        runtime::get_named_arg(RUNTIME_ARG_GOVERNORS),
+        runtime::get_named_arg(RUNTIME_ARG_THRESHOLD),
+        runtime::get_named_arg(RUNTIME_ARG_DELAY),
+    );
+}
+
+/// propose entrypoint of the contract
+///
+/// Gets `action` (a hex encoded, bytesrepr serialized GovernanceAction) from the runtime args, and stores it as a new proposal which is approved by the proposer.
+/// It would revert if the caller is not a governor, or if the action could not be decoded
+/// # Returns
+/// `proposal_id` : `u64`
+/// # Emits
+/// `DropLinkedEvent::ProposalCreated` (and `DropLinkedEvent::ProposalApproved` for the proposer)
+#[no_mangle]
+pub extern "C" fn propose() {
+    let proposer = only_governor();
+    let action_hex: String = runtime::get_named_arg(RUNTIME_ARG_ACTION);
+    let action_bytes =
+        base16::decode(action_hex.as_bytes()).ok().unwrap_or_revert_with(Error::MalformedAction);
+    let (action, rem) =
+        GovernanceAction::from_bytes(&action_bytes).unwrap_or_revert_with(Error::MalformedAction);
+    if !rem.is_empty() {
+        revert(Error::MalformedAction);
+    }
+    let proposals_cnt_uref = get_named_key_by_name(NAMED_KEY_PROPOSALS_CNT);
+    let proposal_id: u64 = storage::read::<u64>(proposals_cnt_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        + 1u64;
+    storage::write(proposals_cnt_uref, proposal_id);
+    emit(DropLinkedEvent::ProposalCreated {
+        proposal_id,
+        proposer,
+        action: action.name().to_string(),
+    });
+    let mut proposal = Proposal {
+        action,
+        proposer,
+        approvals: vec![proposer],
+        executable_at: None,
+        status: PROPOSAL_PENDING,
+    };
+    let executable_at = queue_if_approved(&mut proposal);
+    put_proposal(proposal_id, proposal);
+    emit(DropLinkedEvent::ProposalApproved {
+        proposal_id,
+        governor: proposer,
+        executable_at,
+    });
+    runtime::ret(CLValue::from_t(proposal_id).unwrap_or_revert());
+}
+
+/// approve_proposal entrypoint of the contract
+///
+/// Gets `proposal_id` from the runtime args and adds the caller to its approvals. When the approvals reach the threshold, the proposal is queued
+/// and could be executed after the governance delay. It would revert if the caller is not a governor, if the proposal is not pending, or if the caller already approved it
+/// # Emits
+/// `DropLinkedEvent::ProposalApproved`
+#[no_mangle]
+pub extern "C" fn approve_proposal() {
+    let governor = only_governor();
+    let proposal_id: u64 = runtime::get_named_arg(RUNTIME_ARG_PROPOSAL_ID);
+    let mut proposal = get_pending_proposal(proposal_id);
+    if proposal.approvals.contains(&governor) {
+        revert(Error::AlreadyApproved);
+    }
+    proposal.approvals.push(governor);
+    let executable_at = queue_if_approved(&mut proposal);
+    put_proposal(proposal_id, proposal);
+    emit(DropLinkedEvent::ProposalApproved {
+        proposal_id,
+        governor,
+        executable_at,
+    });
+}
+
+/// execute_proposal entrypoint of the contract
+///
+/// Gets `proposal_id` from the runtime args and applies its action. It would revert if the caller is not a governor, if the proposal is not pending,
+/// if it is not queued or its delay has not passed yet, if its approvals from the current governors are less than the threshold, or if the action itself is not valid
+/// # Emits
+/// `DropLinkedEvent::ProposalExecuted` (and the event of the applied action)
+#[no_mangle]
+pub extern "C" fn execute_proposal() {
+    only_governor();
+    let proposal_id: u64 = runtime::get_named_arg(RUNTIME_ARG_PROPOSAL_ID);
+    let mut proposal = get_pending_proposal(proposal_id);
+    let executable_at = proposal
+        .executable_at
+        .unwrap_or_revert_with(Error::ProposalNotReady);
+    if u64::from(get_blocktime()) < executable_at
+        || count_approvals(&proposal, &get_governors()) < get_governance_threshold() as usize
+    {
+        revert(Error::ProposalNotReady);
+    }
+    proposal.status = PROPOSAL_EXECUTED;
+    match &proposal.action {
+        GovernanceAction::SetFee { fee, effective_at } => apply_set_fee(*fee, *effective_at),
+        GovernanceAction::SetTreasury { treasury } => apply_set_treasury(*treasury),
+        GovernanceAction::AddOracleSigner { public_key } => {
+            apply_add_oracle_signer(public_key.clone())
+        }
+        GovernanceAction::RemoveOracleSigner { public_key } => {
+            apply_remove_oracle_signer(public_key.clone())
+        }
+        GovernanceAction::SetOracleThreshold { threshold } => apply_set_oracle_threshold(*threshold),
+        GovernanceAction::RotateOracleSigner {
+            public_key,
+            new_public_key,
+            grace_period,
+        } => apply_rotate_oracle_signer(public_key.clone(), new_public_key.clone(), *grace_period),
+        GovernanceAction::SetGovernance {
+            governors,
+            threshold,
+            delay,
+        } => apply_set_governance(governors.clone(), *threshold, *delay),
+        GovernanceAction::AuthorizeUpgrade => {
+            storage::write(get_named_key_by_name(NAMED_KEY_UPGRADE_AUTHORIZED), true)
+        }
+    }
+    put_proposal(proposal_id, proposal);
+    emit(DropLinkedEvent::ProposalExecuted { proposal_id });
+}
+
+/// cancel_proposal entrypoint of the contract
+///
+/// Gets `proposal_id` from the runtime args and cancels it, so that it could never be executed. Any governor could cancel a pending proposal.
+/// It would revert if the caller is not a governor, or if the proposal is not pending
+/// # Emits
+/// `DropLinkedEvent::ProposalCancelled`
+#[no_mangle]
+pub extern "C" fn cancel_proposal() {
+    let governor = only_governor();
+    let proposal_id: u64 = runtime::get_named_arg(RUNTIME_ARG_PROPOSAL_ID);
+    let mut proposal = get_pending_proposal(proposal_id);
+    proposal.status = PROPOSAL_CANCELLED;
+    put_proposal(proposal_id, proposal);
+    emit(DropLinkedEvent::ProposalCancelled {
+        proposal_id,
+        governor,
+    });
+}
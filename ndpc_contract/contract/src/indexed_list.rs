@@ -0,0 +1,244 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{CLValue, URef};
+
+use crate::{
+    constants::{
+        MAX_PAGE_SIZE, NAMED_KEY_DICT_BUYER_ORDERS, NAMED_KEY_DICT_OWNERS_NAME,
+        NAMED_KEY_DICT_PRODAPPROVED_NAME, NAMED_KEY_DICT_PRODUCER_ORDERS, NAMED_KEY_DICT_PROD_REQS,
+        NAMED_KEY_DICT_PUBAPPROVED_NAME, NAMED_KEY_DICT_PUBLISHER_ORDERS, NAMED_KEY_DICT_PUB_REQS,
+        RUNTIME_ARG_ACCOUNT, RUNTIME_ARG_LIMIT, RUNTIME_ARG_OFFSET,
+    },
+    ndpc_types::{AsStrized, U64list},
+    ndpc_utils::{get_named_key_by_name, get_party_arg},
+    Error,
+};
+
+/// A list of u64 ids (e.g. the holder ids of an account), stored in a dictionary as a length entry plus an entry per index, so a change only reads and writes a few entries
+///
+/// The entries are stored under the blake2b hashes of `<key>_len`, `<key>_<index>` and `<key>_pos_<id>` (the index of the id plus one, or 0 if it is not in the list), as dictionary keys
+/// are limited to 64 bytes. The ids keep their insertion order, except that removing an id moves the last id to its place (swap-remove). A list that older versions stored as a whole
+/// `U64list` under `<key>` is read as it is, and is converted to the indexed layout on its first change (its `U64list` entry is overwritten with a unit value)
+pub struct IndexedList {
+    dict: URef,
+    key: String,
+}
+
+impl IndexedList {
+    /// Returns the list stored under the key, in the dictionary with the given name
+    pub fn new(dict_name: &str, key: &str) -> Self {
+        Self::from_uref(get_named_key_by_name(dict_name), key)
+    }
+
+    /// Returns the list stored under the key, in the given dictionary
+    pub fn from_uref(dict: URef, key: &str) -> Self {
+        IndexedList {
+            dict,
+            key: key.to_string(),
+        }
+    }
+
+    /// Returns the number of ids in the list
+    pub fn len(&self) -> u64 {
+        match self.read_entry("len") {
+            Some(len) => len,
+            None => self
+                .legacy_list()
+                .map(|legacy| legacy.list.len() as u64)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns true if the list has no ids
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the id at the index, or None if the index is out of the list
+    pub fn get(&self, index: u64) -> Option<u64> {
+        match self.read_entry("len") {
+            Some(len) if index < len => self.read_entry(&index.to_string()),
+            Some(_) => None,
+            None => self
+                .legacy_list()
+                .and_then(|legacy| legacy.list.iter().nth(index as usize).copied()),
+        }
+    }
+
+    /// Returns true if the id is in the list
+    pub fn contains(&self, id: u64) -> bool {
+        match self.read_entry("len") {
+            Some(_) => self.position(id) != 0,
+            None => self
+                .legacy_list()
+                .map(|legacy| legacy.list.contains(&id))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns at most `limit` ids of the list, starting from the index `offset`
+    pub fn page(&self, offset: u64, limit: u64) -> Vec<u64> {
+        match self.read_entry("len") {
+            Some(len) => (offset..len.min(offset.saturating_add(limit)))
+                .filter_map(|index| self.read_entry(&index.to_string()))
+                .collect(),
+            None => self
+                .legacy_list()
+                .map(|legacy| {
+                    legacy
+                        .list
+                        .iter()
+                        .skip(offset as usize)
+                        .take(limit as usize)
+                        .copied()
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Adds the id to the end of the list, if it is not in the list already
+    pub fn push(&self, id: u64) {
+        let len = self.converted_len();
+        if self.position(id) != 0 {
+            return;
+        }
+        self.write_entry(&len.to_string(), id);
+        self.write_entry(&position_suffix(id), len + 1);
+        self.write_entry("len", len + 1);
+    }
+
+    /// Removes the id from the list by moving the last id to its place, and returns false if the id is not in the list
+    pub fn remove(&self, id: u64) -> bool {
+        let len = self.converted_len();
+        let position = self.position(id);
+        if position == 0 {
+            return false;
+        }
+        let last_index = len - 1;
+        if position - 1 != last_index {
+            let last_id = self
+                .read_entry(&last_index.to_string())
+                .unwrap_or_revert_with(Error::EmptyU64List);
+            self.write_entry(&(position - 1).to_string(), last_id);
+            self.write_entry(&position_suffix(last_id), position);
+        }
+        self.write_entry(&position_suffix(id), 0);
+        self.write_entry("len", last_index);
+        true
+    }
+
+    /// Returns the length of the list, after converting it from a legacy `U64list` if it is not converted yet
+    fn converted_len(&self) -> u64 {
+        if let Some(len) = self.read_entry("len") {
+            return len;
+        }
+        let legacy = self.legacy_list();
+        let mut len = 0u64;
+        if let Some(legacy) = &legacy {
+            for id in legacy.list.iter() {
+                self.write_entry(&len.to_string(), *id);
+                self.write_entry(&position_suffix(*id), len + 1);
+                len += 1;
+            }
+            storage::dictionary_put(self.dict, &self.key, ());
+        }
+        self.write_entry("len", len);
+        len
+    }
+
+    /// Returns the index of the id plus one, or 0 if it is not in the (converted) list
+    fn position(&self, id: u64) -> u64 {
+        self.read_entry(&position_suffix(id)).unwrap_or_default()
+    }
+
+    /// The `U64list` that older versions stored under the key, only read while the list is not converted
+    fn legacy_list(&self) -> Option<U64list> {
+        storage::dictionary_get::<U64list>(self.dict, &self.key).unwrap_or_revert()
+    }
+
+    fn item_key(&self, suffix: &str) -> String {
+        base16::encode_lower(&runtime::blake2b(format!("{}_{}", self.key, suffix)))
+    }
+
+    fn read_entry(&self, suffix: &str) -> Option<u64> {
+        storage::dictionary_get(self.dict, &self.item_key(suffix)).unwrap_or_revert()
+    }
+
+    fn write_entry(&self, suffix: &str, value: u64) {
+        storage::dictionary_put(self.dict, &self.item_key(suffix), value);
+    }
+}
+
+fn position_suffix(id: u64) -> String {
+    format!("pos_{}", id)
+}
+
+/// Returns a page of the list of the `account` runtime arg, in the dictionary with the given name
+///
+/// Gets `account` : `Key` (an account or a contract package), `offset` : `u64` and `limit` : `u64` (at most `MAX_PAGE_SIZE`) from the runtime args
+fn ret_account_page(dict_name: &str) {
+    let account = get_party_arg(RUNTIME_ARG_ACCOUNT);
+    let offset: u64 = runtime::get_named_arg(RUNTIME_ARG_OFFSET);
+    let limit: u64 = runtime::get_named_arg(RUNTIME_ARG_LIMIT);
+    if limit > MAX_PAGE_SIZE {
+        runtime::revert(Error::PageTooLarge);
+    }
+    let page = IndexedList::new(dict_name, &account.as_string()).page(offset, limit);
+    runtime::ret(CLValue::from_t(page).unwrap_or_revert());
+}
+
+/// Paginated getter of the holder ids that an account owns
+#[no_mangle]
+pub extern "C" fn get_owner_holders() {
+    ret_account_page(NAMED_KEY_DICT_OWNERS_NAME);
+}
+
+/// Paginated getter of the pending publish request ids that are made to a producer
+#[no_mangle]
+pub extern "C" fn get_producer_requests() {
+    ret_account_page(NAMED_KEY_DICT_PROD_REQS);
+}
+
+/// Paginated getter of the pending publish request ids that a publisher made
+#[no_mangle]
+pub extern "C" fn get_publisher_requests() {
+    ret_account_page(NAMED_KEY_DICT_PUB_REQS);
+}
+
+/// Paginated getter of the approved ids of a producer
+#[no_mangle]
+pub extern "C" fn get_producer_approved() {
+    ret_account_page(NAMED_KEY_DICT_PRODAPPROVED_NAME);
+}
+
+/// Paginated getter of the approved ids of a publisher
+#[no_mangle]
+pub extern "C" fn get_publisher_approved() {
+    ret_account_page(NAMED_KEY_DICT_PUBAPPROVED_NAME);
+}
+
+/// Paginated getter of the order ids of a buyer
+#[no_mangle]
+pub extern "C" fn get_buyer_orders() {
+    ret_account_page(NAMED_KEY_DICT_BUYER_ORDERS);
+}
+
+/// Paginated getter of the order ids of a producer
+#[no_mangle]
+pub extern "C" fn get_producer_orders() {
+    ret_account_page(NAMED_KEY_DICT_PRODUCER_ORDERS);
+}
+
+/// Paginated getter of the order ids of a publisher
+#[no_mangle]
+pub extern "C" fn get_publisher_orders() {
+    ret_account_page(NAMED_KEY_DICT_PUBLISHER_ORDERS);
+}
@@ -1,15 +1,23 @@
 use crate::constants::{
     NAMED_KEY_DICT_APPROVED_NAME, NAMED_KEY_DICT_HOLDERS_NAME, NAMED_KEY_DICT_METADATAS_NAME,
-    NAMED_KEY_DICT_OWNERS_NAME, NAMED_KEY_DICT_PRODAPPROVED_NAME, NAMED_KEY_DICT_PROD_REQS,
+    NAMED_KEY_DICT_EVENTS, NAMED_KEY_DICT_OPERATORS, NAMED_KEY_DICT_OWNERS_NAME, NAMED_KEY_DICT_PROPOSALS, NAMED_KEY_DICT_PRODAPPROVED_NAME, NAMED_KEY_DICT_PROD_REQS,
     NAMED_KEY_DICT_PUBAPPROVED_NAME, NAMED_KEY_DICT_PUB_REQS, NAMED_KEY_DICT_REQ_OBJ,
-    NAMED_KEY_DICT_TOKEN_ID_BY_HASH_NAME, NAMED_KEY_DICT_TOTAL_SUPPLY,
+    NAMED_KEY_DICT_TOKEN_ID_BY_HASH_NAME, NAMED_KEY_DICT_TOTAL_SUPPLY, NAMED_KEY_DICT_ORDERS,
+    NAMED_KEY_DICT_BUYER_ORDERS, NAMED_KEY_DICT_PRODUCER_ORDERS, NAMED_KEY_DICT_PUBLISHER_ORDERS,
+    NAMED_KEY_DICT_ESCROW_BALANCES, NAMED_KEY_ESCROW_PURSE, NAMED_KEY_DICT_PROVENANCE,
+    NAMED_KEY_DICT_TOKEN_PROVENANCE, NAMED_KEY_DICT_BALANCES, NAMED_KEY_DICT_TOKEN_HOLDERS,
+    NAMED_KEY_DICT_HOLDER_OWNERS,
+};
+use casper_contract::{
+    contract_api::{runtime, storage, system},
+    unwrap_or_revert::UnwrapOrRevert,
 };
-use casper_contract::{contract_api::storage, unwrap_or_revert::UnwrapOrRevert};
 
 /// init entrypoint, the first called method of the contract
 /// 
 /// It would be called after the contract is deployed, by the deployer of the contract, and is only callable once and only once, It will
 /// initialize dictionaries that the contract need to work with, and put them into the namedkeys of the contract. 
+/// It also creates the escrow purse, which holds the shares of the contract packages until they withdraw them
 #[no_mangle]
 pub extern "C" fn init() {
     storage::new_dictionary(NAMED_KEY_DICT_APPROVED_NAME).unwrap_or_revert();
@@ -23,4 +31,18 @@ pub extern "C" fn init() {
     storage::new_dictionary(NAMED_KEY_DICT_PROD_REQS).unwrap_or_revert();
     storage::new_dictionary(NAMED_KEY_DICT_PUB_REQS).unwrap_or_revert();
     storage::new_dictionary(NAMED_KEY_DICT_TOTAL_SUPPLY).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_OPERATORS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_PROPOSALS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_EVENTS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_ORDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_BUYER_ORDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_PRODUCER_ORDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_PUBLISHER_ORDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_ESCROW_BALANCES).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_PROVENANCE).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_TOKEN_PROVENANCE).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_BALANCES).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_TOKEN_HOLDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_HOLDER_OWNERS).unwrap_or_revert();
+    runtime::put_key(NAMED_KEY_ESCROW_PURSE, system::create_purse().into());
 }
@@ -1,13 +1,23 @@
 #![no_std]
 #![no_main]
+pub mod access_control;
+pub mod admin;
 pub mod affiliate;
+pub mod balances;
+pub mod cep85;
+pub mod compact;
 mod constants;
 pub mod event;
+pub mod governance;
+pub mod indexed_list;
 pub mod init;
 pub mod mint;
 pub mod ndpc_types;
 mod ndpc_utils;
 pub mod payment;
+pub mod provenance;
+mod storage_context;
+pub mod upgrade;
 #[allow(unused_imports)]
 #[cfg(not(target_arch = "wasm32"))]
 compile_error!("target arch should be wasm32: compile with '--target wasm32-unknown-unknown'");
@@ -21,8 +31,14 @@ use casper_contract::{
     contract_api::{runtime, storage},
     unwrap_or_revert::UnwrapOrRevert,
 };
-use casper_types::{ApiError, AsymmetricType, ContractPackageHash, PublicKey, RuntimeArgs, URef};
-use constants::{get_entrypoints, get_named_keys, NAMED_KEY_RATIO_VERIFIER};
+use casper_types::{
+    contracts::NamedKeys, ApiError, AsymmetricType, ContractHash, ContractPackageHash, Key,
+    PublicKey, RuntimeArgs, URef,
+};
+use constants::{
+    get_entrypoints, get_named_keys, CONSTRUCTOR_GROUP, MIGRATOR_GROUP, NAMED_KEY_ACCESS_UREF,
+    NAMED_KEY_CONTRACT, RUNTIME_ARG_RATIO_VERIFIER, RUNTIME_ARG_TREASURY,
+};
 
 
 /// All the different User error types for the contract
@@ -54,6 +70,54 @@ enum Error {
     FeeNotFound = 23,
     KeyNotUref = 24,
     RequestDoesntExist = 25,
+    MalformedQuote = 26,
+    UnsupportedQuoteVersion = 27,
+    QuoteExpired = 28,
+    QuoteCurrencyMismatch = 29,
+    QuoteNotForListing = 30,
+    QuoteNotForBuyer = 31,
+    InvalidQuoteFreshness = 32,
+    SignatureCountMismatch = 33,
+    UnknownOracleSigner = 34,
+    DuplicateOracleSigner = 35,
+    NotEnoughOracleSignatures = 36,
+    OracleSignerExists = 37,
+    InvalidOracleThreshold = 38,
+    TooManyOracleSigners = 39,
+    InvalidTreasury = 40,
+    NotPendingOwner = 41,
+    InvalidFee = 42,
+    InvalidFeeSchedule = 43,
+    InvalidGracePeriod = 44,
+    ContractPaused = 45,
+    InvalidPauseFlags = 46,
+    InvalidContractVersion = 47,
+    GovernanceRequired = 48,
+    NotGovernor = 49,
+    ProposalNotFound = 50,
+    ProposalNotPending = 51,
+    AlreadyApproved = 52,
+    ProposalNotReady = 53,
+    InvalidGovernance = 54,
+    MalformedAction = 55,
+    UpgradeNotAuthorized = 56,
+    InvalidOptionalArg = 57,
+    InvalidOrderReference = 58,
+    OrderNotFound = 59,
+    BatchTooLarge = 60,
+    PageTooLarge = 61,
+    MigrationInProgress = 62,
+    UnsupportedKey = 63,
+    NothingToWithdraw = 64,
+    InvalidSignatureHex = 65,
+    MalformedSignature = 66,
+    InvalidRecipientKey = 67,
+    InvalidVerifierKey = 68,
+    UnsupportedSignatureScheme = 69,
+    CompactArgsMismatch = 70,
+    InvalidTokenId = 71,
+    BatchArgsMismatch = 72,
+    PriceOverflow = 73,
 }
 impl From<Error> for ApiError {
     fn from(error: Error) -> Self {
@@ -63,42 +127,102 @@ impl From<Error> for ApiError {
 
 /// Installs the droplinked_contract with the given session args and calls the init function of the contract
 /// 
-/// It'll get fee and ratio_verifier publicKey (the account which verifies the ratio of cspr/usd) as input, install droplinked's contract on testnet, and call it's init entrypoint
-/// It stores the contract hash and contract-package hash into the deployer's NAMEDKEYS, Creates a constructor group, which are the only group who can call the init function, adds the deployer to them,
+/// It'll get fee, quote_freshness (the time in ms that a signed quote stays valid for), treasury (the account or purse which receives droplinked's fee)
+/// and ratio_verifier publicKey (the account which verifies the ratio of cspr/usd) as input,
+/// install droplinked's contract on testnet, and call it's init entrypoint. The deployer is stored as the owner of the contract
+/// The contract package is upgradeable, its access uref is only stored in the contract's NAMEDKEYS as `droplinked_access_uref`, so new versions could only be added
+/// through the governed `upgrade_access` entrypoint. It stores the contract hash and contract-package hash into the deployer's NAMEDKEYS,
+/// Creates a constructor group, which are the only group who can call the init function, adds the deployer to them,
 /// Calls the init function and then removes the deployer from that group so that the init method would never be called again!
 fn install_contract() {
     let fee: u64 = runtime::get_named_arg(constants::RUNTIME_FEE);
-    let ratio_verifier_hex = runtime::get_named_arg::<String>(NAMED_KEY_RATIO_VERIFIER);
-    let ratio_verifier = PublicKey::from_hex(ratio_verifier_hex).unwrap();
+    if fee > constants::MAX_FEE {
+        runtime::revert(Error::InvalidFee);
+    }
+    let ratio_verifier_hex = runtime::get_named_arg::<String>(RUNTIME_ARG_RATIO_VERIFIER);
+    let ratio_verifier = PublicKey::from_hex(ratio_verifier_hex)
+        .ok()
+        .unwrap_or_revert_with(Error::InvalidVerifierKey);
+    ndpc_utils::ensure_supported_signer(&ratio_verifier);
+    let treasury: Key = runtime::get_named_arg(RUNTIME_ARG_TREASURY);
+    if !ndpc_utils::is_valid_treasury(&treasury) {
+        runtime::revert(Error::InvalidTreasury);
+    }
+    let quote_freshness: u64 = runtime::get_named_arg(constants::RUNTIME_ARG_QUOTE_FRESHNESS);
+    if !(constants::MIN_QUOTE_FRESHNESS..=constants::MAX_QUOTE_FRESHNESS).contains(&quote_freshness) {
+        runtime::revert(Error::InvalidQuoteFreshness);
+    }
     let entry_points = get_entrypoints();
-    let named_keys = get_named_keys(ratio_verifier, fee);
-    let (contract_hash, _contract_version) = storage::new_locked_contract(
-        entry_points,
-        Some(named_keys),
-        Some(constants::CONTRACTPACKAGEHASH.to_string()),
-        None,
-    );
-    let package_hash = ContractPackageHash::new(
-        runtime::get_key(constants::CONTRACTPACKAGEHASH)
-            .unwrap_or_revert()
-            .into_hash()
-            .unwrap_or_revert(),
+    let (package_hash, access_uref) = storage::create_contract_package_at_hash();
+    let mut named_keys = get_named_keys(
+        ratio_verifier,
+        treasury,
+        fee,
+        quote_freshness,
+        // the install runs as a session code, so its caller is the installing account
+        Key::Account(runtime::get_caller()),
     );
+    named_keys.insert(NAMED_KEY_ACCESS_UREF.to_string(), access_uref.into());
+    let (contract_hash, _contract_version) =
+        storage::add_contract_version(package_hash, entry_points, named_keys);
+    runtime::put_key(constants::CONTRACTPACKAGEHASH, package_hash.into());
     let constructor_access: URef =
-        storage::create_contract_user_group(package_hash, "constructor", 1, Default::default())
+        storage::create_contract_user_group(package_hash, CONSTRUCTOR_GROUP, 1, Default::default())
             .unwrap_or_revert()
             .pop()
             .unwrap_or_revert();
     let _: () = runtime::call_contract(contract_hash, "init", RuntimeArgs::new());
     let mut urefs = BTreeSet::new();
     urefs.insert(constructor_access);
-    storage::remove_contract_user_group_urefs(package_hash, "constructor", urefs)
+    storage::remove_contract_user_group_urefs(package_hash, CONSTRUCTOR_GROUP, urefs)
+        .unwrap_or_revert();
+    runtime::put_key(NAMED_KEY_CONTRACT, contract_hash.into());
+}
+
+/// Adds a new version of the droplinked_contract to the existing `droplinked_package_hash` of the deployer
+/// 
+/// It gets the access uref of the package from the `upgrade_access` entrypoint of the previous version, which reverts unless the deployer is the owner
+/// and, once governance is configured, the upgrade is authorized by an executed `AuthorizeUpgrade` proposal. Installs before version 11 left the uref
+/// in the deployer's NAMEDKEYS instead, that copy is used once and then removed, so that the deployer could not add or disable versions on its own anymore.
+/// The new version gets the entrypoints of this code, and keeps the named keys (and so the dictionaries and all the state) of the previous version, plus the access uref.
+/// It creates a migrator group, which are the only group who can call the migrate function, calls the migrate function with it and then removes the group,
+/// so that migrate only runs once per upgrade. Finally it disables the previous version and stores the new contract hash into the deployer's NAMEDKEYS
+fn upgrade_contract(package_hash: ContractPackageHash) {
+    let previous_contract_hash = ContractHash::new(
+        runtime::get_key(NAMED_KEY_CONTRACT)
+            .unwrap_or_revert()
+            .into_hash()
+            .unwrap_or_revert(),
+    );
+    let legacy_access_key = runtime::get_key(NAMED_KEY_ACCESS_UREF);
+    let access_uref: URef = match legacy_access_key {
+        Some(access_key) => access_key.into_uref().unwrap_or_revert(),
+        None => runtime::call_contract(previous_contract_hash, "upgrade_access", RuntimeArgs::new()),
+    };
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(NAMED_KEY_ACCESS_UREF.to_string(), access_uref.into());
+    let (contract_hash, _contract_version) =
+        storage::add_contract_version(package_hash, get_entrypoints(), named_keys);
+    storage::create_contract_user_group(package_hash, MIGRATOR_GROUP, 1, Default::default())
         .unwrap_or_revert();
-    runtime::put_key("droplinked_contract", contract_hash.into());
+    let _: () = runtime::call_contract(contract_hash, "migrate", RuntimeArgs::new());
+    storage::remove_contract_user_group(package_hash, MIGRATOR_GROUP).unwrap_or_revert();
+    storage::disable_contract_version(package_hash, previous_contract_hash).unwrap_or_revert();
+    runtime::put_key(NAMED_KEY_CONTRACT, contract_hash.into());
+    if legacy_access_key.is_some() {
+        runtime::remove_key(NAMED_KEY_ACCESS_UREF);
+    }
 }
 
-/// This method gets executed when the session code is deployed to the network, It will simply call the install_contract function to install the contract
+/// This method gets executed when the session code is deployed to the network
+/// 
+/// If the deployer already has a `droplinked_package_hash`, it upgrades that package by calling the upgrade_contract function, otherwise it installs the contract with the install_contract function
 #[no_mangle]
 pub extern "C" fn call() {
-    install_contract();
+    match runtime::get_key(constants::CONTRACTPACKAGEHASH) {
+        Some(package_key) => upgrade_contract(ContractPackageHash::new(
+            package_key.into_hash().unwrap_or_revert(),
+        )),
+        None => install_contract(),
+    }
 }
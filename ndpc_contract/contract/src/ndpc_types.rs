@@ -5,7 +5,7 @@
 // |__________________________________________________________________________________________________
 
 extern crate alloc;
-use core::fmt::Display;
+use core::{fmt::Display, iter::Peekable, str::Chars};
 
 use alloc::{
     borrow::ToOwned,
@@ -15,14 +15,27 @@ use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
-use casper_contract::contract_api::runtime::blake2b;
+use casper_contract::{contract_api::runtime::blake2b, unwrap_or_revert::UnwrapOrRevert};
 use casper_types::{
     account::AccountHash,
     bytesrepr::{Error, FromBytes, ToBytes},
-    CLTyped,
+    CLTyped, Key, PublicKey, U512,
 };
 /// Hash-len of the metadata, its blake2b so it would be 32 bytes
 const METADATA_HASH_LENGTH: usize = 32;
+/// Layout version of the PriceQuote that the contract accepts
+pub const PRICE_QUOTE_VERSION: u8 = 1;
+/// Layout versions of the records that the contract stores in its dictionaries, written as the first byte of each record
+///
+/// Records stored before the layouts were versioned have no version byte; they are read through `Legacy` and rewritten by `migrate_storage`
+pub const NFT_METADATA_VERSION: u8 = 1;
+pub const NFT_HOLDER_VERSION: u8 = 1;
+pub const APPROVED_NFT_VERSION: u8 = 2;
+pub const PUBLISH_REQUEST_VERSION: u8 = 2;
+pub const ORDER_VERSION: u8 = 2;
+pub const PROVENANCE_ENTRY_VERSION: u8 = 1;
+/// The first layout version of `ApprovedNFT`, `PublishRequest` and `Order` which stores their roles (owner, publisher, buyer...) as keys, older ones store account hashes
+const ROLE_KEYS_VERSION: u8 = 2;
 
 /// Holds the hash of the metadata
 pub struct MetadataHash(pub [u8; METADATA_HASH_LENGTH]);
@@ -36,8 +49,8 @@ impl AsStrized for MetadataHash {
 pub struct PublishRequest {
     pub holder_id: u64,
     pub amount: u64,
-    pub producer: AccountHash,
-    pub publisher: AccountHash,
+    pub producer: Key,
+    pub publisher: Key,
 }
 /// Metadata of the NFT, including name, uri, checksum, price and comission
 pub struct NftMetadata {
@@ -57,11 +70,118 @@ pub struct NFTHolder {
 pub struct ApprovedNFT {
     pub holder_id: u64,
     pub amount: u64,
-    pub owneraccount: AccountHash,
-    pub publisheraccount: AccountHash,
+    pub owneraccount: Key,
+    pub publisheraccount: Key,
     pub token_id: u64,
 }
 
+/// A signed CSPR/USD price quote, provided by the ratio verifier for the buy entrypoint
+///
+/// It is serialized with bytesrepr and the signature is made over its canonical bytes. `approved_id` and `buyer`
+/// are optional, when they are set the quote could only be used to buy that listing, or by that buyer
+pub struct PriceQuote {
+    pub version: u8,
+    pub currency_pair: String,
+    pub ratio: u64,
+    pub decimals: u8,
+    pub timestamp: u64,
+    pub expiry: u64,
+    pub approved_id: Option<u64>,
+    pub buyer: Option<Key>,
+}
+
+/// An admin action that could be proposed to the governors, and is applied when its proposal is executed
+pub enum GovernanceAction {
+    SetFee {
+        fee: u64,
+        effective_at: Option<u64>,
+    },
+    SetTreasury {
+        treasury: Key,
+    },
+    AddOracleSigner {
+        public_key: PublicKey,
+    },
+    RemoveOracleSigner {
+        public_key: PublicKey,
+    },
+    SetOracleThreshold {
+        threshold: u8,
+    },
+    RotateOracleSigner {
+        public_key: PublicKey,
+        new_public_key: PublicKey,
+        grace_period: u64,
+    },
+    SetGovernance {
+        governors: Vec<Key>,
+        threshold: u8,
+        delay: u64,
+    },
+    AuthorizeUpgrade,
+}
+
+/// Status of a pending governance proposal
+pub const PROPOSAL_PENDING: u8 = 0;
+/// Status of an executed governance proposal
+pub const PROPOSAL_EXECUTED: u8 = 1;
+/// Status of a cancelled governance proposal
+pub const PROPOSAL_CANCELLED: u8 = 2;
+
+/// A governance proposal, holding the proposed action and the governors who approved it
+///
+/// `executable_at` is set when the approvals reach the governance threshold, to the blocktime after which it could be executed.
+/// The proposals stored before version 10 hold account hashes as the governors, they are rewritten with keys by `migrate`
+pub struct Proposal {
+    pub action: GovernanceAction,
+    pub proposer: Key,
+    pub approvals: Vec<Key>,
+    pub executable_at: Option<u64>,
+    pub status: u8,
+}
+
+/// A purchase made with the buy entrypoint, with its price breakdown and the blocktime it is made at
+pub struct Order {
+    pub buyer: Key,
+    pub approved_id: u64,
+    pub token_id: u64,
+    pub amount: u64,
+    pub product_price: u64,
+    pub shipping_price: u64,
+    pub tax_price: u64,
+    pub fee: u64,
+    pub ratio: u64,
+    pub ratio_decimals: u8,
+    pub producer: Key,
+    pub producer_amount: u64,
+    pub publisher: Key,
+    pub publisher_amount: u64,
+    pub treasury_amount: u64,
+    pub refund: U512,
+    pub timestamp: u64,
+}
+
+/// Why an amount of a token moved between two parties
+///
+/// Only `Mint` and `Buy` are written today : the contract has no transfer or burn entrypoint, and `Transfer` and `Burn` keep their tags reserved for them
+pub enum ProvenanceReason {
+    Mint,
+    Buy,
+    Transfer,
+    Burn,
+}
+
+/// A movement of an amount of a token, as it is appended to the provenance log of the token
+///
+/// `from` is None for a mint (`to` is always set, as tokens are never burnt)
+pub struct ProvenanceEntry {
+    pub from: Option<Key>,
+    pub to: Option<Key>,
+    pub quantity: u64,
+    pub blocktime: u64,
+    pub reason: ProvenanceReason,
+}
+
 /// a simple wrapper for a set of u64
 pub struct U64list {
     pub list: BTreeSet<u64>,
@@ -70,6 +190,7 @@ pub struct U64list {
 impl ToBytes for NftMetadata {
     fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
         let mut result = Vec::new();
+        result.append(&mut NFT_METADATA_VERSION.to_bytes()?);
         result.append(&mut self.name.to_bytes()?);
         result.append(&mut self.token_uri.to_bytes()?);
         result.append(&mut self.checksum.to_bytes()?);
@@ -84,7 +205,8 @@ impl ToBytes for NftMetadata {
         self.to_bytes()
     }
     fn serialized_length(&self) -> usize {
-        self.name.serialized_length()
+        NFT_METADATA_VERSION.serialized_length()
+            + self.name.serialized_length()
             + self.token_uri.serialized_length()
             + self.checksum.serialized_length()
             + self.price.serialized_length()
@@ -100,6 +222,22 @@ impl CLTyped for NftMetadata {
 
 impl FromBytes for NftMetadata {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (version, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        match version {
+            // version 1 is the unversioned layout of the older versions, behind the version byte
+            NFT_METADATA_VERSION => Self::from_legacy_bytes(rem),
+            _ => Err(Error::Formatting),
+        }
+    }
+    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
+        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
+    }
+}
+impl LegacyRecord for NftMetadata {
+    fn legacy_cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
         let (name, rem) = FromBytes::from_bytes(bytes)?;
         let (token_uri, rem) = FromBytes::from_bytes(rem)?;
         let (checksum, rem) = FromBytes::from_bytes(rem)?;
@@ -116,9 +254,6 @@ impl FromBytes for NftMetadata {
             rem,
         ))
     }
-    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
-        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
-    }
 }
 
 impl NftMetadata {
@@ -149,28 +284,121 @@ impl NftMetadata {
     pub fn to_json(&self) -> String {
         format!("{{\"name\":\"{}\",\"token_uri\":\"{}\",\"checksum\":\"{}\",\"price\":\"{}\",\"comission\":\"{}\"}}",self.name,self.token_uri,self.checksum,self.price,self.comission)
     }
+    /// The metadata as a CEP-78 metadata json, which only has the `name`, `token_uri` and `checksum` of the token
+    pub fn to_cep78_json(&self) -> String {
+        format!("{{\"name\":\"{}\",\"token_uri\":\"{}\",\"checksum\":\"{}\"}}",escape_json(&self.name),escape_json(&self.token_uri),escape_json(&self.checksum))
+    }
+    /// Reads the `name`, `token_uri` and `checksum` of the metadata json given to mint (a missing one is left empty), unescaping their values
     pub fn from_json(json: String, price: u64, comission: u64) -> Result<Self, Error> {
-        let split = json.split('\"');
-        //TODO: use another functionality to get the name, token_uri and checksum from the json (this one depends on the index of the split)
-        let mut name = String::new();
-        let mut token_uri = String::new();
-        let mut checksum = String::new();
-        for (i, s) in split.enumerate() {
-            if i == 3 {
-                name = s.to_owned();
+        let fields = parse_json_strings(&json)?;
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+        Ok(NftMetadata::new(
+            field("name"), field("token_uri"), field("checksum"), price, comission,
+        ))
+    }
+}
+
+/// Reads the fields of a flat json object whose values are all strings, as (key, value) pairs in their order
+///
+/// It returns `Formatting` for any other json, and for a `\u` escape of a lone surrogate
+fn parse_json_strings(json: &str) -> Result<Vec<(String, String)>, Error> {
+    let mut chars = json.trim().chars().peekable();
+    if chars.next() != Some('{') {
+        return Err(Error::Formatting);
+    }
+    let mut fields = Vec::new();
+    skip_json_whitespace(&mut chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+    } else {
+        loop {
+            skip_json_whitespace(&mut chars);
+            let key = parse_json_string(&mut chars)?;
+            skip_json_whitespace(&mut chars);
+            if chars.next() != Some(':') {
+                return Err(Error::Formatting);
             }
-            if i == 7 {
-                token_uri = s.to_owned();
+            skip_json_whitespace(&mut chars);
+            let value = parse_json_string(&mut chars)?;
+            fields.push((key, value));
+            skip_json_whitespace(&mut chars);
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err(Error::Formatting),
             }
-            if i == 11 {
-                checksum = s.to_owned();
+        }
+    }
+    if chars.next().is_some() {
+        return Err(Error::Formatting);
+    }
+    Ok(fields)
+}
+
+fn skip_json_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| matches!(c, ' ' | '\t' | '\n' | '\r')).is_some() {}
+}
+
+/// Reads a json string literal (from its opening quote to its closing one) and returns its unescaped value
+fn parse_json_string(chars: &mut Peekable<Chars>) -> Result<String, Error> {
+    if chars.next() != Some('"') {
+        return Err(Error::Formatting);
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next().ok_or(Error::Formatting)? {
+            '"' => return Ok(value),
+            '\\' => value.push(match chars.next().ok_or(Error::Formatting)? {
+                '"' => '"',
+                '\\' => '\\',
+                '/' => '/',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return Err(Error::Formatting);
+                    }
+                    let code = u32::from_str_radix(&hex, 16).map_err(|_| Error::Formatting)?;
+                    char::from_u32(code).ok_or(Error::Formatting)?
+                }
+                _ => return Err(Error::Formatting),
+            }),
+            // the control characters should be escaped in a json string
+            c if u32::from(c) < 0x20 => return Err(Error::Formatting),
+            c => value.push(c),
+        }
+    }
+}
+
+/// Escapes the string for a json string literal : `"` and `\` are backslash-escaped, and the control characters are written as `\n`, `\r`, `\t` or `\u00XX`
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", u32::from(c)))
             }
+            c => escaped.push(c),
         }
-        Ok(NftMetadata::new(
-            name, token_uri, checksum, price, comission,
-        ))
     }
+    escaped
 }
+
 impl Display for NftMetadata {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
@@ -184,6 +412,7 @@ impl Display for NftMetadata {
 impl ToBytes for NFTHolder {
     fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
         let mut result = Vec::new();
+        result.append(&mut NFT_HOLDER_VERSION.to_bytes()?);
         result.append(&mut self.amount.to_bytes()?);
         result.append(&mut self.token_id.to_bytes()?);
         Ok(result)
@@ -195,24 +424,39 @@ impl ToBytes for NFTHolder {
         self.to_bytes()
     }
     fn serialized_length(&self) -> usize {
-        self.amount.serialized_length() + self.token_id.serialized_length()
+        NFT_HOLDER_VERSION.serialized_length()
+            + self.amount.serialized_length()
+            + self.token_id.serialized_length()
     }
 }
 
 impl FromBytes for NFTHolder {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
-        let (amount, rem) = FromBytes::from_bytes(bytes)?;
-        let (token_id, rem) = FromBytes::from_bytes(rem)?;
-        Ok((NFTHolder { amount, token_id }, rem))
+        let (version, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        match version {
+            // version 1 is the unversioned layout of the older versions, behind the version byte
+            NFT_HOLDER_VERSION => Self::from_legacy_bytes(rem),
+            _ => Err(Error::Formatting),
+        }
     }
     fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
         Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
     }
 }
+impl LegacyRecord for NFTHolder {
+    fn legacy_cl_type() -> casper_types::CLType {
+        casper_types::CLType::ByteArray(4u32)
+    }
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (amount, rem) = FromBytes::from_bytes(bytes)?;
+        let (token_id, rem) = FromBytes::from_bytes(rem)?;
+        Ok((NFTHolder { amount, token_id }, rem))
+    }
+}
 
 impl CLTyped for NFTHolder {
     fn cl_type() -> casper_types::CLType {
-        casper_types::CLType::ByteArray(4u32)
+        casper_types::CLType::Any
     }
 }
 
@@ -235,6 +479,7 @@ impl Display for NFTHolder {
 impl ToBytes for ApprovedNFT {
     fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
         let mut result = Vec::new();
+        result.append(&mut APPROVED_NFT_VERSION.to_bytes()?);
         result.append(&mut self.holder_id.to_bytes()?);
         result.append(&mut self.amount.to_bytes()?);
         result.append(&mut self.owneraccount.to_bytes()?);
@@ -249,7 +494,8 @@ impl ToBytes for ApprovedNFT {
         self.to_bytes()
     }
     fn serialized_length(&self) -> usize {
-        self.holder_id.serialized_length()
+        APPROVED_NFT_VERSION.serialized_length()
+            + self.holder_id.serialized_length()
             + self.amount.serialized_length()
             + self.owneraccount.serialized_length()
             + self.publisheraccount.serialized_length()
@@ -259,10 +505,33 @@ impl ToBytes for ApprovedNFT {
 
 impl FromBytes for ApprovedNFT {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (version, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        match version {
+            // version 1 is the unversioned layout of the older versions behind the version byte, version 2 stores the roles as keys
+            1..=APPROVED_NFT_VERSION => Self::from_layout(rem, version),
+            _ => Err(Error::Formatting),
+        }
+    }
+    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
+        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
+    }
+}
+impl LegacyRecord for ApprovedNFT {
+    fn legacy_cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        // the unversioned layout is the layout of version 1, without the version byte
+        Self::from_layout(bytes, 1)
+    }
+}
+impl ApprovedNFT {
+    /// Reads the fields of the record, in the layout of the given version
+    fn from_layout(bytes: &[u8], version: u8) -> Result<(Self, &[u8]), Error> {
         let (holder_id, rem) = FromBytes::from_bytes(bytes)?;
         let (amount, rem) = FromBytes::from_bytes(rem)?;
-        let (owneraccount, rem) = FromBytes::from_bytes(rem)?;
-        let (publisheraccount, rem) = FromBytes::from_bytes(rem)?;
+        let (owneraccount, rem) = role_from_bytes(rem, version)?;
+        let (publisheraccount, rem) = role_from_bytes(rem, version)?;
         let (token_id, rem) = FromBytes::from_bytes(rem)?;
         Ok((
             ApprovedNFT {
@@ -275,10 +544,8 @@ impl FromBytes for ApprovedNFT {
             rem,
         ))
     }
-    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
-        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
-    }
 }
+
 impl CLTyped for ApprovedNFT {
     fn cl_type() -> casper_types::CLType {
         casper_types::CLType::Any
@@ -289,8 +556,8 @@ impl ApprovedNFT {
     pub fn new(
         holder_id: u64,
         amount: u64,
-        owneraccount: AccountHash,
-        publisheraccount: AccountHash,
+        owneraccount: Key,
+        publisheraccount: Key,
         token_id: u64,
     ) -> Self {
         ApprovedNFT {
@@ -358,6 +625,7 @@ impl Default for U64list {
 impl ToBytes for PublishRequest {
     fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
         let mut result = Vec::new();
+        result.append(&mut PUBLISH_REQUEST_VERSION.to_bytes()?);
         result.append(&mut self.holder_id.to_bytes()?);
         result.append(&mut self.amount.to_bytes()?);
         result.append(&mut self.producer.to_bytes()?);
@@ -371,7 +639,8 @@ impl ToBytes for PublishRequest {
         self.to_bytes()
     }
     fn serialized_length(&self) -> usize {
-        self.holder_id.serialized_length()
+        PUBLISH_REQUEST_VERSION.serialized_length()
+            + self.holder_id.serialized_length()
             + self.amount.serialized_length()
             + self.producer.serialized_length()
             + self.publisher.serialized_length()
@@ -379,10 +648,33 @@ impl ToBytes for PublishRequest {
 }
 impl FromBytes for PublishRequest {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (version, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        match version {
+            // version 1 is the unversioned layout of the older versions behind the version byte, version 2 stores the roles as keys
+            1..=PUBLISH_REQUEST_VERSION => Self::from_layout(rem, version),
+            _ => Err(Error::Formatting),
+        }
+    }
+    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
+        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
+    }
+}
+impl LegacyRecord for PublishRequest {
+    fn legacy_cl_type() -> casper_types::CLType {
+        casper_types::CLType::ByteArray(20u32)
+    }
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        // the unversioned layout is the layout of version 1, without the version byte
+        Self::from_layout(bytes, 1)
+    }
+}
+impl PublishRequest {
+    /// Reads the fields of the record, in the layout of the given version
+    fn from_layout(bytes: &[u8], version: u8) -> Result<(Self, &[u8]), Error> {
         let (holder_id, rem) = FromBytes::from_bytes(bytes)?;
         let (amount, rem) = FromBytes::from_bytes(rem)?;
-        let (producer, rem) = FromBytes::from_bytes(rem)?;
-        let (publisher, rem) = FromBytes::from_bytes(rem)?;
+        let (producer, rem) = role_from_bytes(rem, version)?;
+        let (publisher, rem) = role_from_bytes(rem, version)?;
         Ok((
             PublishRequest {
                 holder_id,
@@ -393,18 +685,16 @@ impl FromBytes for PublishRequest {
             rem,
         ))
     }
-    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
-        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
-    }
 }
+
 impl CLTyped for PublishRequest {
     fn cl_type() -> casper_types::CLType {
-        casper_types::CLType::ByteArray(20u32)
+        casper_types::CLType::Any
     }
 }
 
 impl PublishRequest {
-    pub fn new(holder_id: u64, amount: u64, producer: AccountHash, publisher: AccountHash) -> Self {
+    pub fn new(holder_id: u64, amount: u64, producer: Key, publisher: Key) -> Self {
         PublishRequest {
             holder_id,
             amount,
@@ -414,13 +704,582 @@ impl PublishRequest {
     }
 }
 
+impl ToBytes for PriceQuote {
+    fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
+        let mut result = Vec::new();
+        result.append(&mut self.version.to_bytes()?);
+        result.append(&mut self.currency_pair.to_bytes()?);
+        result.append(&mut self.ratio.to_bytes()?);
+        result.append(&mut self.decimals.to_bytes()?);
+        result.append(&mut self.timestamp.to_bytes()?);
+        result.append(&mut self.expiry.to_bytes()?);
+        result.append(&mut self.approved_id.to_bytes()?);
+        result.append(&mut self.buyer.to_bytes()?);
+        Ok(result)
+    }
+    fn into_bytes(self) -> Result<Vec<u8>, casper_types::bytesrepr::Error>
+    where
+        Self: Sized,
+    {
+        self.to_bytes()
+    }
+    fn serialized_length(&self) -> usize {
+        self.version.serialized_length()
+            + self.currency_pair.serialized_length()
+            + self.ratio.serialized_length()
+            + self.decimals.serialized_length()
+            + self.timestamp.serialized_length()
+            + self.expiry.serialized_length()
+            + self.approved_id.serialized_length()
+            + self.buyer.serialized_length()
+    }
+}
+impl FromBytes for PriceQuote {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (version, rem) = FromBytes::from_bytes(bytes)?;
+        let (currency_pair, rem) = FromBytes::from_bytes(rem)?;
+        let (ratio, rem) = FromBytes::from_bytes(rem)?;
+        let (decimals, rem) = FromBytes::from_bytes(rem)?;
+        let (timestamp, rem) = FromBytes::from_bytes(rem)?;
+        let (expiry, rem) = FromBytes::from_bytes(rem)?;
+        let (approved_id, rem) = FromBytes::from_bytes(rem)?;
+        let (buyer, rem) = FromBytes::from_bytes(rem)?;
+        Ok((
+            PriceQuote {
+                version,
+                currency_pair,
+                ratio,
+                decimals,
+                timestamp,
+                expiry,
+                approved_id,
+                buyer,
+            },
+            rem,
+        ))
+    }
+    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
+        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
+    }
+}
+impl CLTyped for PriceQuote {
+    fn cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+}
+
+impl GovernanceAction {
+    /// Returns the name of the action, used in the governance events
+    pub fn name(&self) -> &str {
+        match self {
+            GovernanceAction::SetFee { .. } => "set_fee",
+            GovernanceAction::SetTreasury { .. } => "set_treasury",
+            GovernanceAction::AddOracleSigner { .. } => "add_oracle_signer",
+            GovernanceAction::RemoveOracleSigner { .. } => "remove_oracle_signer",
+            GovernanceAction::SetOracleThreshold { .. } => "set_oracle_threshold",
+            GovernanceAction::RotateOracleSigner { .. } => "rotate_oracle_signer",
+            GovernanceAction::SetGovernance { .. } => "set_governance",
+            GovernanceAction::AuthorizeUpgrade => "authorize_upgrade",
+        }
+    }
+    fn tag(&self) -> u8 {
+        match self {
+            GovernanceAction::SetFee { .. } => 0,
+            GovernanceAction::SetTreasury { .. } => 1,
+            GovernanceAction::AddOracleSigner { .. } => 2,
+            GovernanceAction::RemoveOracleSigner { .. } => 3,
+            GovernanceAction::SetOracleThreshold { .. } => 4,
+            GovernanceAction::RotateOracleSigner { .. } => 5,
+            GovernanceAction::SetGovernance { .. } => 6,
+            GovernanceAction::AuthorizeUpgrade => 7,
+        }
+    }
+}
+impl ToBytes for GovernanceAction {
+    fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
+        let mut result = Vec::new();
+        result.append(&mut self.tag().to_bytes()?);
+        match self {
+            GovernanceAction::SetFee { fee, effective_at } => {
+                result.append(&mut fee.to_bytes()?);
+                result.append(&mut effective_at.to_bytes()?);
+            }
+            GovernanceAction::SetTreasury { treasury } => {
+                result.append(&mut treasury.to_bytes()?);
+            }
+            GovernanceAction::AddOracleSigner { public_key }
+            | GovernanceAction::RemoveOracleSigner { public_key } => {
+                result.append(&mut public_key.to_bytes()?);
+            }
+            GovernanceAction::SetOracleThreshold { threshold } => {
+                result.append(&mut threshold.to_bytes()?);
+            }
+            GovernanceAction::RotateOracleSigner {
+                public_key,
+                new_public_key,
+                grace_period,
+            } => {
+                result.append(&mut public_key.to_bytes()?);
+                result.append(&mut new_public_key.to_bytes()?);
+                result.append(&mut grace_period.to_bytes()?);
+            }
+            GovernanceAction::SetGovernance {
+                governors,
+                threshold,
+                delay,
+            } => {
+                result.append(&mut governors.to_bytes()?);
+                result.append(&mut threshold.to_bytes()?);
+                result.append(&mut delay.to_bytes()?);
+            }
+            GovernanceAction::AuthorizeUpgrade => {}
+        }
+        Ok(result)
+    }
+    fn into_bytes(self) -> Result<Vec<u8>, casper_types::bytesrepr::Error>
+    where
+        Self: Sized,
+    {
+        self.to_bytes()
+    }
+    fn serialized_length(&self) -> usize {
+        self.tag().serialized_length()
+            + match self {
+                GovernanceAction::SetFee { fee, effective_at } => {
+                    fee.serialized_length() + effective_at.serialized_length()
+                }
+                GovernanceAction::SetTreasury { treasury } => treasury.serialized_length(),
+                GovernanceAction::AddOracleSigner { public_key }
+                | GovernanceAction::RemoveOracleSigner { public_key } => {
+                    public_key.serialized_length()
+                }
+                GovernanceAction::SetOracleThreshold { threshold } => threshold.serialized_length(),
+                GovernanceAction::RotateOracleSigner {
+                    public_key,
+                    new_public_key,
+                    grace_period,
+                } => {
+                    public_key.serialized_length()
+                        + new_public_key.serialized_length()
+                        + grace_period.serialized_length()
+                }
+                GovernanceAction::SetGovernance {
+                    governors,
+                    threshold,
+                    delay,
+                } => {
+                    governors.serialized_length()
+                        + threshold.serialized_length()
+                        + delay.serialized_length()
+                }
+                GovernanceAction::AuthorizeUpgrade => 0,
+            }
+    }
+}
+impl FromBytes for GovernanceAction {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (tag, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        match tag {
+            0 => {
+                let (fee, rem) = FromBytes::from_bytes(rem)?;
+                let (effective_at, rem) = FromBytes::from_bytes(rem)?;
+                Ok((GovernanceAction::SetFee { fee, effective_at }, rem))
+            }
+            1 => {
+                let (treasury, rem) = FromBytes::from_bytes(rem)?;
+                Ok((GovernanceAction::SetTreasury { treasury }, rem))
+            }
+            2 => {
+                let (public_key, rem) = FromBytes::from_bytes(rem)?;
+                Ok((GovernanceAction::AddOracleSigner { public_key }, rem))
+            }
+            3 => {
+                let (public_key, rem) = FromBytes::from_bytes(rem)?;
+                Ok((GovernanceAction::RemoveOracleSigner { public_key }, rem))
+            }
+            4 => {
+                let (threshold, rem) = FromBytes::from_bytes(rem)?;
+                Ok((GovernanceAction::SetOracleThreshold { threshold }, rem))
+            }
+            5 => {
+                let (public_key, rem) = FromBytes::from_bytes(rem)?;
+                let (new_public_key, rem) = FromBytes::from_bytes(rem)?;
+                let (grace_period, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    GovernanceAction::RotateOracleSigner {
+                        public_key,
+                        new_public_key,
+                        grace_period,
+                    },
+                    rem,
+                ))
+            }
+            6 => {
+                let (governors, rem) = FromBytes::from_bytes(rem)?;
+                let (threshold, rem) = FromBytes::from_bytes(rem)?;
+                let (delay, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    GovernanceAction::SetGovernance {
+                        governors,
+                        threshold,
+                        delay,
+                    },
+                    rem,
+                ))
+            }
+            7 => Ok((GovernanceAction::AuthorizeUpgrade, rem)),
+            _ => Err(casper_types::bytesrepr::Error::Formatting),
+        }
+    }
+    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
+        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
+    }
+}
+
+impl ToBytes for Proposal {
+    fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
+        let mut result = Vec::new();
+        result.append(&mut self.action.to_bytes()?);
+        result.append(&mut self.proposer.to_bytes()?);
+        result.append(&mut self.approvals.to_bytes()?);
+        result.append(&mut self.executable_at.to_bytes()?);
+        result.append(&mut self.status.to_bytes()?);
+        Ok(result)
+    }
+    fn into_bytes(self) -> Result<Vec<u8>, casper_types::bytesrepr::Error>
+    where
+        Self: Sized,
+    {
+        self.to_bytes()
+    }
+    fn serialized_length(&self) -> usize {
+        self.action.serialized_length()
+            + self.proposer.serialized_length()
+            + self.approvals.serialized_length()
+            + self.executable_at.serialized_length()
+            + self.status.serialized_length()
+    }
+}
+impl FromBytes for Proposal {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (action, rem) = FromBytes::from_bytes(bytes)?;
+        let (proposer, rem) = FromBytes::from_bytes(rem)?;
+        let (approvals, rem) = FromBytes::from_bytes(rem)?;
+        let (executable_at, rem) = FromBytes::from_bytes(rem)?;
+        let (status, rem) = FromBytes::from_bytes(rem)?;
+        Ok((
+            Proposal {
+                action,
+                proposer,
+                approvals,
+                executable_at,
+                status,
+            },
+            rem,
+        ))
+    }
+    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
+        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
+    }
+}
+impl LegacyRecord for Proposal {
+    fn legacy_cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+    /// The older layout stores the governors (of the proposer, the approvals and a `SetGovernance` action) as account hashes
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (action, rem) = match bytes.first() {
+            Some(6) => {
+                let (governors, rem) = Vec::<AccountHash>::from_bytes(&bytes[1..])?;
+                let (threshold, rem) = FromBytes::from_bytes(rem)?;
+                let (delay, rem) = FromBytes::from_bytes(rem)?;
+                let governors = governors.into_iter().map(Key::Account).collect();
+                (
+                    GovernanceAction::SetGovernance {
+                        governors,
+                        threshold,
+                        delay,
+                    },
+                    rem,
+                )
+            }
+            _ => GovernanceAction::from_bytes(bytes)?,
+        };
+        let (proposer, rem) = AccountHash::from_bytes(rem)?;
+        let (approvals, rem) = Vec::<AccountHash>::from_bytes(rem)?;
+        let (executable_at, rem) = FromBytes::from_bytes(rem)?;
+        let (status, rem) = FromBytes::from_bytes(rem)?;
+        Ok((
+            Proposal {
+                action,
+                proposer: Key::Account(proposer),
+                approvals: approvals.into_iter().map(Key::Account).collect(),
+                executable_at,
+                status,
+            },
+            rem,
+        ))
+    }
+}
+impl CLTyped for Proposal {
+    fn cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+}
+
+impl ToBytes for Order {
+    fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
+        let mut result = Vec::new();
+        result.append(&mut ORDER_VERSION.to_bytes()?);
+        result.append(&mut self.buyer.to_bytes()?);
+        result.append(&mut self.approved_id.to_bytes()?);
+        result.append(&mut self.token_id.to_bytes()?);
+        result.append(&mut self.amount.to_bytes()?);
+        result.append(&mut self.product_price.to_bytes()?);
+        result.append(&mut self.shipping_price.to_bytes()?);
+        result.append(&mut self.tax_price.to_bytes()?);
+        result.append(&mut self.fee.to_bytes()?);
+        result.append(&mut self.ratio.to_bytes()?);
+        result.append(&mut self.ratio_decimals.to_bytes()?);
+        result.append(&mut self.producer.to_bytes()?);
+        result.append(&mut self.producer_amount.to_bytes()?);
+        result.append(&mut self.publisher.to_bytes()?);
+        result.append(&mut self.publisher_amount.to_bytes()?);
+        result.append(&mut self.treasury_amount.to_bytes()?);
+        result.append(&mut self.refund.to_bytes()?);
+        result.append(&mut self.timestamp.to_bytes()?);
+        Ok(result)
+    }
+    fn into_bytes(self) -> Result<Vec<u8>, casper_types::bytesrepr::Error>
+    where
+        Self: Sized,
+    {
+        self.to_bytes()
+    }
+    fn serialized_length(&self) -> usize {
+        ORDER_VERSION.serialized_length()
+            + self.buyer.serialized_length()
+            + self.approved_id.serialized_length()
+            + self.token_id.serialized_length()
+            + self.amount.serialized_length()
+            + self.product_price.serialized_length()
+            + self.shipping_price.serialized_length()
+            + self.tax_price.serialized_length()
+            + self.fee.serialized_length()
+            + self.ratio.serialized_length()
+            + self.ratio_decimals.serialized_length()
+            + self.producer.serialized_length()
+            + self.producer_amount.serialized_length()
+            + self.publisher.serialized_length()
+            + self.publisher_amount.serialized_length()
+            + self.treasury_amount.serialized_length()
+            + self.refund.serialized_length()
+            + self.timestamp.serialized_length()
+    }
+}
+impl FromBytes for Order {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (version, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        match version {
+            // version 1 is the unversioned layout of the older versions behind the version byte, version 2 stores the roles as keys
+            1..=ORDER_VERSION => Self::from_layout(rem, version),
+            _ => Err(Error::Formatting),
+        }
+    }
+    fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
+        Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
+    }
+}
+impl LegacyRecord for Order {
+    fn legacy_cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        // the unversioned layout is the layout of version 1, without the version byte
+        Self::from_layout(bytes, 1)
+    }
+}
+impl Order {
+    /// Reads the fields of the record, in the layout of the given version
+    fn from_layout(bytes: &[u8], version: u8) -> Result<(Self, &[u8]), Error> {
+        let (buyer, rem) = role_from_bytes(bytes, version)?;
+        let (approved_id, rem) = FromBytes::from_bytes(rem)?;
+        let (token_id, rem) = FromBytes::from_bytes(rem)?;
+        let (amount, rem) = FromBytes::from_bytes(rem)?;
+        let (product_price, rem) = FromBytes::from_bytes(rem)?;
+        let (shipping_price, rem) = FromBytes::from_bytes(rem)?;
+        let (tax_price, rem) = FromBytes::from_bytes(rem)?;
+        let (fee, rem) = FromBytes::from_bytes(rem)?;
+        let (ratio, rem) = FromBytes::from_bytes(rem)?;
+        let (ratio_decimals, rem) = FromBytes::from_bytes(rem)?;
+        let (producer, rem) = role_from_bytes(rem, version)?;
+        let (producer_amount, rem) = FromBytes::from_bytes(rem)?;
+        let (publisher, rem) = role_from_bytes(rem, version)?;
+        let (publisher_amount, rem) = FromBytes::from_bytes(rem)?;
+        let (treasury_amount, rem) = FromBytes::from_bytes(rem)?;
+        let (refund, rem) = FromBytes::from_bytes(rem)?;
+        let (timestamp, rem) = FromBytes::from_bytes(rem)?;
+        Ok((
+            Order {
+                buyer,
+                approved_id,
+                token_id,
+                amount,
+                product_price,
+                shipping_price,
+                tax_price,
+                fee,
+                ratio,
+                ratio_decimals,
+                producer,
+                producer_amount,
+                publisher,
+                publisher_amount,
+                treasury_amount,
+                refund,
+                timestamp,
+            },
+            rem,
+        ))
+    }
+}
+
+impl CLTyped for Order {
+    fn cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+}
+
+impl ProvenanceReason {
+    fn tag(&self) -> u8 {
+        match self {
+            ProvenanceReason::Mint => 0,
+            ProvenanceReason::Buy => 1,
+            ProvenanceReason::Transfer => 2,
+            ProvenanceReason::Burn => 3,
+        }
+    }
+}
+impl ToBytes for ProvenanceReason {
+    fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
+        self.tag().to_bytes()
+    }
+    fn serialized_length(&self) -> usize {
+        self.tag().serialized_length()
+    }
+}
+impl FromBytes for ProvenanceReason {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (tag, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        let reason = match tag {
+            0 => ProvenanceReason::Mint,
+            1 => ProvenanceReason::Buy,
+            2 => ProvenanceReason::Transfer,
+            3 => ProvenanceReason::Burn,
+            _ => return Err(Error::Formatting),
+        };
+        Ok((reason, rem))
+    }
+}
+
+impl ToBytes for ProvenanceEntry {
+    fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
+        let mut result = Vec::new();
+        result.append(&mut PROVENANCE_ENTRY_VERSION.to_bytes()?);
+        result.append(&mut self.from.to_bytes()?);
+        result.append(&mut self.to.to_bytes()?);
+        result.append(&mut self.quantity.to_bytes()?);
+        result.append(&mut self.blocktime.to_bytes()?);
+        result.append(&mut self.reason.to_bytes()?);
+        Ok(result)
+    }
+    fn into_bytes(self) -> Result<Vec<u8>, casper_types::bytesrepr::Error>
+    where
+        Self: Sized,
+    {
+        self.to_bytes()
+    }
+    fn serialized_length(&self) -> usize {
+        PROVENANCE_ENTRY_VERSION.serialized_length()
+            + self.from.serialized_length()
+            + self.to.serialized_length()
+            + self.quantity.serialized_length()
+            + self.blocktime.serialized_length()
+            + self.reason.serialized_length()
+    }
+}
+impl FromBytes for ProvenanceEntry {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        let (version, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        if version != PROVENANCE_ENTRY_VERSION {
+            return Err(Error::Formatting);
+        }
+        let (from, rem) = FromBytes::from_bytes(rem)?;
+        let (to, rem) = FromBytes::from_bytes(rem)?;
+        let (quantity, rem) = FromBytes::from_bytes(rem)?;
+        let (blocktime, rem) = FromBytes::from_bytes(rem)?;
+        let (reason, rem) = FromBytes::from_bytes(rem)?;
+        Ok((
+            ProvenanceEntry {
+                from,
+                to,
+                quantity,
+                blocktime,
+                reason,
+            },
+            rem,
+        ))
+    }
+}
+impl CLTyped for ProvenanceEntry {
+    fn cl_type() -> casper_types::CLType {
+        casper_types::CLType::Any
+    }
+}
+
+/// A record whose stored layout is versioned, which could also be read from the unversioned layout that older versions stored
+pub trait LegacyRecord: Sized {
+    /// The CLType that older versions stored the record with
+    fn legacy_cl_type() -> casper_types::CLType;
+    /// Reads the record from its unversioned layout (its fields, without a version byte)
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error>;
+}
+
+/// Reads a role of a record (an owner, a producer, a publisher or a buyer), in the layout of the given version
+///
+/// Layouts before `ROLE_KEYS_VERSION` store it as an account hash, which is read as a `Key::Account`
+fn role_from_bytes(bytes: &[u8], version: u8) -> Result<(Key, &[u8]), Error> {
+    if version < ROLE_KEYS_VERSION {
+        let (account_hash, rem) = AccountHash::from_bytes(bytes)?;
+        Ok((Key::Account(account_hash), rem))
+    } else {
+        Key::from_bytes(bytes)
+    }
+}
+
+/// A record read from the unversioned layout of older versions, only used by `migrate_storage` to rewrite it in its versioned layout
+pub struct Legacy<T>(pub T);
+
+impl<T: LegacyRecord> FromBytes for Legacy<T> {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+        T::from_legacy_bytes(bytes).map(|(record, rem)| (Legacy(record), rem))
+    }
+}
+impl<T: LegacyRecord> CLTyped for Legacy<T> {
+    fn cl_type() -> casper_types::CLType {
+        T::legacy_cl_type()
+    }
+}
+
 /// Converts the given Strign to the type, used to convert hex encoded string to accounthash
 pub trait FromStringize {
     fn from_string(string: String) -> Self;
 }
 impl FromStringize for AccountHash {
     fn from_string(string: String) -> Self {
-        AccountHash::from_formatted_str(format!("account-hash-{}", string).as_str()).unwrap()
+        AccountHash::from_formatted_str(format!("account-hash-{}", string).as_str())
+            .ok()
+            .unwrap_or_revert_with(crate::Error::NotAccountHash)
     }
 }
 /// Converts the given object to String, Its used to convert the AccountHash to base16 encoded string
@@ -432,3 +1291,13 @@ impl AsStrized for AccountHash {
         base16::encode_lower(&self.0)
     }
 }
+/// Accounts are keyed by their account hash (as the older versions did), and other keys (e.g. contract packages) by the blake2b hash of their bytes,
+/// so they could not collide with an account and still fit in a dictionary key
+impl AsStrized for Key {
+    fn as_string(&self) -> String {
+        match self {
+            Key::Account(account_hash) => account_hash.as_string(),
+            _ => base16::encode_lower(&blake2b(self.to_bytes().unwrap_or_default())),
+        }
+    }
+}
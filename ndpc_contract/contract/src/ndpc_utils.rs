@@ -1,26 +1,38 @@
 use core::ops::{Add, Div, Mul, Sub};
 
 use alloc::string::ToString;
-use alloc::{borrow::ToOwned, string::String};
+use alloc::{string::String, vec::Vec};
 use casper_contract::contract_api::runtime::get_key;
 use casper_contract::{
     contract_api::{
-        runtime::{get_call_stack, revert},
+        self,
+        runtime::{self, get_blocktime, revert},
         storage,
+        system::{transfer_from_purse_to_account, transfer_from_purse_to_purse},
     },
+    ext_ffi,
     unwrap_or_revert::UnwrapOrRevert,
 };
 use casper_types::U512;
 use casper_types::{
-    system::CallStackElement, ApiError, ContractPackageHash, PublicKey, URef,
+    api_error, bytesrepr, system::CallStackElement, ApiError, Key, PublicKey, URef,
 };
 use ed25519_dalek::{ed25519::signature::Signature, Verifier};
 
-use crate::constants::RUNTIME_FEE;
-use crate::ndpc_types::{ApprovedNFT, NFTHolder, PublishRequest, NftMetadata};
+use crate::constants::{
+    NAMED_KEY_DICT_ESCROW_BALANCES, NAMED_KEY_ESCROW_PURSE, NAMED_KEY_ORACLE_SIGNERS,
+    NAMED_KEY_ORACLE_THRESHOLD, NAMED_KEY_PENDING_FEE, NAMED_KEY_PAUSED, NAMED_KEY_QUOTE_FRESHNESS, NAMED_KEY_RETIRED_ORACLE_SIGNERS,
+    NAMED_KEY_SCHEMA_VERSION, QUOTE_MAX_DECIMALS, RUNTIME_FEE, SCHEMA_VERSION,
+};
+use crate::ndpc_types::{
+    ApprovedNFT, AsStrized, NFTHolder, NftMetadata, PriceQuote, PublishRequest,
+    PRICE_QUOTE_VERSION,
+};
 use crate::{
-    constants::NAMED_KEY_RATIO_VERIFIER,
-    ndpc_types::{self, U64list},
+    balances::set_balance,
+    constants::NAMED_KEY_TREASURY,
+    ndpc_types,
+    storage_context::StorageContext,
     Error,
 };
 
@@ -37,38 +49,127 @@ pub fn get_named_key_by_name(dict_name: &str) -> casper_types::URef {
         .unwrap_or_revert()
 }
 
-/// A getter function, which gets the ratio verifier account from the contract state, and return it as a PublicKey
-pub fn get_ratio_verifier() -> casper_types::PublicKey {
-    let ratio_verifier = casper_contract::contract_api::runtime::get_key(NAMED_KEY_RATIO_VERIFIER)
-        .unwrap_or_revert()
-        .into_uref()
-        .unwrap_or_revert();
-    storage::read::<casper_types::PublicKey>(ratio_verifier)
-        .unwrap_or_revert()
-        .unwrap_or_revert()
-}
-
 /// Verify a signature of a message, which is signed by the given publicKey
 /// 
+/// The signed payload is `"Casper Message:\n"` followed by the message bytes, the same prefix that casper signers put before a message
+/// 
 /// Note : It only supports Ed25519 publicKeys, as the verifier for the Secp256k1 keys, would larger up the contract and it would not be practical
-pub fn verify_signature(public_key: PublicKey, signature: String, message: String) -> bool {
-    let mut owned_string = "Casper Message:\n".to_owned();
-    owned_string.push_str(&message);
+/// 
+/// It would revert with `InvalidSignatureHex` if the signature is not hex encoded, with `MalformedSignature` if it is not a 64 bytes Ed25519 signature,
+/// and with `UnsupportedSignatureScheme` if the publicKey is not an Ed25519 key
+pub fn verify_signature(public_key: PublicKey, signature: String, message: &[u8]) -> bool {
+    let mut owned_message = b"Casper Message:\n".to_vec();
+    owned_message.extend_from_slice(message);
     match public_key {
         casper_types::PublicKey::Ed25519(x) => {
-            let sig = ed25519_dalek::Signature::from_bytes(
-                base16::decode(signature.as_str()).unwrap().as_slice(),
-            )
-            .unwrap();
-            x.verify(owned_string.as_bytes(), &sig).is_ok()
+            let signature_bytes = base16::decode(signature.as_str())
+                .ok()
+                .unwrap_or_revert_with(Error::InvalidSignatureHex);
+            let sig = ed25519_dalek::Signature::from_bytes(signature_bytes.as_slice())
+                .ok()
+                .unwrap_or_revert_with(Error::MalformedSignature);
+            x.verify(owned_message.as_slice(), &sig).is_ok()
         }
-        casper_types::PublicKey::Secp256k1(_x) => {
-            revert(Error::TransferFailed);
+        _ => revert(Error::UnsupportedSignatureScheme),
+    }
+}
+
+/// Reverts with `UnsupportedSignatureScheme` if the publicKey could not sign quotes, as `verify_signature` only supports Ed25519 keys
+pub fn ensure_supported_signer(public_key: &PublicKey) {
+    if !matches!(public_key, PublicKey::Ed25519(_)) {
+        revert(Error::UnsupportedSignatureScheme);
+    }
+}
+
+/// A getter function, which returns the public keys of the oracle signers, which could sign price quotes for `buy`
+pub fn get_oracle_signers() -> Vec<PublicKey> {
+    storage::read(get_named_key_by_name(NAMED_KEY_ORACLE_SIGNERS))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// A getter function, which returns the number of distinct oracle signers that should sign a price quote
+pub fn get_oracle_threshold() -> u8 {
+    storage::read(get_named_key_by_name(NAMED_KEY_ORACLE_THRESHOLD))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// A getter function, which returns the rotated oracle signers as (retired key, replacement key, valid until blocktime)
+pub fn get_retired_oracle_signers() -> Vec<(PublicKey, PublicKey, u64)> {
+    storage::read(get_named_key_by_name(NAMED_KEY_RETIRED_ORACLE_SIGNERS))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// Returns the oracle signer that a signature of `signer` counts for, or None if `signer` could not sign quotes
+/// 
+/// An active signer counts for itself, a rotated signer counts for its replacement until its grace period ends
+fn get_counted_oracle_signer(
+    signer: &PublicKey,
+    oracle_signers: &[PublicKey],
+    retired_oracle_signers: &[(PublicKey, PublicKey, u64)],
+    now: u64,
+) -> Option<PublicKey> {
+    if oracle_signers.contains(signer) {
+        return Some(signer.clone());
+    }
+    retired_oracle_signers
+        .iter()
+        .find(|(retired, replacement, valid_until)| {
+            retired == signer && *valid_until >= now && oracle_signers.contains(replacement)
+        })
+        .map(|(_, replacement, _)| replacement.clone())
+}
+
+/// Verifies that the message is signed by at least `oracle_threshold` distinct oracle signers
+/// 
+/// `signers` and `signatures` are matched by their index. A rotated signer is accepted during its grace period, in place of its replacement.
+/// It reverts if a signer is not an oracle signer, is repeated, or its signature is not valid
+pub fn verify_oracle_signatures(signers: Vec<PublicKey>, signatures: Vec<String>, message: &[u8]) {
+    if signers.len() != signatures.len() {
+        revert(Error::SignatureCountMismatch);
+    }
+    let oracle_signers = get_oracle_signers();
+    let retired_oracle_signers = get_retired_oracle_signers();
+    let now = u64::from(get_blocktime());
+    let mut verified_signers: Vec<PublicKey> = Vec::new();
+    for (signer, signature) in signers.into_iter().zip(signatures.into_iter()) {
+        let counted_signer =
+            get_counted_oracle_signer(&signer, &oracle_signers, &retired_oracle_signers, now)
+                .unwrap_or_revert_with(Error::UnknownOracleSigner);
+        if verified_signers.contains(&counted_signer) {
+            revert(Error::DuplicateOracleSigner);
         }
-        _ => {
-            panic!("Invalid ratio verifier type");
+        if !verify_signature(signer, signature, message) {
+            revert(Error::InvalidSignature);
         }
+        verified_signers.push(counted_signer);
+    }
+    if verified_signers.len() < get_oracle_threshold() as usize {
+        revert(Error::NotEnoughOracleSignatures);
+    }
+}
+
+/// Decodes the hex encoded PriceQuote given to the buy entrypoint
+/// 
+/// Reverts with `UnsupportedQuoteVersion` if the quote is not in the layout this contract knows, and with `MalformedQuote` if
+/// the bytes could not be decoded (or have trailing bytes), or if its `decimals` is more than `QUOTE_MAX_DECIMALS`
+pub fn decode_price_quote(quote_hex: &str) -> PriceQuote {
+    let quote_bytes = base16::decode(quote_hex)
+        .ok()
+        .unwrap_or_revert_with(Error::MalformedQuote);
+    match quote_bytes.first() {
+        Some(version) if *version == PRICE_QUOTE_VERSION => {}
+        Some(_) => revert(Error::UnsupportedQuoteVersion),
+        None => revert(Error::MalformedQuote),
+    }
+    let quote = bytesrepr::deserialize::<PriceQuote>(quote_bytes)
+        .unwrap_or_revert_with(Error::MalformedQuote);
+    if quote.decimals > QUOTE_MAX_DECIMALS {
+        revert(Error::MalformedQuote);
     }
+    quote
 }
 
 /// A shortcut function which returns the holders_cnt as a u64
@@ -78,27 +179,6 @@ pub fn get_holders_cnt(holders_cnt_uref: casper_types::URef) -> u64 {
         .unwrap_or_revert()
 }
 
-/// A shortcut function which returns the holder_ids which a account owns, as a Option<U64list>
-pub fn get_holder_ids(owners_dict_uref: casper_types::URef, owner: &str) -> Option<U64list> {
-    storage::dictionary_get(owners_dict_uref, owner).unwrap_or_revert()
-}
-
-/// Returns the contractPackage hash
-/// 
-/// Uses the callstack, to get the last caller, convert it to storedContract, and return its contractPackage hash
-pub fn contract_package_hash() -> ContractPackageHash {
-    let call_stacks = get_call_stack();
-    let last_entry = call_stacks.last().unwrap_or_revert();
-    let package_hash: Option<ContractPackageHash> = match last_entry {
-        CallStackElement::StoredContract {
-            contract_package_hash,
-            contract_hash: _,
-        } => Some(*contract_package_hash),
-        _ => None,
-    };
-    package_hash.unwrap_or_revert()
-}
-
 /// Gets `token_id` and returns the NFTMetadata object related to it
 pub fn get_nft_metadata(token_id: String, metadatas_dict: URef) -> ndpc_types::NftMetadata {
     storage::dictionary_get::<NftMetadata>(metadatas_dict, token_id.as_str()).unwrap_or_revert_with(ApiError::from(Error
@@ -128,11 +208,139 @@ pub fn get_approved_holder_by_id(approved_dict: URef, approved_id: u64) -> Appro
 }
 
 //-----------------------------------
-/// Simply returns the ratioVerifier of the contract
+/// Gets a holder_id by adding a new holder
 /// 
-/// This account would get the fee% of the payments
-pub fn get_droplinked_account() -> PublicKey {
-    get_ratio_verifier()
+/// Adds the minted NFT to the holders list of the owner account, It would search for the holder_id that corresponds with the token_id, and if it found it,
+/// It would modify the amount of it and add the `amount` to it. If it failed to find the holder_id , it would use holders_cnt+1 as new holder_id, and modify holders_cnt to holders_cnt+1, and 
+/// finally it would return the final_holder_id (the new created one, or 0 if the amount is added to an existing one).
+/// It is used by mint for the recipient, and by buy for the buyer, and updates the balance of the owner in the token
+pub fn add_nft_holder(
+    ctx: &mut StorageContext,
+    owner: Key,
+    token_id: u64,
+    amount: u64,
+) -> u64 {
+    let mut holder_id_final: u64 = 0;
+    let owner_holder_ids = ctx.owner_holders(&owner.as_string());
+    let mut existed = false;
+    for index in 0..owner_holder_ids.len() {
+        let holder_id = owner_holder_ids
+            .get(index)
+            .unwrap_or_revert_with(ApiError::from(Error::MintHolderNotFound));
+        let holder = ctx.holder(holder_id);
+        if holder.token_id == token_id {
+            holder.amount += amount;
+            let new_amount = holder.amount;
+            ctx.write_holder(holder_id);
+            set_balance(ctx, owner, token_id, holder_id, new_amount);
+            existed = true;
+            break;
+        }
+    }
+    if !existed {
+        let holders_cnt_uref = ctx.holders_cnt();
+        let holderid: u64 = get_holders_cnt(holders_cnt_uref) + 1u64;
+        holder_id_final = holderid;
+        storage::write(holders_cnt_uref, holderid);
+        storage::dictionary_put(
+            ctx.holders_dict(),
+            holderid.to_string().as_str(),
+            NFTHolder::new(amount, token_id),
+        );
+        owner_holder_ids.push(holderid);
+        set_balance(ctx, owner, token_id, holderid, amount);
+    }
+    holder_id_final
+}
+
+/// A getter function, which returns droplinked's treasury (an account hash or a purse)
+/// 
+/// The treasury gets the fee% of the payments
+pub fn get_treasury() -> Key {
+    storage::read(get_named_key_by_name(NAMED_KEY_TREASURY))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// Returns true if the key could be used as the treasury, an account hash, or a purse URef that could be added to
+pub fn is_valid_treasury(treasury: &Key) -> bool {
+    match treasury {
+        Key::Account(_) => true,
+        Key::URef(purse) => purse.is_addable(),
+        _ => false,
+    }
+}
+
+/// Transfers `amount` motes from the given purse to droplinked's treasury
+pub fn transfer_to_treasury(purse: URef, amount: U512) {
+    match get_treasury() {
+        Key::Account(treasury_account) => {
+            transfer_from_purse_to_account(purse, treasury_account, amount, None)
+                .unwrap_or_revert_with(Error::TransferFailed);
+        }
+        Key::URef(treasury_purse) => {
+            transfer_from_purse_to_purse(purse, treasury_purse, amount, None)
+                .unwrap_or_revert_with(Error::TransferFailed);
+        }
+        _ => revert(Error::InvalidTreasury),
+    }
+}
+
+/// Transfers `amount` motes from the given purse to a party of a sale (a producer or a publisher)
+///
+/// An account is paid directly. A contract package could not be transferred to, so its amount is moved to the escrow purse and added to
+/// its balance in the `escrow_balances` dictionary, which it could `withdraw`
+pub fn pay_party(purse: URef, party: Key, amount: U512) {
+    match party {
+        Key::Account(account_hash) => {
+            transfer_from_purse_to_account(purse, account_hash, amount, None)
+                .unwrap_or_revert_with(Error::TransferFailed);
+        }
+        Key::Hash(_) => {
+            if amount.is_zero() {
+                return;
+            }
+            let escrow_purse = get_named_key_by_name(NAMED_KEY_ESCROW_PURSE);
+            transfer_from_purse_to_purse(purse, escrow_purse, amount, None)
+                .unwrap_or_revert_with(Error::TransferFailed);
+            let balances_dict = get_named_key_by_name(NAMED_KEY_DICT_ESCROW_BALANCES);
+            let party_string = party.as_string();
+            let balance: U512 = storage::dictionary_get(balances_dict, &party_string)
+                .unwrap_or_revert()
+                .unwrap_or_default();
+            storage::dictionary_put(balances_dict, &party_string, balance.add(amount));
+        }
+        _ => revert(Error::UnsupportedKey),
+    }
+}
+
+/// Returns the immediate caller of the contract, from the call stack : the account of the deploy (or of a stored session), or the package of the calling contract
+pub fn get_immediate_caller() -> Key {
+    let call_stack = runtime::get_call_stack();
+    // the last element of the call stack is this contract, so the one before it is its caller
+    match call_stack
+        .iter()
+        .nth_back(1)
+        .unwrap_or_revert_with(Error::UnsupportedKey)
+    {
+        CallStackElement::Session { account_hash }
+        | CallStackElement::StoredSession { account_hash, .. } => Key::Account(*account_hash),
+        CallStackElement::StoredContract {
+            contract_package_hash,
+            ..
+        } => Key::from(*contract_package_hash),
+    }
+}
+
+/// Gets the runtime arg with the given name, which should be a party of the marketplace (an owner, a producer, a publisher or a buyer)
+///
+/// A party is either an account (`Key::Account`) or a contract package (`Key::Hash`), it reverts with `UnsupportedKey` for any other key
+pub fn get_party_arg(name: &str) -> Key {
+    let party: Key = runtime::get_named_arg(name);
+    match party {
+        Key::Account(_) | Key::Hash(_) => party,
+        _ => revert(Error::UnsupportedKey),
+    }
 }
 
 pub struct PaymentDetails {
@@ -147,8 +355,8 @@ pub fn calculate_payment(
     fee: u64,
 ) -> PaymentDetails {
     let droplinked_part = product_price.mul(fee).div(10000u64);
-    let recipient_part = shipping_price
-        .add(product_price.sub(droplinked_part))
+    let recipient_part = product_price
+        .sub(droplinked_part)
         .add(shipping_price)
         .add(tax_price);
     PaymentDetails {
@@ -156,13 +364,99 @@ pub fn calculate_payment(
         recipient: recipient_part,
     }
 }
-/// A shortcut function, which returns the fee that was set in the contract state during installation process
+/// A shortcut function, which returns the fee (in basis points) that is currently in effect
+/// 
+/// If a fee change was scheduled with `set_fee` and its `effective_at` blocktime has passed, the pending fee is applied to the contract state and returned
 pub(crate) fn get_fee() -> u64 {
     let fee_uref = get_key(RUNTIME_FEE)
         .unwrap_or_revert_with(Error::KeyNotFound)
         .into_uref()
         .unwrap_or_revert_with(Error::KeyNotUref);
+    let pending_fee_uref = get_named_key_by_name(NAMED_KEY_PENDING_FEE);
+    let pending_fee: Option<(u64, u64)> = storage::read(pending_fee_uref)
+        .unwrap_or_revert_with(Error::FeeNotFound)
+        .unwrap_or_revert_with(Error::FeeNotFound);
+    if let Some((fee, effective_at)) = pending_fee {
+        if u64::from(get_blocktime()) >= effective_at {
+            storage::write(fee_uref, fee);
+            storage::write(pending_fee_uref, Option::<(u64, u64)>::None);
+            return fee;
+        }
+    }
     storage::read::<u64>(fee_uref)
         .unwrap_or_revert_with(Error::FeeNotFound)
         .unwrap_or_revert_with(Error::FeeNotFound)
 }
+
+/// Returns the runtime arg with the given name, or None if the caller did not provide it
+/// 
+/// `runtime::get_named_arg` reverts when the arg is missing, so this reads it through the host functions instead.
+/// It reverts with `InvalidOptionalArg` if the arg is provided but is not a `T`
+pub(crate) fn get_optional_named_arg<T: bytesrepr::FromBytes>(name: &str) -> Option<T> {
+    let mut arg_size: usize = 0;
+    let ret = unsafe {
+        ext_ffi::casper_get_named_arg_size(
+            name.as_bytes().as_ptr(),
+            name.len(),
+            &mut arg_size as *mut usize,
+        )
+    };
+    match api_error::result_from(ret) {
+        Ok(()) => {}
+        Err(ApiError::MissingArgument) => return None,
+        Err(error) => revert(error),
+    }
+    let arg_bytes = if arg_size > 0 {
+        let data_non_null_ptr = contract_api::alloc_bytes(arg_size);
+        let ret = unsafe {
+            ext_ffi::casper_get_named_arg(
+                name.as_bytes().as_ptr(),
+                name.len(),
+                data_non_null_ptr.as_ptr(),
+                arg_size,
+            )
+        };
+        let data =
+            unsafe { Vec::from_raw_parts(data_non_null_ptr.as_ptr(), arg_size, arg_size) };
+        api_error::result_from(ret).unwrap_or_revert();
+        data
+    } else {
+        Vec::new()
+    };
+    Some(bytesrepr::deserialize(arg_bytes).unwrap_or_revert_with(Error::InvalidOptionalArg))
+}
+
+/// A shortcut function, which returns the paused flags of the contract (a bitmask of the `PAUSE_*` constants)
+pub(crate) fn get_paused() -> u8 {
+    storage::read::<u8>(get_named_key_by_name(NAMED_KEY_PAUSED))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// Reverts with `ContractPaused` if the entrypoint with the given `PAUSE_*` flag is paused
+pub(crate) fn when_not_paused(flag: u8) {
+    if get_paused() & flag != 0 {
+        revert(Error::ContractPaused);
+    }
+}
+
+/// Reverts with `MigrationInProgress` if the records of the contract are not all stored in their current layouts yet (until `migrate_storage` finishes)
+pub(crate) fn when_storage_migrated() {
+    let schema_version: u32 = storage::read(get_named_key_by_name(NAMED_KEY_SCHEMA_VERSION))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    if schema_version < SCHEMA_VERSION {
+        revert(Error::MigrationInProgress);
+    }
+}
+
+/// A shortcut function, which returns the time window (in ms) that a signed price quote is accepted for after its timestamp
+pub(crate) fn get_quote_freshness() -> u64 {
+    let quote_freshness_uref = get_key(NAMED_KEY_QUOTE_FRESHNESS)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .into_uref()
+        .unwrap_or_revert_with(Error::KeyNotUref);
+    storage::read::<u64>(quote_freshness_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
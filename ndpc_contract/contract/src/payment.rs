@@ -1,70 +1,61 @@
 use core::ops::{Add, Sub};
 
 use crate::{
+    balances::set_balance,
     constants::{
-        NAMED_KEY_DICT_APPROVED_NAME, NAMED_KEY_DICT_HOLDERS_NAME, NAMED_KEY_DICT_METADATAS_NAME,
-        NAMED_KEY_DICT_OWNERS_NAME, NAMED_KEY_DICT_PRODAPPROVED_NAME,
-        NAMED_KEY_DICT_PUBAPPROVED_NAME, RUNTIME_ARG_AMOUNT,
-        RUNTIME_ARG_APPROVED_ID, RUNTIME_ARG_CURRENT_PRICE_TIMESTAMP, RUNTIME_ARG_PURSE_ADDR,
-        RUNTIME_ARG_RECIPIENT, RUNTIME_ARG_SHIPPING_PRICE, RUNTIME_ARG_SIGNATURE,
-        RUNTIME_ARG_TAX_PRICE, RUNTIME_PRODUCT_PRICE, NAMED_KEY_HOLDERSCNT,
+        NAMED_KEY_DICT_BUYER_ORDERS, NAMED_KEY_DICT_ESCROW_BALANCES, NAMED_KEY_DICT_ORDERS,
+        NAMED_KEY_DICT_PRODUCER_ORDERS, NAMED_KEY_DICT_PUBLISHER_ORDERS, NAMED_KEY_ESCROW_PURSE,
+        NAMED_KEY_ORDERS_CNT, RUNTIME_ARG_AMOUNT, RUNTIME_ARG_ORDER_ID,
+        RUNTIME_ARG_APPROVED_ID, RUNTIME_ARG_ORDER_REFERENCE, RUNTIME_ARG_PURSE_ADDR, RUNTIME_ARG_QUOTE,
+        RUNTIME_ARG_RECIPIENT, RUNTIME_ARG_SHIPPING_PRICE, RUNTIME_ARG_SIGNATURES, RUNTIME_ARG_SIGNERS,
+        RUNTIME_ARG_TAX_PRICE, RUNTIME_PRODUCT_PRICE,
+        MAX_ORDER_REFERENCE_LENGTH, PAUSE_BUY, PAUSE_DIRECT_PAY, QUOTE_CURRENCY_PAIR,
     },
     event::{emit, DropLinkedEvent},
-    ndpc_types::{self, AsStrized, U64list},
+    ndpc_types::{AsStrized, Order, PriceQuote, ProvenanceReason},
     ndpc_utils::{
-        self, calculate_payment, get_approved_holder_by_id, get_droplinked_account,
-        get_nft_metadata, get_ratio_verifier, verify_signature, get_fee,
+        self, add_nft_holder, calculate_payment, decode_price_quote, get_immediate_caller,
+        get_quote_freshness, pay_party, transfer_to_treasury, verify_oracle_signatures, get_fee,
+        when_not_paused, when_storage_migrated,
     },
+    provenance::record_provenance,
+    storage_context::StorageContext,
     Error,
 };
 use alloc::{
     string::{String, ToString},
-    vec,
-    vec::Vec, format,
+    vec::Vec,
 };
 use casper_contract::{
     contract_api::{
-        runtime::{self, get_blocktime, get_caller, get_named_arg, revert},
+        runtime::{self, get_blocktime, get_named_arg, revert},
         storage,
         system::{
             get_purse_balance, transfer_from_purse_to_account, transfer_from_purse_to_public_key,
+            transfer_from_purse_to_purse,
         },
     },
     unwrap_or_revert::UnwrapOrRevert,
 };
-use casper_types::{account::AccountHash, ApiError, AsymmetricType, Key, PublicKey, U512};
-
-/// Buy Entrypoint's needed dicts
-fn get_buy_storage() -> (
-    casper_types::URef,
-    casper_types::URef,
-    casper_types::URef,
-    casper_types::URef,
-    casper_types::URef,
-    casper_types::URef,
-) {
-    (
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_OWNERS_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_APPROVED_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PUBAPPROVED_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_PRODAPPROVED_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_HOLDERS_NAME),
-        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_METADATAS_NAME),
-    )
-}
+use casper_types::{
+    bytesrepr::ToBytes, ApiError, AsymmetricType, CLValue, Key, PublicKey,
+    U512,
+};
 
 /// Buy entrypoint's runtime args
 fn get_buy_runtime_args() -> (
     alloc::string::String,
-    alloc::string::String,
+    Vec<PublicKey>,
+    Vec<String>,
     u64,
     u64,
     U512,
     U512,
 ) {
     (
-        runtime::get_named_arg::<String>(RUNTIME_ARG_CURRENT_PRICE_TIMESTAMP),
-        runtime::get_named_arg::<String>(RUNTIME_ARG_SIGNATURE),
+        runtime::get_named_arg::<String>(RUNTIME_ARG_QUOTE),
+        runtime::get_named_arg::<Vec<PublicKey>>(RUNTIME_ARG_SIGNERS),
+        runtime::get_named_arg::<Vec<String>>(RUNTIME_ARG_SIGNATURES),
         runtime::get_named_arg::<u64>(RUNTIME_ARG_APPROVED_ID),
         runtime::get_named_arg::<u64>(RUNTIME_ARG_AMOUNT),
         runtime::get_named_arg::<U512>(RUNTIME_ARG_SHIPPING_PRICE),
@@ -72,177 +63,260 @@ fn get_buy_runtime_args() -> (
     )
 }
 
+/// Decodes the quote given to buy, and checks it against the oracle signatures and the purchase it is used for
+/// 
+/// The quote should be for the `CSPR/USD` pair, signed over its canonical bytes by at least `oracle_threshold` distinct oracle signers, not older than the freshness window
+/// and not expired. If the quote is scoped to an `approved_id` or a `buyer` (an account or a contract package), they should match the current purchase
+fn verify_price_quote(
+    quote_hex: String,
+    signers: Vec<PublicKey>,
+    signatures: Vec<String>,
+    approved_id: u64,
+    buyer: Key,
+) -> PriceQuote {
+    let quote = decode_price_quote(&quote_hex);
+    let quote_bytes = quote.to_bytes().unwrap_or_revert_with(Error::MalformedQuote);
+    verify_oracle_signatures(signers, signatures, &quote_bytes);
+    if quote.currency_pair != QUOTE_CURRENCY_PAIR {
+        revert(ApiError::from(Error::QuoteCurrencyMismatch));
+    }
+    if quote.expiry < quote.timestamp {
+        revert(ApiError::from(Error::MalformedQuote));
+    }
+
+    let latest_block_time: u64 = u64::from(get_blocktime());
+    if latest_block_time > quote.timestamp + get_quote_freshness() {
+        revert(ApiError::from(Error::InvalidTimestamp));
+    }
+    if latest_block_time > quote.expiry {
+        revert(ApiError::from(Error::QuoteExpired));
+    }
+
+    if let Some(quote_approved_id) = quote.approved_id {
+        if quote_approved_id != approved_id {
+            revert(ApiError::from(Error::QuoteNotForListing));
+        }
+    }
+    if let Some(quote_buyer) = quote.buyer {
+        if quote_buyer != buyer {
+            revert(ApiError::from(Error::QuoteNotForBuyer));
+        }
+    }
+    quote
+}
+
+/// Adds the order id to the order list of the party (an account or a contract package), in the given dictionary
+fn add_to_order_list(ctx: &mut StorageContext, dict_name: &'static str, party: Key, order_id: u64) {
+    ctx.list(dict_name, &party.as_string()).push(order_id);
+}
+
+/// Stores the order in the `orders` dictionary under a new sequential order id, and adds it to the order lists of its buyer, producer and publisher
+/// 
+/// Returns the order id
+fn record_order(ctx: &mut StorageContext, order: Order) -> u64 {
+    let orders_cnt_uref = ctx.uref(NAMED_KEY_ORDERS_CNT);
+    let orders_cnt: u64 = storage::read(orders_cnt_uref)
+        .unwrap_or_revert()
+        .unwrap_or_revert();
+    let order_id = orders_cnt + 1;
+    storage::write(orders_cnt_uref, order_id);
+    add_to_order_list(ctx, NAMED_KEY_DICT_BUYER_ORDERS, order.buyer, order_id);
+    add_to_order_list(ctx, NAMED_KEY_DICT_PRODUCER_ORDERS, order.producer, order_id);
+    add_to_order_list(ctx, NAMED_KEY_DICT_PUBLISHER_ORDERS, order.publisher, order_id);
+    storage::dictionary_put(
+        ctx.uref(NAMED_KEY_DICT_ORDERS),
+        order_id.to_string().as_str(),
+        order,
+    );
+    order_id
+}
+
+/// Converts an amount of motes to a u64, it reverts with `PriceOverflow` if it does not fit in one
+fn to_u64_amount(amount: U512) -> u64 {
+    if amount > U512::from(u64::MAX) {
+        revert(Error::PriceOverflow);
+    }
+    amount.as_u64()
+}
+
 /// Buy entrypoint of the droplinked contract
 /// 
-/// Gets the ratio verifier, gets the incoming purse, splits its tokens to the producer, publisher and droplinked based on the fee and comission and shipping and tax, and ratio of casper/usd
-/// Verifies the signature of the droplinked account on the ratio, and checks the time provided to it (to prevent time based ratio attacks)
-/// Transfers the calculated amounts to corresponding accounts, and transfers the NFT
+/// Gets the price quote, gets the incoming purse, splits its tokens to the producer, publisher and droplinked based on the fee and comission and shipping and tax, and ratio of casper/usd
+/// Verifies the signatures of the oracle signers on the quote, and checks the time provided in it (to prevent time based ratio attacks)
+/// Transfers the calculated amounts to corresponding accounts, refunds what is left in the purse to the buyer, and transfers the NFT
+/// The buyer is the immediate caller, an account or a contract package. The shares of a producer or a publisher which is a contract package are held in escrow
+/// for it to `withdraw`, and a contract buyer gets no refund, as what is left stays in the purse it passed
+/// The purchase is recorded as an order, in the `orders` dictionary and the order lists of the buyer, producer and publisher, and is appended to the provenance log of the token
+/// # Emits
+/// `DropLinkedEvent::Buy`, `DropLinkedEvent::Settlement`
 #[no_mangle]
 pub extern "C" fn buy() {
-    let ratio_verifier = get_ratio_verifier();
-    
-    let (mp, sig, approved_id, amount, shipping_price, tax_price) = get_buy_runtime_args();
+    when_not_paused(PAUSE_BUY);
+    when_storage_migrated();
+    let (quote_hex, signers, signatures, approved_id, amount, shipping_price, tax_price) =
+        get_buy_runtime_args();
     
     let purse = {
         let purse_key: Key = runtime::get_named_arg(RUNTIME_ARG_PURSE_ADDR);
         purse_key.into_uref().unwrap_or_revert()
     };
 
+    let buyer = get_immediate_caller();
+    let quote = verify_price_quote(quote_hex, signers, signatures, approved_id, buyer);
+    let price_ratio: u64 = quote.ratio;
+    // decimals is at most QUOTE_MAX_DECIMALS, so the scale could not overflow
+    let ratio_scale = U512::from(100u64) * U512::from(10u64).pow(U512::from(quote.decimals));
 
-    if !verify_signature(ratio_verifier, sig, mp.clone()) {
-        revert(ApiError::from(Error::InvalidSignature));
-    }
-
-    let signed_data = mp.split(',').collect::<Vec<&str>>();
-    
-    let price_ratio: u64 = signed_data[0].parse::<u64>().unwrap();
-    let provided_timestamp = signed_data[1].parse::<u64>().unwrap();
-    
-    let latest_block_time: u64 = u64::from(get_blocktime()); //1)
-
-    if latest_block_time > provided_timestamp + 130000u64 {
-        revert(ApiError::from(Error::InvalidTimestamp));
-    }
-
-    let (
-        _owners_dict,
-        approved_dict,
-        _publishers_approved_dict,
-        _producers_approved_dict,
-        _holders_dict,
-        metadata_dict,
-    ) = get_buy_storage();
+    // the dicts and records are read once, and cached for the rest of the call
+    let mut ctx = StorageContext::new();
     
-    let caller_string = get_caller().as_string();
     
-    
-    
-    let mut _approved_holder: ndpc_types::ApprovedNFT = get_approved_holder_by_id(approved_dict, approved_id); //1)
+    let _approved_holder = ctx.approved_nft(approved_id); //1)
     let token_id = _approved_holder.token_id;
-
-    
+    let holder_id = _approved_holder.holder_id;
     
-    let token_metadata = get_nft_metadata(token_id.to_string(), metadata_dict); //2)
-    
-    let producer_hash: AccountHash = _approved_holder.owneraccount;
-    let publisher_hash: AccountHash = _approved_holder.publisheraccount;
-    let producer_string: String = producer_hash.as_string();
-    let publisher_string: String = publisher_hash.as_string();
-
+    let producer_hash: Key = _approved_holder.owneraccount;
+    let publisher_hash: Key = _approved_holder.publisheraccount;
     
     if amount > _approved_holder.amount {
         runtime::revert(ApiError::from(Error::NotEnoughAmount));
     }
+    // approve does not lock the amount of the holder, so the other listings on it could have sold it already
+    if amount > ctx.holder(holder_id).amount {
+        runtime::revert(ApiError::from(Error::NotEnoughAmount));
+    }
+    
+    let token_metadata = ctx.nft_metadata(token_id); //2)
     
     // Do a function call to transfer function 
     // EIFUH
     
     let fee = get_fee();
-    let product_price = (token_metadata.price * price_ratio * amount)/100u64;
-    let total_amount = product_price + shipping_price.as_u64()+ tax_price.as_u64();
-    let droplinked_share = (product_price * fee) / 10000u64 ;
-    let producer_share = ((product_price - droplinked_share) * ((10000u64 - token_metadata.comission)))/10000u64 + shipping_price.as_u64() + tax_price.as_u64();
+    // the prices are computed in U512, as price * ratio * amount could overflow a u64, and each of them should fit in a u64
+    let shipping_price = to_u64_amount(shipping_price);
+    let tax_price = to_u64_amount(tax_price);
+    let commission_rest = 10000u64
+        .checked_sub(token_metadata.comission)
+        .unwrap_or_revert_with(Error::PriceOverflow);
+    let product_price_motes = U512::from(token_metadata.price) * U512::from(price_ratio) * U512::from(amount) / ratio_scale;
+    let droplinked_share_motes = product_price_motes * U512::from(fee) / U512::from(10000u64);
+    let product_price = to_u64_amount(product_price_motes);
+    let total_amount = to_u64_amount(product_price_motes + U512::from(shipping_price) + U512::from(tax_price));
+    let droplinked_share = to_u64_amount(droplinked_share_motes);
+    let producer_share = to_u64_amount(
+        (product_price_motes - droplinked_share_motes) * U512::from(commission_rest) / U512::from(10000u64)
+            + U512::from(shipping_price)
+            + U512::from(tax_price),
+    );
     let publisher_share = total_amount - producer_share - droplinked_share;
 
-    let log = format!(
-        "Buyer: {}, Producer: {}, Publisher: {}, Amount: {}, Price: {}, Shipping: {}, Tax: {}, Droplinked Share: {}, Producer Share: {}, Publisher Share: {}",
-        caller_string,
-        producer_string,
-        publisher_string,
+    //transfer to producer
+    pay_party(purse, producer_hash, U512::from(producer_share));
+    //transfer to publisher
+    pay_party(purse, publisher_hash, U512::from(publisher_share));
+    //transfer to droplinked's treasury
+    transfer_to_treasury(purse, U512::from(droplinked_share));
+    //refund what is left in the purse to the buyer, if it is an account
+    let mut refund = U512::zero();
+    if let Key::Account(buyer_account) = buyer {
+        refund = get_purse_balance(purse).unwrap_or_revert_with(Error::GetBalance);
+        if !refund.is_zero() {
+            transfer_from_purse_to_account(purse, buyer_account, refund, None)
+                .unwrap_or_revert_with(Error::TransferFailed);
+        }
+    }
+    
+    let order_id = record_order(&mut ctx, Order {
+        buyer,
+        approved_id,
+        token_id,
         amount,
         product_price,
         shipping_price,
         tax_price,
-        droplinked_share,
-        producer_share,
-        publisher_share
-    );
-
-    runtime::put_key("_log", storage::new_uref(log).into());
-    runtime::put_key("_purse", purse.into());
-
-    //transfer to producer
-    transfer_from_purse_to_account(purse, producer_hash, U512::from(producer_share), None)
-        .unwrap_or_revert_with(Error::TransferFailed);
-    //transfer to publisher
-    transfer_from_purse_to_account(purse, publisher_hash, U512::from(publisher_share), None)
-        .unwrap_or_revert_with(Error::TransferFailed);
-    //transfer to droplinked
-    transfer_from_purse_to_public_key(purse, get_ratio_verifier(), U512::from(droplinked_share), None)
-        .unwrap_or_revert_with(Error::TransferFailed);
+        fee,
+        ratio: price_ratio,
+        ratio_decimals: quote.decimals,
+        producer: producer_hash,
+        producer_amount: producer_share,
+        publisher: publisher_hash,
+        publisher_amount: publisher_share,
+        treasury_amount: droplinked_share,
+        refund,
+        timestamp: u64::from(get_blocktime()),
+    });
     
     emit(DropLinkedEvent::Buy {
         amount,
         approved_id,
-        buyer: get_caller(),
+        buyer,
+    });
+    emit(DropLinkedEvent::Settlement {
+        order_id,
+        buyer,
+        approved_id,
+        token_id,
+        amount,
+        product_price,
+        ratio: price_ratio,
+        ratio_decimals: quote.decimals,
+        shipping_price,
+        tax_price,
+        fee,
+        producer: producer_hash,
+        producer_amount: producer_share,
+        publisher: publisher_hash,
+        publisher_amount: publisher_share,
+        treasury_amount: droplinked_share,
+        refund,
     });
     //
 
-    let mut approved_holder = get_approved_holder_by_id(approved_dict, approved_id);
-    approved_holder.amount -= amount;
+    ctx.approved_nft(approved_id).amount -= amount;
+    ctx.write_approved_nft(approved_id);
 
-    let mut holder: ndpc_types::NFTHolder = storage::dictionary_get::<ndpc_types::NFTHolder>(
-        _holders_dict,
-        approved_holder.holder_id.to_string().as_str(),
-    )
-    .unwrap_or_revert_with(Error::HolderDoesentExist)
-    .unwrap_or_revert_with(Error::HolderDoesentExist);
-    holder.amount -= amount;
-    storage::dictionary_put(
-        _holders_dict,
-        approved_holder.holder_id.to_string().as_str(),
-        holder,
-    );
-    let token_id = approved_holder.token_id;
-    storage::dictionary_put(
-        approved_dict,
-        approved_id.to_string().as_str(),
-        approved_holder,
+    ctx.holder(holder_id).amount -= amount;
+    let producer_amount = ctx.holder(holder_id).amount;
+    ctx.write_holder(holder_id);
+    set_balance(&mut ctx, producer_hash, token_id, holder_id, producer_amount);
+    // the bought amount goes to the buyer's holder of the token, or to a new holder in the buyer's owners list
+    add_nft_holder(&mut ctx, buyer, token_id, amount);
+    record_provenance(
+        &mut ctx,
+        token_id,
+        Some(producer_hash),
+        Some(buyer),
+        amount,
+        ProvenanceReason::Buy,
     );
-    let new_holder = ndpc_types::NFTHolder::new(amount, token_id);
-    let holders_cnt_uref = ndpc_utils::get_named_key_by_name(NAMED_KEY_HOLDERSCNT);
-    let mut holders_cnt: u64 = storage::read(holders_cnt_uref)
-        .unwrap_or_revert()
-        .unwrap_or_revert();
-    holders_cnt += 1;
-    storage::write(holders_cnt_uref, holders_cnt);
-    let new_holder_id = holders_cnt;
-    storage::dictionary_put(_holders_dict, new_holder_id.to_string().as_str(), new_holder);
-    let caller_list_opt =
-        storage::dictionary_get::<U64list>(_holders_dict, &caller_string).unwrap_or_revert();
-    if caller_list_opt.is_none() {
-        let mut new_list = U64list::new();
-        new_list.add(new_holder_id);
-        storage::dictionary_put(_holders_dict, &caller_string, new_list);
-    } else {
-        let mut caller_list = caller_list_opt.unwrap_or_revert();
-        caller_list.add(new_holder_id);
-        storage::dictionary_put(_holders_dict, &caller_string, caller_list);
-    }
-    let caller_tokens_opt =
-        storage::dictionary_get::<U64list>(_owners_dict, &caller_string).unwrap_or_revert();
-    if caller_tokens_opt.is_none() {
-        let mut new_list = U64list::new();
-        new_list.add(new_holder_id);
-        storage::dictionary_put(_owners_dict, &caller_string, new_list);
-    } else {
-        let mut caller_tokens = caller_tokens_opt.unwrap_or_revert();
-        caller_tokens.add(new_holder_id);
-        storage::dictionary_put(_owners_dict, &caller_string, caller_tokens);
-    }
-    
 }
 
 /// Direct buy is used to proxy the casper transfers through droplinked's contract, to transfer droplinked's share to its account, and transfer the rest of it to the producer
 /// 
-/// fee% of the product price should go to droplinked's account, and the rest of it (tax price + shipping price + rest of the product price) to the producer's account
+/// fee% of the product price should go to droplinked's treasury, and the rest of it (tax price + shipping price + rest of the product price) to the producer's account.
+/// What is left in the purse is refunded to the payer if it is an account (a contract payer keeps it in the purse it passed), so an overpayment is never forwarded
+/// 
+/// The caller may pass an optional `order_reference` (at most 64 bytes), which is echoed in the Payment event so the payment can be matched to an off-chain order
+/// 
+/// # Emits
+/// Payment, with the payer, the recipient, the quoted prices, the fee and the amounts sent to the treasury and the recipient
 #[no_mangle]
 pub extern "C" fn direct_pay() {
+    when_not_paused(PAUSE_DIRECT_PAY);
     let product_price: U512 = get_named_arg(RUNTIME_PRODUCT_PRICE);
     let product_shipping: U512 = get_named_arg(RUNTIME_ARG_SHIPPING_PRICE);
     let product_tax: U512 = get_named_arg(RUNTIME_ARG_TAX_PRICE);
     let recipient_key_hex: String = get_named_arg(RUNTIME_ARG_RECIPIENT);
-    let recipient: PublicKey = PublicKey::from_hex(recipient_key_hex.clone()).unwrap();
+    let recipient: PublicKey = PublicKey::from_hex(recipient_key_hex)
+        .ok()
+        .unwrap_or_revert_with(Error::InvalidRecipientKey);
+    let order_reference: Option<String> =
+        ndpc_utils::get_optional_named_arg(RUNTIME_ARG_ORDER_REFERENCE);
+    if let Some(order_reference) = &order_reference {
+        if order_reference.is_empty() || order_reference.len() > MAX_ORDER_REFERENCE_LENGTH {
+            runtime::revert(Error::InvalidOrderReference);
+        }
+    }
 
     let purse = {
         let purse_key: Key = runtime::get_named_arg(RUNTIME_ARG_PURSE_ADDR);
@@ -257,14 +331,85 @@ pub extern "C" fn direct_pay() {
     }
     let payment_details = calculate_payment(product_price, product_shipping, product_tax, fee);
     let droplinked_share = payment_details.droplinked;
-    let recipient_part = purse_balance.sub(droplinked_share);
-    transfer_from_purse_to_public_key(purse, get_droplinked_account(), droplinked_share, None)
-        .unwrap_or_revert_with(Error::TransferFailed);
-    transfer_from_purse_to_public_key(purse, recipient, recipient_part, None)
+    let recipient_part = payment_details.recipient;
+    transfer_to_treasury(purse, droplinked_share);
+    transfer_from_purse_to_public_key(purse, recipient.clone(), recipient_part, None)
         .unwrap_or_revert_with(Error::TransferFailed);
+    //refund what is left in the purse to the payer, if it is an account
+    let payer = get_immediate_caller();
+    if let Key::Account(payer_account) = payer {
+        let refund = purse_balance.sub(droplinked_share).sub(recipient_part);
+        if !refund.is_zero() {
+            transfer_from_purse_to_account(purse, payer_account, refund, None)
+                .unwrap_or_revert_with(Error::TransferFailed);
+        }
+    }
 
     emit(DropLinkedEvent::Payment {
-        recipient: recipient_key_hex,
-        amounts: vec![product_price, product_shipping, product_tax],
+        payer,
+        recipient,
+        product_price,
+        shipping_price: product_shipping,
+        tax_price: product_tax,
+        fee,
+        treasury_amount: droplinked_share,
+        recipient_amount: recipient_part,
+        order_reference,
+    });
+}
+
+/// get_order entrypoint of the contract
+/// 
+/// Gets `order_id` and returns the order stored by buy, It would revert with `OrderNotFound` if there is no order with that id
+/// # Returns
+/// `order` : `Order`
+#[no_mangle]
+pub extern "C" fn get_order() {
+    when_storage_migrated();
+    let order_id: u64 = runtime::get_named_arg(RUNTIME_ARG_ORDER_ID);
+    let order = storage::dictionary_get::<Order>(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_ORDERS),
+        order_id.to_string().as_str(),
+    )
+    .unwrap_or_revert()
+    .unwrap_or_revert_with(Error::OrderNotFound);
+    runtime::ret(CLValue::from_t(order).unwrap_or_revert());
+}
+
+/// withdraw entrypoint of the contract
+/// 
+/// A contract package could not be transferred to, so its shares of the sales (as a producer or a publisher) are held in the escrow purse.
+/// The package calls withdraw with `purse_addr`, a purse it owns, to get its whole escrowed balance; it would revert with `NothingToWithdraw` if the balance is zero.
+/// It is never paused, so escrowed amounts could always be withdrawn
+/// # Emits
+/// `DropLinkedEvent::Withdrawn`
+#[no_mangle]
+pub extern "C" fn withdraw() {
+    let caller = get_immediate_caller();
+    let purse = {
+        let purse_key: Key = runtime::get_named_arg(RUNTIME_ARG_PURSE_ADDR);
+        purse_key
+            .into_uref()
+            .unwrap_or_revert_with(Error::PuseIsNotValid)
+    };
+    let balances_dict = ndpc_utils::get_named_key_by_name(NAMED_KEY_DICT_ESCROW_BALANCES);
+    let caller_string = caller.as_string();
+    let amount: U512 = storage::dictionary_get(balances_dict, &caller_string)
+        .unwrap_or_revert()
+        .unwrap_or_default();
+    if amount.is_zero() {
+        runtime::revert(Error::NothingToWithdraw);
+    }
+    storage::dictionary_put(balances_dict, &caller_string, U512::zero());
+    transfer_from_purse_to_purse(
+        ndpc_utils::get_named_key_by_name(NAMED_KEY_ESCROW_PURSE),
+        purse,
+        amount,
+        None,
+    )
+    .unwrap_or_revert_with(Error::TransferFailed);
+    emit(DropLinkedEvent::Withdrawn {
+        recipient: caller,
+        amount,
     });
 }
@@ -0,0 +1,80 @@
+use alloc::{string::ToString, vec::Vec};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{CLValue, Key};
+
+use crate::{
+    constants::{
+        MAX_PAGE_SIZE, NAMED_KEY_DICT_PROVENANCE, NAMED_KEY_DICT_TOKEN_PROVENANCE,
+        NAMED_KEY_PROVENANCE_CNT, RUNTIME_ARG_LIMIT, RUNTIME_ARG_OFFSET, RUNTIME_ARG_TOKEN_ID,
+    },
+    ndpc_types::{ProvenanceEntry, ProvenanceReason},
+    ndpc_utils::when_storage_migrated,
+    storage_context::StorageContext,
+    Error,
+};
+
+/// Appends a movement of `quantity` of the token, from `from` to `to`, to the provenance log of the token
+///
+/// The entry is stored in the `provenance` dictionary under a new sequential id, and the id is added to the indexed list of the token in `token_provenance`
+pub fn record_provenance(
+    ctx: &mut StorageContext,
+    token_id: u64,
+    from: Option<Key>,
+    to: Option<Key>,
+    quantity: u64,
+    reason: ProvenanceReason,
+) {
+    let provenance_cnt_uref = ctx.uref(NAMED_KEY_PROVENANCE_CNT);
+    let provenance_cnt: u64 = storage::read(provenance_cnt_uref)
+        .unwrap_or_revert()
+        .unwrap_or_revert();
+    let entry_id = provenance_cnt + 1;
+    storage::write(provenance_cnt_uref, entry_id);
+    storage::dictionary_put(
+        ctx.uref(NAMED_KEY_DICT_PROVENANCE),
+        entry_id.to_string().as_str(),
+        ProvenanceEntry {
+            from,
+            to,
+            quantity,
+            blocktime: u64::from(runtime::get_blocktime()),
+            reason,
+        },
+    );
+    ctx.list(NAMED_KEY_DICT_TOKEN_PROVENANCE, &token_id.to_string())
+        .push(entry_id);
+}
+
+/// get_token_provenance entrypoint of the contract
+///
+/// Gets `token_id`, `offset` and `limit` (at most `MAX_PAGE_SIZE`), and returns a page of the provenance log of the token : its mints and sales,
+/// oldest first. Only mint and buy move tokens between holders (there is no transfer or burn entrypoint), so they are the only entries logged. A token without any movement gets an empty page
+/// # Returns
+/// `token_provenance` : `Vec<ProvenanceEntry>`
+#[no_mangle]
+pub extern "C" fn get_token_provenance() {
+    when_storage_migrated();
+    let token_id: u64 = runtime::get_named_arg(RUNTIME_ARG_TOKEN_ID);
+    let offset: u64 = runtime::get_named_arg(RUNTIME_ARG_OFFSET);
+    let limit: u64 = runtime::get_named_arg(RUNTIME_ARG_LIMIT);
+    if limit > MAX_PAGE_SIZE {
+        runtime::revert(Error::PageTooLarge);
+    }
+    let mut ctx = StorageContext::new();
+    let entry_ids = ctx
+        .list(NAMED_KEY_DICT_TOKEN_PROVENANCE, &token_id.to_string())
+        .page(offset, limit);
+    let provenance_dict = ctx.uref(NAMED_KEY_DICT_PROVENANCE);
+    let entries: Vec<ProvenanceEntry> = entry_ids
+        .into_iter()
+        .map(|entry_id| {
+            storage::dictionary_get(provenance_dict, entry_id.to_string().as_str())
+                .unwrap_or_revert()
+                .unwrap_or_revert_with(Error::KeyNotFound)
+        })
+        .collect();
+    runtime::ret(CLValue::from_t(entries).unwrap_or_revert());
+}
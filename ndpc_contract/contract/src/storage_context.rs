@@ -0,0 +1,167 @@
+use alloc::{collections::BTreeMap, string::ToString};
+use casper_contract::contract_api::storage;
+use casper_types::URef;
+
+use crate::{
+    constants::{
+        NAMED_KEY_APPROVED_CNT, NAMED_KEY_DICT_APPROVED_NAME, NAMED_KEY_DICT_HOLDERS_NAME,
+        NAMED_KEY_DICT_METADATAS_NAME, NAMED_KEY_DICT_OWNERS_NAME,
+        NAMED_KEY_DICT_PRODAPPROVED_NAME, NAMED_KEY_DICT_PROD_REQS,
+        NAMED_KEY_DICT_PUBAPPROVED_NAME, NAMED_KEY_DICT_PUB_REQS, NAMED_KEY_DICT_REQ_OBJ,
+        NAMED_KEY_DICT_TOKEN_ID_BY_HASH_NAME, NAMED_KEY_DICT_TOTAL_SUPPLY, NAMED_KEY_HOLDERSCNT,
+        NAMED_KEY_REQ_CNT, NAMED_KEY_TOKENSCNT,
+    },
+    indexed_list::IndexedList,
+    ndpc_types::{ApprovedNFT, NFTHolder, NftMetadata, PublishRequest},
+    ndpc_utils::{
+        get_approved_holder_by_id, get_holder_by_id, get_named_key_by_name, get_nft_metadata,
+        get_request_by_id,
+    },
+};
+
+/// The storage that an entrypoint works with, resolved on its first use and cached for the rest of the execution
+///
+/// Each `runtime::get_key` looks the name up in the named keys of the contract, and each `dictionary_get` is a global state read, so the URefs of the
+/// dictionaries and counters, and the records read through the context, are only resolved once per call. A record is changed through the `&mut` that its getter
+/// returns, and is written back with its `write_` method (which also takes it out of the cache, so a later read sees the stored record)
+#[derive(Default)]
+pub struct StorageContext {
+    urefs: BTreeMap<&'static str, URef>,
+    approved_nfts: BTreeMap<u64, ApprovedNFT>,
+    holders: BTreeMap<u64, NFTHolder>,
+    metadatas: BTreeMap<u64, NftMetadata>,
+    requests: BTreeMap<u64, PublishRequest>,
+}
+
+impl StorageContext {
+    /// Returns an empty context, nothing is resolved until it is used
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the URef of the named key, resolving it on its first use
+    pub fn uref(&mut self, name: &'static str) -> URef {
+        *self
+            .urefs
+            .entry(name)
+            .or_insert_with(|| get_named_key_by_name(name))
+    }
+
+    /// Returns the indexed list stored under the key, in the dictionary with the given name
+    pub fn list(&mut self, dict_name: &'static str, key: &str) -> IndexedList {
+        IndexedList::from_uref(self.uref(dict_name), key)
+    }
+
+    pub fn approved_dict(&mut self) -> URef {
+        self.uref(NAMED_KEY_DICT_APPROVED_NAME)
+    }
+
+    pub fn holders_dict(&mut self) -> URef {
+        self.uref(NAMED_KEY_DICT_HOLDERS_NAME)
+    }
+
+    pub fn metadatas_dict(&mut self) -> URef {
+        self.uref(NAMED_KEY_DICT_METADATAS_NAME)
+    }
+
+    pub fn requests_dict(&mut self) -> URef {
+        self.uref(NAMED_KEY_DICT_REQ_OBJ)
+    }
+
+    pub fn token_id_by_hash_dict(&mut self) -> URef {
+        self.uref(NAMED_KEY_DICT_TOKEN_ID_BY_HASH_NAME)
+    }
+
+    pub fn total_supply_dict(&mut self) -> URef {
+        self.uref(NAMED_KEY_DICT_TOTAL_SUPPLY)
+    }
+
+    pub fn approved_cnt(&mut self) -> URef {
+        self.uref(NAMED_KEY_APPROVED_CNT)
+    }
+
+    pub fn holders_cnt(&mut self) -> URef {
+        self.uref(NAMED_KEY_HOLDERSCNT)
+    }
+
+    pub fn request_cnt(&mut self) -> URef {
+        self.uref(NAMED_KEY_REQ_CNT)
+    }
+
+    pub fn tokens_cnt(&mut self) -> URef {
+        self.uref(NAMED_KEY_TOKENSCNT)
+    }
+
+    /// Returns the holder ids of the owner (the `owners` dictionary)
+    pub fn owner_holders(&mut self, owner: &str) -> IndexedList {
+        self.list(NAMED_KEY_DICT_OWNERS_NAME, owner)
+    }
+
+    /// Returns the pending request ids of the producer (the `producer_requests` dictionary)
+    pub fn producer_requests(&mut self, producer: &str) -> IndexedList {
+        self.list(NAMED_KEY_DICT_PROD_REQS, producer)
+    }
+
+    /// Returns the pending request ids of the publisher (the `publiser_requests` dictionary)
+    pub fn publisher_requests(&mut self, publisher: &str) -> IndexedList {
+        self.list(NAMED_KEY_DICT_PUB_REQS, publisher)
+    }
+
+    /// Returns the approved ids of the producer (the `producers_approved` dictionary)
+    pub fn producer_approved(&mut self, producer: &str) -> IndexedList {
+        self.list(NAMED_KEY_DICT_PRODAPPROVED_NAME, producer)
+    }
+
+    /// Returns the approved ids of the publisher (the `publishers_approved` dictionary)
+    pub fn publisher_approved(&mut self, publisher: &str) -> IndexedList {
+        self.list(NAMED_KEY_DICT_PUBAPPROVED_NAME, publisher)
+    }
+
+    /// Returns the approved listing, it would revert with `ApprovedHolderDoesentExist` if there is none with that id
+    pub fn approved_nft(&mut self, approved_id: u64) -> &mut ApprovedNFT {
+        let dict = self.approved_dict();
+        self.approved_nfts
+            .entry(approved_id)
+            .or_insert_with(|| get_approved_holder_by_id(dict, approved_id))
+    }
+
+    /// Writes the (possibly changed) approved listing back to the `approved` dictionary
+    pub fn write_approved_nft(&mut self, approved_id: u64) {
+        let dict = self.approved_dict();
+        if let Some(approved_nft) = self.approved_nfts.remove(&approved_id) {
+            storage::dictionary_put(dict, approved_id.to_string().as_str(), approved_nft);
+        }
+    }
+
+    /// Returns the holder, it would revert with `HolderDoesentExist` if there is none with that id
+    pub fn holder(&mut self, holder_id: u64) -> &mut NFTHolder {
+        let dict = self.holders_dict();
+        self.holders
+            .entry(holder_id)
+            .or_insert_with(|| get_holder_by_id(dict, holder_id))
+    }
+
+    /// Writes the (possibly changed) holder back to the `holders` dictionary
+    pub fn write_holder(&mut self, holder_id: u64) {
+        let dict = self.holders_dict();
+        if let Some(holder) = self.holders.remove(&holder_id) {
+            storage::dictionary_put(dict, holder_id.to_string().as_str(), holder);
+        }
+    }
+
+    /// Returns the metadata of the token, it would revert with `MetadataDoesentExist` if the token is not minted
+    pub fn nft_metadata(&mut self, token_id: u64) -> &NftMetadata {
+        let dict = self.metadatas_dict();
+        self.metadatas
+            .entry(token_id)
+            .or_insert_with(|| get_nft_metadata(token_id.to_string(), dict))
+    }
+
+    /// Returns the publish request, it would revert with `RequestDoesntExist` if there is none with that id
+    pub fn request(&mut self, request_id: u64) -> &PublishRequest {
+        let dict = self.requests_dict();
+        self.requests
+            .entry(request_id)
+            .or_insert_with(|| get_request_by_id(dict, request_id))
+    }
+}
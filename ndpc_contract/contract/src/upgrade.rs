@@ -0,0 +1,322 @@
+use alloc::{string::{String, ToString}, vec::Vec};
+use casper_contract::{
+    contract_api::{runtime, storage, system},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{account::AccountHash, bytesrepr::ToBytes, CLTyped, CLValue, Key, URef};
+
+use crate::{
+    access_control::{only_operator, only_owner},
+    constants::{
+        CONTRACT_VERSION, MAX_CLEANUP_BATCH, NAMED_KEY_ACCESS_UREF, MAX_MIGRATION_BATCH, NAMED_KEY_APPROVED_CNT,
+        NAMED_KEY_CONTRACT_VERSION, NAMED_KEY_DICT_APPROVED_NAME, NAMED_KEY_DICT_HOLDERS_NAME,
+        NAMED_KEY_DICT_METADATAS_NAME, NAMED_KEY_DICT_REQ_OBJ, NAMED_KEY_HOLDERSCNT,
+        NAMED_KEY_MIGRATION_CURSOR, NAMED_KEY_REQ_CNT, NAMED_KEY_SCHEMA_VERSION, NAMED_KEY_TOKENSCNT,
+        RUNTIME_ARG_ACCOUNTS, SCHEMA_VERSION, NAMED_KEY_DICT_BUYER_ORDERS,
+        NAMED_KEY_DICT_EVENTS, NAMED_KEY_DICT_ORDERS, NAMED_KEY_DICT_PRODUCER_ORDERS,
+        NAMED_KEY_DICT_PROPOSALS, NAMED_KEY_DICT_PUBLISHER_ORDERS, NAMED_KEY_ORDERS_CNT, NAMED_KEY_EVENTS_CES_VERSION, NAMED_KEY_EVENTS_LENGTH,
+        NAMED_KEY_EVENTS_SCHEMA, NAMED_KEY_GOVERNANCE_DELAY, NAMED_KEY_GOVERNANCE_THRESHOLD, NAMED_KEY_GOVERNORS,
+        NAMED_KEY_PROPOSALS_CNT, NAMED_KEY_UPGRADE_AUTHORIZED, NAMED_KEY_DICT_ESCROW_BALANCES,
+        NAMED_KEY_ESCROW_PURSE, NAMED_KEY_DICT_PROVENANCE, NAMED_KEY_DICT_TOKEN_PROVENANCE,
+        NAMED_KEY_PROVENANCE_CNT, NAMED_KEY_DICT_BALANCES, NAMED_KEY_DICT_TOKEN_HOLDERS,
+        NAMED_KEY_DICT_HOLDER_OWNERS, NAMED_KEY_CEP85_NAME, NAMED_KEY_CEP85_URI,
+        CEP85_COLLECTION_NAME, NAMED_KEY_OWNER, NAMED_KEY_PENDING_OWNER,
+    },
+    event::{emit, get_schemas, DropLinkedEvent, CES_VERSION},
+    governance::consume_upgrade_authorization,
+    ndpc_types::{
+        ApprovedNFT, AsStrized, Legacy, LegacyRecord, NFTHolder, NftMetadata, Order, Proposal,
+        PublishRequest, U64list,
+    },
+    ndpc_utils::get_named_key_by_name,
+    Error,
+};
+
+/// A getter function, which returns the version of the contract that the stored state was last migrated to
+pub fn get_contract_version() -> u32 {
+    storage::read(get_named_key_by_name(NAMED_KEY_CONTRACT_VERSION))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+}
+
+/// upgrade_access entrypoint of the contract
+/// 
+/// It is called by the session code of an upgrade, before it adds the new version. The access uref of the contract package is only stored in the named keys
+/// of the contract, and this is the only way out for it : it returns the uref to the calling session, so that it could add the new version and disable this one.
+/// It would revert if the caller is not the owner, or if governance is configured and the upgrade is not authorized by an executed `AuthorizeUpgrade` proposal,
+/// whose authorization is consumed
+#[no_mangle]
+pub extern "C" fn upgrade_access() {
+    only_owner();
+    consume_upgrade_authorization();
+    runtime::ret(CLValue::from_t(get_named_key_by_name(NAMED_KEY_ACCESS_UREF)).unwrap_or_revert());
+}
+
+/// migrate entrypoint of the contract
+/// 
+/// It is called once by the session code, right after a new version is added to the contract package, and is only callable by the migrator group.
+/// The new version already has the named keys and dictionaries of the previous one; migrate runs the migration steps of every version
+/// between the stored `contract_version` and `CONTRACT_VERSION`, and then stores `CONTRACT_VERSION`. It would revert if the stored state
+/// is of a newer version than this code (a downgrade), if the access uref is not passed to the named keys of the new version,
+/// or if an upgrade from a version before `upgrade_access` is not authorized by an executed `AuthorizeUpgrade` proposal while governance is configured
+/// # Emits
+/// `DropLinkedEvent::ContractMigrated`
+#[no_mangle]
+pub extern "C" fn migrate() {
+    let old_version = get_contract_version();
+    if old_version > CONTRACT_VERSION {
+        runtime::revert(Error::InvalidContractVersion);
+    }
+    // Governance exists from version 2, so older versions could not have authorized an upgrade. From version 11 the authorization
+    // is consumed by upgrade_access of the previous version, as the deployer no longer holds the access uref to skip it
+    if (2..11).contains(&old_version) {
+        consume_upgrade_authorization();
+    }
+    // Versions before 11 left the access uref in the deployer's named keys, the session moves it into the new version's named keys
+    if !runtime::has_key(NAMED_KEY_ACCESS_UREF) {
+        runtime::revert(Error::KeyNotFound);
+    }
+    // Migration steps of the versions after `old_version` go here, in order
+    if old_version < 2 {
+        migrate_to_governance();
+    }
+    if old_version < 3 {
+        migrate_to_ces();
+    }
+    if old_version < 4 {
+        migrate_to_orders();
+    }
+    if old_version < 5 {
+        migrate_to_schema_versions();
+    }
+    if old_version < 6 {
+        migrate_to_escrow();
+    }
+    if old_version < 7 {
+        migrate_to_provenance();
+    }
+    if old_version < 8 {
+        migrate_to_balances();
+    }
+    if old_version < 9 {
+        migrate_to_cep85();
+    }
+    if old_version < 10 {
+        migrate_to_key_roles();
+    }
+    // Event schemas may change in any version (e.g. the typed Payment fields), so they are always rewritten
+    storage::write(get_named_key_by_name(NAMED_KEY_EVENTS_SCHEMA), get_schemas());
+    storage::write(
+        get_named_key_by_name(NAMED_KEY_CONTRACT_VERSION),
+        CONTRACT_VERSION,
+    );
+    emit(DropLinkedEvent::ContractMigrated {
+        old_version,
+        new_version: CONTRACT_VERSION,
+    });
+}
+
+/// Migration step of version 2, which adds the (not configured) governance keys and the proposals dictionary
+fn migrate_to_governance() {
+    runtime::put_key(
+        NAMED_KEY_GOVERNORS,
+        storage::new_uref(Vec::<AccountHash>::new()).into(),
+    );
+    runtime::put_key(NAMED_KEY_GOVERNANCE_THRESHOLD, storage::new_uref(0u8).into());
+    runtime::put_key(NAMED_KEY_GOVERNANCE_DELAY, storage::new_uref(0u64).into());
+    runtime::put_key(NAMED_KEY_PROPOSALS_CNT, storage::new_uref(0u64).into());
+    runtime::put_key(NAMED_KEY_UPGRADE_AUTHORIZED, storage::new_uref(false).into());
+    storage::new_dictionary(NAMED_KEY_DICT_PROPOSALS).unwrap_or_revert();
+}
+
+/// Migration step of version 3, which adds the Casper Event Standard keys and the `__events` dictionary
+fn migrate_to_ces() {
+    runtime::put_key(NAMED_KEY_EVENTS_LENGTH, storage::new_uref(0u32).into());
+    runtime::put_key(NAMED_KEY_EVENTS_SCHEMA, storage::new_uref(get_schemas()).into());
+    runtime::put_key(
+        NAMED_KEY_EVENTS_CES_VERSION,
+        storage::new_uref(CES_VERSION.to_string()).into(),
+    );
+    storage::new_dictionary(NAMED_KEY_DICT_EVENTS).unwrap_or_revert();
+}
+
+/// Migration step of version 4, which adds the orders counter, the `orders` dictionary and the order lists of the buyers, producers and publishers
+fn migrate_to_orders() {
+    runtime::put_key(NAMED_KEY_ORDERS_CNT, storage::new_uref(0u64).into());
+    storage::new_dictionary(NAMED_KEY_DICT_ORDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_BUYER_ORDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_PRODUCER_ORDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_PUBLISHER_ORDERS).unwrap_or_revert();
+}
+
+/// Migration step of version 5, which adds the schema version of the stored records (0, as the records of the older versions have no version byte)
+/// and the cursor of `migrate_storage`. Until `migrate_storage` rewrites every record, the entrypoints that read or write records revert with `MigrationInProgress`
+fn migrate_to_schema_versions() {
+    runtime::put_key(NAMED_KEY_SCHEMA_VERSION, storage::new_uref(0u32).into());
+    runtime::put_key(NAMED_KEY_MIGRATION_CURSOR, storage::new_uref((0u8, 1u64)).into());
+}
+
+/// Migration step of version 6, which adds the escrow purse and the `escrow_balances` dictionary, for the payouts to contract packages
+fn migrate_to_escrow() {
+    storage::new_dictionary(NAMED_KEY_DICT_ESCROW_BALANCES).unwrap_or_revert();
+    runtime::put_key(NAMED_KEY_ESCROW_PURSE, system::create_purse().into());
+}
+
+/// Migration step of version 7, which adds the provenance counter, the `provenance` dictionary and the provenance logs of the tokens
+/// (the movements made before the upgrade are not in the logs)
+fn migrate_to_provenance() {
+    runtime::put_key(NAMED_KEY_PROVENANCE_CNT, storage::new_uref(0u64).into());
+    storage::new_dictionary(NAMED_KEY_DICT_PROVENANCE).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_TOKEN_PROVENANCE).unwrap_or_revert();
+}
+
+/// Migration step of version 8, which adds the `balances`, `token_holders` and `holder_owners` dictionaries
+/// (a holder is indexed on its first touch : its next change, or a `balance_of` of its owner, see `balances::balance_of_owner`)
+fn migrate_to_balances() {
+    storage::new_dictionary(NAMED_KEY_DICT_BALANCES).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_TOKEN_HOLDERS).unwrap_or_revert();
+    storage::new_dictionary(NAMED_KEY_DICT_HOLDER_OWNERS).unwrap_or_revert();
+}
+
+/// Migration step of version 9, which adds the CEP-85 `name` and `uri` keys
+fn migrate_to_cep85() {
+    runtime::put_key(
+        NAMED_KEY_CEP85_NAME,
+        storage::new_uref(CEP85_COLLECTION_NAME.to_string()).into(),
+    );
+    runtime::put_key(NAMED_KEY_CEP85_URI, storage::new_uref(String::new()).into());
+}
+
+/// Migration step of version 10, which stores the owner, the pending owner and the governors as keys (accounts or contract packages) instead of account hashes,
+/// and rewrites the stored proposals with keys. The `operators` dictionary is keyed the same way for accounts, so the granted operators are kept as they are
+fn migrate_to_key_roles() {
+    let owner_uref = get_named_key_by_name(NAMED_KEY_OWNER);
+    let owner: AccountHash = storage::read(owner_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    storage::write(owner_uref, Key::Account(owner));
+    let pending_owner_uref = get_named_key_by_name(NAMED_KEY_PENDING_OWNER);
+    let pending_owner: Option<AccountHash> = storage::read(pending_owner_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    storage::write(pending_owner_uref, pending_owner.map(Key::Account));
+    let governors_uref = get_named_key_by_name(NAMED_KEY_GOVERNORS);
+    let governors: Vec<AccountHash> = storage::read(governors_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    storage::write(
+        governors_uref,
+        governors.into_iter().map(Key::Account).collect::<Vec<Key>>(),
+    );
+    // proposals are only made by the governors, so there are few of them to rewrite in one deploy
+    let proposals_dict = get_named_key_by_name(NAMED_KEY_DICT_PROPOSALS);
+    let proposals_cnt: u64 = storage::read(get_named_key_by_name(NAMED_KEY_PROPOSALS_CNT))
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    for proposal_id in 1..=proposals_cnt {
+        migrate_record::<Proposal>(proposals_dict, proposal_id);
+    }
+}
+
+/// The dictionaries of the versioned records, the counters of their ids and the functions that rewrite them, in the order that `migrate_storage` rewrites them
+const MIGRATED_RECORDS: [(&str, &str, fn(URef, u64)); 5] = [
+    (NAMED_KEY_DICT_METADATAS_NAME, NAMED_KEY_TOKENSCNT, migrate_record::<NftMetadata>),
+    (NAMED_KEY_DICT_HOLDERS_NAME, NAMED_KEY_HOLDERSCNT, migrate_record::<NFTHolder>),
+    (NAMED_KEY_DICT_REQ_OBJ, NAMED_KEY_REQ_CNT, migrate_record::<PublishRequest>),
+    (NAMED_KEY_DICT_APPROVED_NAME, NAMED_KEY_APPROVED_CNT, migrate_record::<ApprovedNFT>),
+    (NAMED_KEY_DICT_ORDERS, NAMED_KEY_ORDERS_CNT, migrate_record::<Order>),
+];
+
+/// Rewrites the record with the given id in its versioned layout, if it is stored in the unversioned layout of the older versions
+fn migrate_record<T: LegacyRecord + ToBytes + CLTyped>(dict: URef, id: u64) {
+    let key = id.to_string();
+    let legacy = storage::dictionary_get::<Legacy<T>>(dict, &key).unwrap_or_revert();
+    if let Some(Legacy(record)) = legacy {
+        storage::dictionary_put(dict, &key, record);
+    }
+}
+
+/// migrate_storage entrypoint of the contract
+/// 
+/// Rewrites at most `MAX_MIGRATION_BATCH` records (token metadatas, holders, publish requests, approved holders and orders, by id) from the unversioned layout of the older versions
+/// into their versioned layouts, continuing from the stored `migration_cursor`. When every record is rewritten, it stores `SCHEMA_VERSION` as the `schema_version`, which lifts the
+/// `MigrationInProgress` guard of the other entrypoints. It should be called after an upgrade until it returns true, and is only callable by the owner or an operator
+/// # Returns
+/// `migrated` : `bool`, true when every record is stored in its current layout
+/// # Emits
+/// `DropLinkedEvent::StorageMigrated` (once, when the migration finishes)
+#[no_mangle]
+pub extern "C" fn migrate_storage() {
+    only_operator();
+    let schema_version_uref = get_named_key_by_name(NAMED_KEY_SCHEMA_VERSION);
+    let old_version: u32 = storage::read(schema_version_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    if old_version >= SCHEMA_VERSION {
+        runtime::ret(CLValue::from_t(true).unwrap_or_revert());
+    }
+    let cursor_uref = get_named_key_by_name(NAMED_KEY_MIGRATION_CURSOR);
+    let (mut kind, mut next_id): (u8, u64) = storage::read(cursor_uref)
+        .unwrap_or_revert_with(Error::KeyNotFound)
+        .unwrap_or_revert_with(Error::KeyNotFound);
+    let mut budget = MAX_MIGRATION_BATCH;
+    while budget > 0 && usize::from(kind) < MIGRATED_RECORDS.len() {
+        let (dict_name, cnt_name, migrate) = MIGRATED_RECORDS[usize::from(kind)];
+        let dict = get_named_key_by_name(dict_name);
+        let last_id: u64 = storage::read(get_named_key_by_name(cnt_name))
+            .unwrap_or_revert_with(Error::KeyNotFound)
+            .unwrap_or_revert_with(Error::KeyNotFound);
+        // the ids from next_id to end (inclusive) are rewritten in this batch
+        let end = last_id.min(next_id + budget - 1);
+        for id in next_id..=end {
+            migrate(dict, id);
+        }
+        budget -= (end + 1).saturating_sub(next_id);
+        if end >= last_id {
+            kind += 1;
+            next_id = 1;
+        } else {
+            next_id = end + 1;
+        }
+    }
+    storage::write(cursor_uref, (kind, next_id));
+    let migrated = usize::from(kind) >= MIGRATED_RECORDS.len();
+    if migrated {
+        storage::write(schema_version_uref, SCHEMA_VERSION);
+        emit(DropLinkedEvent::StorageMigrated {
+            old_version,
+            new_version: SCHEMA_VERSION,
+        });
+    }
+    runtime::ret(CLValue::from_t(migrated).unwrap_or_revert());
+}
+
+/// clean_holders entrypoint of the contract
+/// 
+/// Older versions of buy also wrote the holder ids of the buyer into the `holders` dictionary, under the account hash of the buyer, next to the `NFTHolder`s keyed by holder id.
+/// Gets `accounts` (at most `MAX_CLEANUP_BATCH` account keys) and overwrites such an entry of each of them with a unit value, as an entry could not be removed from a dictionary;
+/// accounts without such an entry are skipped. It should be called with the buyers of the older versions (e.g. from the `Buy` events) in batches, and is only callable by the owner or an operator
+/// # Returns
+/// `cleaned` : `u64`, the number of overwritten entries
+#[no_mangle]
+pub extern "C" fn clean_holders() {
+    only_operator();
+    let accounts: Vec<Key> = runtime::get_named_arg(RUNTIME_ARG_ACCOUNTS);
+    if accounts.len() > MAX_CLEANUP_BATCH {
+        runtime::revert(Error::BatchTooLarge);
+    }
+    let holders_dict = get_named_key_by_name(NAMED_KEY_DICT_HOLDERS_NAME);
+    let mut cleaned = 0u64;
+    for account in accounts {
+        let account_string = account
+            .into_account()
+            .unwrap_or_revert_with(Error::NotAccountHash)
+            .as_string();
+        // an overwritten entry (or any other value) is not a U64list, so it is not cleaned twice
+        if let Ok(Some(_)) = storage::dictionary_get::<U64list>(holders_dict, &account_string) {
+            storage::dictionary_put(holders_dict, &account_string, ());
+            cleaned += 1;
+        }
+    }
+    runtime::ret(CLValue::from_t(cleaned).unwrap_or_revert());
+}
@@ -0,0 +1,668 @@
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{self, Bytes, FromBytes},
+    CLValue, ExecutionResult, Key, PublicKey, Transform, URef, U512,
+};
+
+/// Prefix of the event names in the serialized events
+pub const EVENT_PREFIX: &str = "event_";
+/// Suffix of the names of the events whose parties are keys (see `KEY_LAYOUT_EVENTS`)
+pub const KEY_LAYOUT_SUFFIX: &str = "V2";
+/// The events which have parties : the contract emits them as `<Name>V2` with keys, and emitted them as `<Name>` with account hashes before
+/// (before version 6 for the marketplace events, and before version 10 for the roles)
+pub const KEY_LAYOUT_EVENTS: [&str; 13] = [
+    "Mint",
+    "PublishRequest",
+    "Buy",
+    "Settlement",
+    "Payment",
+    "OwnershipTransferStarted",
+    "OwnershipTransferred",
+    "OperatorGranted",
+    "OperatorRevoked",
+    "GovernanceChanged",
+    "ProposalCreated",
+    "ProposalApproved",
+    "ProposalCancelled",
+];
+/// Prefix of the formatted dictionary keys, in the transforms of an execution result
+const DICTIONARY_KEY_PREFIX: &str = "dictionary-";
+
+/// Droplinked Events, as they are emitted by the contract (see `event.rs` of the contract)
+///
+/// The variants and the order of their fields should be kept in sync with the contract, as they are decoded in that order.
+/// The parties of the marketplace (recipients, owners, publishers, buyers, producers and payers) are `Key`s (an account or a contract package) from version 6 of the contract,
+/// and the roles (owners, operators and governors) from version 10. The events that older versions emitted, with account hashes, are decoded into the same variants,
+/// with `Key::Account` parties
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DropLinkedEvent {
+    Mint {
+        recipient: Key,
+        token_id: u64,
+        holder_id: u64,
+        amount: u64,
+        comission: u64,
+        price: u64,
+    },
+    PublishRequest {
+        owner: Key,
+        publisher: Key,
+        amount: u64,
+        holder_id: u64,
+        request_id: u64,
+    },
+    ApprovedPublish {
+        request_id: u64,
+        approved_id: u64,
+    },
+    DisapprovedPublish {
+        approved_id: u64,
+        amount: u64,
+    },
+    CancelRequest {
+        request_id: u64,
+    },
+    Buy {
+        amount: u64,
+        approved_id: u64,
+        buyer: Key,
+    },
+    Settlement {
+        order_id: u64,
+        buyer: Key,
+        approved_id: u64,
+        token_id: u64,
+        amount: u64,
+        product_price: u64,
+        ratio: u64,
+        ratio_decimals: u8,
+        shipping_price: u64,
+        tax_price: u64,
+        fee: u64,
+        producer: Key,
+        producer_amount: u64,
+        publisher: Key,
+        publisher_amount: u64,
+        treasury_amount: u64,
+        refund: U512,
+    },
+    Payment {
+        payer: Key,
+        recipient: PublicKey,
+        product_price: U512,
+        shipping_price: U512,
+        tax_price: U512,
+        fee: u64,
+        treasury_amount: U512,
+        recipient_amount: U512,
+        order_reference: Option<String>,
+    },
+    ConfigChanged {
+        name: String,
+        old_value: String,
+        new_value: String,
+    },
+    FeeChanged {
+        old_fee: u64,
+        new_fee: u64,
+        effective_at: u64,
+    },
+    OracleSignerAdded {
+        public_key: PublicKey,
+    },
+    OracleSignerRemoved {
+        public_key: PublicKey,
+    },
+    OracleSignerRotated {
+        old_public_key: PublicKey,
+        new_public_key: PublicKey,
+        grace_until: u64,
+    },
+    PausedChanged {
+        old_paused: u8,
+        new_paused: u8,
+    },
+    ContractMigrated {
+        old_version: u32,
+        new_version: u32,
+    },
+    StorageMigrated {
+        old_version: u32,
+        new_version: u32,
+    },
+    Withdrawn {
+        recipient: Key,
+        amount: U512,
+    },
+    Compacted {
+        holder_ids: Vec<u64>,
+        approved_ids: Vec<u64>,
+        request_ids: Vec<u64>,
+    },
+    OwnershipTransferStarted {
+        previous_owner: Key,
+        new_owner: Key,
+    },
+    OwnershipTransferred {
+        previous_owner: Key,
+        new_owner: Key,
+    },
+    OperatorGranted {
+        account: Key,
+    },
+    OperatorRevoked {
+        account: Key,
+    },
+    GovernanceChanged {
+        governors: Vec<Key>,
+        threshold: u8,
+        delay: u64,
+    },
+    ProposalCreated {
+        proposal_id: u64,
+        proposer: Key,
+        action: String,
+    },
+    ProposalApproved {
+        proposal_id: u64,
+        governor: Key,
+        executable_at: Option<u64>,
+    },
+    ProposalExecuted {
+        proposal_id: u64,
+    },
+    ProposalCancelled {
+        proposal_id: u64,
+        governor: Key,
+    },
+}
+
+/// Reads a party of an event : a key, or an account hash in the layout of the events emitted before `KEY_LAYOUT_SUFFIX`
+fn party_from_bytes(bytes: &[u8], account_layout: bool) -> Result<(Key, &[u8]), bytesrepr::Error> {
+    if account_layout {
+        let (account_hash, rem) = AccountHash::from_bytes(bytes)?;
+        Ok((Key::Account(account_hash), rem))
+    } else {
+        Key::from_bytes(bytes)
+    }
+}
+
+/// Decodes the event as the contract serializes it : the prefixed name of the event (`event_<Name>`) followed by its fields
+///
+/// The events of `KEY_LAYOUT_EVENTS` are decoded from both of their layouts, by their name. It would return `bytesrepr::Error::Formatting`
+/// if the name is not prefixed, or is not a known event
+impl FromBytes for DropLinkedEvent {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (prefixed_name, rem) = String::from_bytes(bytes)?;
+        let name = prefixed_name
+            .strip_prefix(EVENT_PREFIX)
+            .ok_or(bytesrepr::Error::Formatting)?;
+        let (name, account_layout) = match name.strip_suffix(KEY_LAYOUT_SUFFIX) {
+            Some(name) if KEY_LAYOUT_EVENTS.contains(&name) => (name, false),
+            _ if KEY_LAYOUT_EVENTS.contains(&name) => (name, true),
+            // the other events have no parties, and have a single layout
+            _ => (name, false),
+        };
+        match name {
+            "Mint" => {
+                let (recipient, rem) = party_from_bytes(rem, account_layout)?;
+                let (token_id, rem) = FromBytes::from_bytes(rem)?;
+                let (holder_id, rem) = FromBytes::from_bytes(rem)?;
+                let (amount, rem) = FromBytes::from_bytes(rem)?;
+                let (comission, rem) = FromBytes::from_bytes(rem)?;
+                let (price, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::Mint {
+                        recipient,
+                        token_id,
+                        holder_id,
+                        amount,
+                        comission,
+                        price,
+                    },
+                    rem,
+                ))
+            }
+            "PublishRequest" => {
+                let (owner, rem) = party_from_bytes(rem, account_layout)?;
+                let (publisher, rem) = party_from_bytes(rem, account_layout)?;
+                let (amount, rem) = FromBytes::from_bytes(rem)?;
+                let (holder_id, rem) = FromBytes::from_bytes(rem)?;
+                let (request_id, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::PublishRequest {
+                        owner,
+                        publisher,
+                        amount,
+                        holder_id,
+                        request_id,
+                    },
+                    rem,
+                ))
+            }
+            "ApprovedPublish" => {
+                let (request_id, rem) = FromBytes::from_bytes(rem)?;
+                let (approved_id, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::ApprovedPublish {
+                        request_id,
+                        approved_id,
+                    },
+                    rem,
+                ))
+            }
+            "DisapprovedPublish" => {
+                let (approved_id, rem) = FromBytes::from_bytes(rem)?;
+                let (amount, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::DisapprovedPublish {
+                        approved_id,
+                        amount,
+                    },
+                    rem,
+                ))
+            }
+            "CancelRequest" => {
+                let (request_id, rem) = FromBytes::from_bytes(rem)?;
+                Ok((DropLinkedEvent::CancelRequest { request_id }, rem))
+            }
+            "Buy" => {
+                let (amount, rem) = FromBytes::from_bytes(rem)?;
+                let (approved_id, rem) = FromBytes::from_bytes(rem)?;
+                let (buyer, rem) = party_from_bytes(rem, account_layout)?;
+                Ok((
+                    DropLinkedEvent::Buy {
+                        amount,
+                        approved_id,
+                        buyer,
+                    },
+                    rem,
+                ))
+            }
+            "Settlement" => {
+                let (order_id, rem) = FromBytes::from_bytes(rem)?;
+                let (buyer, rem) = party_from_bytes(rem, account_layout)?;
+                let (approved_id, rem) = FromBytes::from_bytes(rem)?;
+                let (token_id, rem) = FromBytes::from_bytes(rem)?;
+                let (amount, rem) = FromBytes::from_bytes(rem)?;
+                let (product_price, rem) = FromBytes::from_bytes(rem)?;
+                let (ratio, rem) = FromBytes::from_bytes(rem)?;
+                let (ratio_decimals, rem) = FromBytes::from_bytes(rem)?;
+                let (shipping_price, rem) = FromBytes::from_bytes(rem)?;
+                let (tax_price, rem) = FromBytes::from_bytes(rem)?;
+                let (fee, rem) = FromBytes::from_bytes(rem)?;
+                let (producer, rem) = party_from_bytes(rem, account_layout)?;
+                let (producer_amount, rem) = FromBytes::from_bytes(rem)?;
+                let (publisher, rem) = party_from_bytes(rem, account_layout)?;
+                let (publisher_amount, rem) = FromBytes::from_bytes(rem)?;
+                let (treasury_amount, rem) = FromBytes::from_bytes(rem)?;
+                let (refund, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::Settlement {
+                        order_id,
+                        buyer,
+                        approved_id,
+                        token_id,
+                        amount,
+                        product_price,
+                        ratio,
+                        ratio_decimals,
+                        shipping_price,
+                        tax_price,
+                        fee,
+                        producer,
+                        producer_amount,
+                        publisher,
+                        publisher_amount,
+                        treasury_amount,
+                        refund,
+                    },
+                    rem,
+                ))
+            }
+            "Payment" => {
+                let (payer, rem) = party_from_bytes(rem, account_layout)?;
+                let (recipient, rem) = FromBytes::from_bytes(rem)?;
+                let (product_price, rem) = FromBytes::from_bytes(rem)?;
+                let (shipping_price, rem) = FromBytes::from_bytes(rem)?;
+                let (tax_price, rem) = FromBytes::from_bytes(rem)?;
+                let (fee, rem) = FromBytes::from_bytes(rem)?;
+                let (treasury_amount, rem) = FromBytes::from_bytes(rem)?;
+                let (recipient_amount, rem) = FromBytes::from_bytes(rem)?;
+                let (order_reference, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::Payment {
+                        payer,
+                        recipient,
+                        product_price,
+                        shipping_price,
+                        tax_price,
+                        fee,
+                        treasury_amount,
+                        recipient_amount,
+                        order_reference,
+                    },
+                    rem,
+                ))
+            }
+            "ConfigChanged" => {
+                let (name, rem) = FromBytes::from_bytes(rem)?;
+                let (old_value, rem) = FromBytes::from_bytes(rem)?;
+                let (new_value, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::ConfigChanged {
+                        name,
+                        old_value,
+                        new_value,
+                    },
+                    rem,
+                ))
+            }
+            "FeeChanged" => {
+                let (old_fee, rem) = FromBytes::from_bytes(rem)?;
+                let (new_fee, rem) = FromBytes::from_bytes(rem)?;
+                let (effective_at, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::FeeChanged {
+                        old_fee,
+                        new_fee,
+                        effective_at,
+                    },
+                    rem,
+                ))
+            }
+            "OracleSignerAdded" => {
+                let (public_key, rem) = FromBytes::from_bytes(rem)?;
+                Ok((DropLinkedEvent::OracleSignerAdded { public_key }, rem))
+            }
+            "OracleSignerRemoved" => {
+                let (public_key, rem) = FromBytes::from_bytes(rem)?;
+                Ok((DropLinkedEvent::OracleSignerRemoved { public_key }, rem))
+            }
+            "OracleSignerRotated" => {
+                let (old_public_key, rem) = FromBytes::from_bytes(rem)?;
+                let (new_public_key, rem) = FromBytes::from_bytes(rem)?;
+                let (grace_until, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::OracleSignerRotated {
+                        old_public_key,
+                        new_public_key,
+                        grace_until,
+                    },
+                    rem,
+                ))
+            }
+            "PausedChanged" => {
+                let (old_paused, rem) = FromBytes::from_bytes(rem)?;
+                let (new_paused, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::PausedChanged {
+                        old_paused,
+                        new_paused,
+                    },
+                    rem,
+                ))
+            }
+            "ContractMigrated" => {
+                let (old_version, rem) = FromBytes::from_bytes(rem)?;
+                let (new_version, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::ContractMigrated {
+                        old_version,
+                        new_version,
+                    },
+                    rem,
+                ))
+            }
+            "StorageMigrated" => {
+                let (old_version, rem) = FromBytes::from_bytes(rem)?;
+                let (new_version, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::StorageMigrated {
+                        old_version,
+                        new_version,
+                    },
+                    rem,
+                ))
+            }
+            "Withdrawn" => {
+                let (recipient, rem) = FromBytes::from_bytes(rem)?;
+                let (amount, rem) = FromBytes::from_bytes(rem)?;
+                Ok((DropLinkedEvent::Withdrawn { recipient, amount }, rem))
+            }
+            "Compacted" => {
+                let (holder_ids, rem) = FromBytes::from_bytes(rem)?;
+                let (approved_ids, rem) = FromBytes::from_bytes(rem)?;
+                let (request_ids, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::Compacted {
+                        holder_ids,
+                        approved_ids,
+                        request_ids,
+                    },
+                    rem,
+                ))
+            }
+            "OwnershipTransferStarted" => {
+                let (previous_owner, rem) = party_from_bytes(rem, account_layout)?;
+                let (new_owner, rem) = party_from_bytes(rem, account_layout)?;
+                Ok((
+                    DropLinkedEvent::OwnershipTransferStarted {
+                        previous_owner,
+                        new_owner,
+                    },
+                    rem,
+                ))
+            }
+            "OwnershipTransferred" => {
+                let (previous_owner, rem) = party_from_bytes(rem, account_layout)?;
+                let (new_owner, rem) = party_from_bytes(rem, account_layout)?;
+                Ok((
+                    DropLinkedEvent::OwnershipTransferred {
+                        previous_owner,
+                        new_owner,
+                    },
+                    rem,
+                ))
+            }
+            "OperatorGranted" => {
+                let (account, rem) = party_from_bytes(rem, account_layout)?;
+                Ok((DropLinkedEvent::OperatorGranted { account }, rem))
+            }
+            "OperatorRevoked" => {
+                let (account, rem) = party_from_bytes(rem, account_layout)?;
+                Ok((DropLinkedEvent::OperatorRevoked { account }, rem))
+            }
+            "GovernanceChanged" => {
+                let (governors, rem) = if account_layout {
+                    let (governors, rem) = Vec::<AccountHash>::from_bytes(rem)?;
+                    (governors.into_iter().map(Key::Account).collect(), rem)
+                } else {
+                    Vec::<Key>::from_bytes(rem)?
+                };
+                let (threshold, rem) = FromBytes::from_bytes(rem)?;
+                let (delay, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::GovernanceChanged {
+                        governors,
+                        threshold,
+                        delay,
+                    },
+                    rem,
+                ))
+            }
+            "ProposalCreated" => {
+                let (proposal_id, rem) = FromBytes::from_bytes(rem)?;
+                let (proposer, rem) = party_from_bytes(rem, account_layout)?;
+                let (action, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::ProposalCreated {
+                        proposal_id,
+                        proposer,
+                        action,
+                    },
+                    rem,
+                ))
+            }
+            "ProposalApproved" => {
+                let (proposal_id, rem) = FromBytes::from_bytes(rem)?;
+                let (governor, rem) = party_from_bytes(rem, account_layout)?;
+                let (executable_at, rem) = FromBytes::from_bytes(rem)?;
+                Ok((
+                    DropLinkedEvent::ProposalApproved {
+                        proposal_id,
+                        governor,
+                        executable_at,
+                    },
+                    rem,
+                ))
+            }
+            "ProposalExecuted" => {
+                let (proposal_id, rem) = FromBytes::from_bytes(rem)?;
+                Ok((DropLinkedEvent::ProposalExecuted { proposal_id }, rem))
+            }
+            "ProposalCancelled" => {
+                let (proposal_id, rem) = FromBytes::from_bytes(rem)?;
+                let (governor, rem) = party_from_bytes(rem, account_layout)?;
+                Ok((
+                    DropLinkedEvent::ProposalCancelled {
+                        proposal_id,
+                        governor,
+                    },
+                    rem,
+                ))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+impl DropLinkedEvent {
+    /// Decodes an event, as it is stored (as `Bytes`) in the `__events` dictionary of the contract
+    pub fn from_cl_value(cl_value: CLValue) -> Result<Self, bytesrepr::Error> {
+        let bytes: Bytes = cl_value
+            .into_t()
+            .map_err(|_| bytesrepr::Error::Formatting)?;
+        bytesrepr::deserialize(bytes.into())
+    }
+}
+
+/// Returns the events written to the `__events` dictionary (whose uref is `events_dict`) by a deploy, as (event index, event) pairs, ordered by their index
+///
+/// The dictionary values in the transforms are wrapped with the seed uref of their dictionary and their key, which are used to pick the events of the contract and their indices.
+/// A failed deploy has no events, as its writes are reverted
+pub fn events_from_execution_result(
+    execution_result: &ExecutionResult,
+    events_dict: URef,
+) -> Result<Vec<(u32, DropLinkedEvent)>, bytesrepr::Error> {
+    let effect = match execution_result {
+        ExecutionResult::Success { effect, .. } => effect,
+        ExecutionResult::Failure { .. } => return Ok(Vec::new()),
+    };
+    let mut events = Vec::new();
+    for entry in effect.transforms.iter() {
+        if !entry.key.starts_with(DICTIONARY_KEY_PREFIX) {
+            continue;
+        }
+        let wrapped = match &entry.transform {
+            Transform::WriteCLValue(wrapped) => wrapped,
+            _ => continue,
+        };
+        let (cl_value, rem) = CLValue::from_bytes(wrapped.inner_bytes())?;
+        let (seed_uref_addr, rem) = Bytes::from_bytes(rem)?;
+        let (item_key, _) = Bytes::from_bytes(rem)?;
+        if seed_uref_addr[..] != events_dict.addr()[..] {
+            continue;
+        }
+        let index: u32 = String::from_utf8(item_key.into())
+            .ok()
+            .and_then(|key| key.parse().ok())
+            .ok_or(bytesrepr::Error::Formatting)?;
+        events.push((index, DropLinkedEvent::from_cl_value(cl_value)?));
+    }
+    events.sort_by_key(|(index, _)| *index);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::bytesrepr::ToBytes;
+
+    use super::*;
+
+    const ACCOUNT: AccountHash = AccountHash::new([1u8; 32]);
+
+    /// Serializes an event as the contract does, from its name and its serialized fields
+    fn event_bytes(name: &str, fields: Vec<Vec<u8>>) -> Vec<u8> {
+        let mut bytes = format!("{}{}", EVENT_PREFIX, name).to_bytes().unwrap();
+        for mut field in fields {
+            bytes.append(&mut field);
+        }
+        bytes
+    }
+
+    fn field<T: ToBytes>(value: T) -> Vec<u8> {
+        value.to_bytes().unwrap()
+    }
+
+    fn mint_fields(recipient: Vec<u8>) -> Vec<Vec<u8>> {
+        vec![
+            recipient,
+            field(1u64),
+            field(2u64),
+            field(10u64),
+            field(1234u64),
+            field(20u64),
+        ]
+    }
+
+    fn mint(recipient: Key) -> DropLinkedEvent {
+        DropLinkedEvent::Mint {
+            recipient,
+            token_id: 1,
+            holder_id: 2,
+            amount: 10,
+            comission: 1234,
+            price: 20,
+        }
+    }
+
+    #[test]
+    fn decodes_events_emitted_before_keys() {
+        // a Mint of version 5, with the account hash of the recipient
+        let bytes = event_bytes("Mint", mint_fields(field(ACCOUNT)));
+        assert_eq!(
+            bytesrepr::deserialize::<DropLinkedEvent>(bytes).unwrap(),
+            mint(Key::Account(ACCOUNT))
+        );
+        // a GovernanceChanged of version 9, with the account hashes of the governors
+        let fields = vec![field(vec![ACCOUNT]), field(1u8), field(86_400_000u64)];
+        assert_eq!(
+            bytesrepr::deserialize::<DropLinkedEvent>(event_bytes("GovernanceChanged", fields))
+                .unwrap(),
+            DropLinkedEvent::GovernanceChanged {
+                governors: vec![Key::Account(ACCOUNT)],
+                threshold: 1,
+                delay: 86_400_000,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_events_with_keys() {
+        let package = Key::Hash([4u8; 32]);
+        let bytes = event_bytes("MintV2", mint_fields(field(package)));
+        assert_eq!(
+            bytesrepr::deserialize::<DropLinkedEvent>(bytes).unwrap(),
+            mint(package)
+        );
+        // the events without parties have a single name
+        let bytes = event_bytes("ProposalExecuted", vec![field(3u64)]);
+        assert_eq!(
+            bytesrepr::deserialize::<DropLinkedEvent>(bytes).unwrap(),
+            DropLinkedEvent::ProposalExecuted { proposal_id: 3 }
+        );
+        let bytes = event_bytes("ProposalExecutedV2", vec![field(3u64)]);
+        assert!(bytesrepr::deserialize::<DropLinkedEvent>(bytes).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+//! Off-chain indexer of the droplinked contract
+//!
+//! It decodes the `DropLinkedEvent`s that the contract stores in its `__events` dictionary (per the Casper Event Standard),
+//! either from the execution results of deploys or from the global state, and folds them into a `Marketplace`,
+//! an in-memory model of the tokens, holders, publish requests, approvals and sales of the contract
+pub mod event;
+pub mod marketplace;
+
+pub use event::{events_from_execution_result, DropLinkedEvent};
+pub use marketplace::{Approval, Holder, IndexerError, Marketplace, Payment, Request, Sale, Token};
@@ -0,0 +1,819 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use casper_types::{bytesrepr, Key, PublicKey, U512};
+
+use crate::event::DropLinkedEvent;
+
+/// Errors of folding the events into the marketplace, which mean the events are not the (complete and ordered) events of a single contract
+#[derive(Debug, PartialEq, Eq)]
+pub enum IndexerError {
+    /// The event could not be decoded
+    Bytesrepr(bytesrepr::Error),
+    /// The event refers to a holder that no earlier event created
+    HolderNotFound(u64),
+    /// The event refers to a publish request that no earlier event created
+    RequestNotFound(u64),
+    /// The event refers to an approved listing that no earlier event created
+    ApprovalNotFound(u64),
+    /// The event takes more tokens from a holder or an approved listing than it has
+    NotEnoughAmount,
+}
+
+impl From<bytesrepr::Error> for IndexerError {
+    fn from(error: bytesrepr::Error) -> Self {
+        IndexerError::Bytesrepr(error)
+    }
+}
+
+/// A minted token, as it is stored in the `metadatas` and `total_supply` dictionaries of the contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub price: u64,
+    pub comission: u64,
+    pub total_supply: u64,
+}
+
+/// An amount of a token that an account (or a contract package) holds, as it is stored in the `holders` dictionary of the contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Holder {
+    pub owner: Key,
+    pub token_id: u64,
+    pub amount: u64,
+}
+
+/// A publish request, as it is stored in the `request_objects` dictionary of the contract
+///
+/// The contract keeps the approved and cancelled requests too, and only removes them from the producer and publisher request lists
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+    pub holder_id: u64,
+    pub amount: u64,
+    pub producer: Key,
+    pub publisher: Key,
+}
+
+/// An approved listing, as it is stored in the `approved` dictionary of the contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Approval {
+    pub holder_id: u64,
+    pub amount: u64,
+    pub producer: Key,
+    pub publisher: Key,
+    pub token_id: u64,
+}
+
+/// A settled `buy`, with the split of the price between the producer, the publisher and the treasury, as it is stored in the `orders` dictionary of the contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sale {
+    pub buyer: Key,
+    pub approved_id: u64,
+    pub token_id: u64,
+    pub amount: u64,
+    pub product_price: u64,
+    pub shipping_price: u64,
+    pub tax_price: u64,
+    pub fee: u64,
+    pub producer_amount: u64,
+    pub publisher_amount: u64,
+    pub treasury_amount: u64,
+    pub refund: U512,
+}
+
+/// A `direct_pay`, with the split of the paid amount between the treasury and the recipient
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payment {
+    pub payer: Key,
+    pub recipient: PublicKey,
+    pub fee: u64,
+    pub treasury_amount: U512,
+    pub recipient_amount: U512,
+    pub order_reference: Option<String>,
+}
+
+/// In-memory model of the marketplace state of the contract, rebuilt from its events
+///
+/// The events should be applied in the order they were emitted (their index in the `__events` dictionary), starting from the first one.
+/// The maps are keyed the same way as the dictionaries of the contract, so each of them could be checked against its dictionary
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Marketplace {
+    pub tokens: BTreeMap<u64, Token>,
+    pub holders: BTreeMap<u64, Holder>,
+    /// holder ids of each account or contract package (the `owners` dictionary)
+    pub owners: BTreeMap<Key, BTreeSet<u64>>,
+    pub requests: BTreeMap<u64, Request>,
+    /// pending request ids of each producer (the `producer_requests` dictionary)
+    pub producer_requests: BTreeMap<Key, BTreeSet<u64>>,
+    /// pending request ids of each publisher (the `publiser_requests` dictionary)
+    pub publisher_requests: BTreeMap<Key, BTreeSet<u64>>,
+    pub approvals: BTreeMap<u64, Approval>,
+    /// approved ids of each producer (the `producers_approved` dictionary)
+    pub producer_approved: BTreeMap<Key, BTreeSet<u64>>,
+    /// approved ids of each publisher (the `publishers_approved` dictionary)
+    pub publisher_approved: BTreeMap<Key, BTreeSet<u64>>,
+    /// sales by their order id (the `orders` dictionary)
+    pub sales: BTreeMap<u64, Sale>,
+    pub payments: Vec<Payment>,
+    /// escrowed shares of each contract package, which it has not withdrawn yet (the `escrow_balances` dictionary)
+    pub escrow: BTreeMap<Key, U512>,
+    /// the last holder id that is used (the `holders_cnt` named key)
+    pub holders_cnt: u64,
+    /// the number of events that are applied
+    pub events_cnt: u32,
+}
+
+impl Marketplace {
+    /// Returns an empty marketplace, as the contract is right after its install
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes an event, as it is stored in the `__events` dictionary, and applies it
+    pub fn apply_bytes(&mut self, bytes: &[u8]) -> Result<(), IndexerError> {
+        let event: DropLinkedEvent = bytesrepr::deserialize(bytes.to_vec())?;
+        self.apply(event)
+    }
+
+    /// Applies the events in order, and stops at the first one that could not be applied
+    pub fn apply_all<I: IntoIterator<Item = DropLinkedEvent>>(
+        &mut self,
+        events: I,
+    ) -> Result<(), IndexerError> {
+        for event in events {
+            self.apply(event)?;
+        }
+        Ok(())
+    }
+
+    /// Applies an event to the marketplace, the same way its entrypoint changed the dictionaries of the contract
+    ///
+    /// The events that do not change the marketplace (configuration, access control, governance, ...) are only counted
+    pub fn apply(&mut self, event: DropLinkedEvent) -> Result<(), IndexerError> {
+        match event {
+            DropLinkedEvent::Mint {
+                recipient,
+                token_id,
+                holder_id,
+                amount,
+                comission,
+                price,
+            } => self.apply_mint(recipient, token_id, holder_id, amount, comission, price)?,
+            DropLinkedEvent::PublishRequest {
+                owner,
+                publisher,
+                amount,
+                holder_id,
+                request_id,
+            } => {
+                self.requests.insert(
+                    request_id,
+                    Request {
+                        holder_id,
+                        amount,
+                        producer: owner,
+                        publisher,
+                    },
+                );
+                self.producer_requests.entry(owner).or_default().insert(request_id);
+                self.publisher_requests.entry(publisher).or_default().insert(request_id);
+            }
+            DropLinkedEvent::ApprovedPublish {
+                request_id,
+                approved_id,
+            } => {
+                let request = self
+                    .requests
+                    .get(&request_id)
+                    .cloned()
+                    .ok_or(IndexerError::RequestNotFound(request_id))?;
+                let token_id = self.holder(request.holder_id)?.token_id;
+                self.approvals.insert(
+                    approved_id,
+                    Approval {
+                        holder_id: request.holder_id,
+                        amount: request.amount,
+                        producer: request.producer,
+                        publisher: request.publisher,
+                        token_id,
+                    },
+                );
+                self.producer_approved.entry(request.producer).or_default().insert(approved_id);
+                self.publisher_approved.entry(request.publisher).or_default().insert(approved_id);
+                self.remove_pending_request(&request, request_id);
+            }
+            DropLinkedEvent::DisapprovedPublish {
+                approved_id,
+                amount,
+            } => {
+                let approval = self.approval_mut(approved_id)?;
+                approval.amount = approval
+                    .amount
+                    .checked_sub(amount)
+                    .ok_or(IndexerError::NotEnoughAmount)?;
+                if approval.amount == 0 {
+                    let (producer, publisher) = (approval.producer, approval.publisher);
+                    remove_from(&mut self.producer_approved, &producer, approved_id);
+                    remove_from(&mut self.publisher_approved, &publisher, approved_id);
+                }
+            }
+            DropLinkedEvent::CancelRequest { request_id } => {
+                let request = self
+                    .requests
+                    .get(&request_id)
+                    .cloned()
+                    .ok_or(IndexerError::RequestNotFound(request_id))?;
+                self.remove_pending_request(&request, request_id);
+            }
+            DropLinkedEvent::Buy {
+                amount,
+                approved_id,
+                buyer,
+            } => self.apply_buy(amount, approved_id, buyer)?,
+            DropLinkedEvent::Settlement {
+                order_id,
+                buyer,
+                approved_id,
+                token_id,
+                amount,
+                product_price,
+                shipping_price,
+                tax_price,
+                fee,
+                producer,
+                producer_amount,
+                publisher,
+                publisher_amount,
+                treasury_amount,
+                refund,
+                ..
+            } => {
+                self.add_escrow(producer, producer_amount);
+                self.add_escrow(publisher, publisher_amount);
+                self.sales.insert(
+                    order_id,
+                    Sale {
+                        buyer,
+                        approved_id,
+                        token_id,
+                        amount,
+                        product_price,
+                        shipping_price,
+                        tax_price,
+                        fee,
+                        producer_amount,
+                        publisher_amount,
+                        treasury_amount,
+                        refund,
+                    },
+                );
+            }
+            DropLinkedEvent::Payment {
+                payer,
+                recipient,
+                fee,
+                treasury_amount,
+                recipient_amount,
+                order_reference,
+                ..
+            } => self.payments.push(Payment {
+                payer,
+                recipient,
+                fee,
+                treasury_amount,
+                recipient_amount,
+                order_reference,
+            }),
+            DropLinkedEvent::Withdrawn { recipient, .. } => {
+                self.escrow.insert(recipient, U512::zero());
+            }
+            DropLinkedEvent::Compacted {
+                holder_ids,
+                approved_ids,
+                request_ids,
+            } => self.apply_compacted(holder_ids, approved_ids, request_ids),
+            _ => {}
+        }
+        self.events_cnt += 1;
+        Ok(())
+    }
+
+    /// Returns the holders of an account (or a contract package), as (holder id, holder) pairs
+    pub fn holders_of(&self, owner: &Key) -> Vec<(u64, &Holder)> {
+        self.owners
+            .get(owner)
+            .map(|holder_ids| {
+                holder_ids
+                    .iter()
+                    .filter_map(|holder_id| self.holders.get(holder_id).map(|holder| (*holder_id, holder)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The contract returns 0 as the holder id of a mint which is added to an existing holder of the recipient (of the same token)
+    fn apply_mint(
+        &mut self,
+        recipient: Key,
+        token_id: u64,
+        holder_id: u64,
+        amount: u64,
+        comission: u64,
+        price: u64,
+    ) -> Result<(), IndexerError> {
+        let token = self.tokens.entry(token_id).or_insert(Token {
+            price,
+            comission,
+            total_supply: 0,
+        });
+        token.price = price;
+        token.comission = comission;
+        token.total_supply += amount;
+        if holder_id == 0 {
+            let existing = self
+                .holder_id_of(&recipient, token_id)
+                .ok_or(IndexerError::HolderNotFound(holder_id))?;
+            self.holder_mut(existing)?.amount += amount;
+        } else {
+            self.add_holder(holder_id, recipient, token_id, amount);
+        }
+        Ok(())
+    }
+
+    /// Buy takes the amount from the approved listing and its holder, and gives it to the buyer's holder of the token, or to a new holder if the buyer has none
+    fn apply_buy(
+        &mut self,
+        amount: u64,
+        approved_id: u64,
+        buyer: Key,
+    ) -> Result<(), IndexerError> {
+        let approval = self.approval_mut(approved_id)?;
+        approval.amount = approval
+            .amount
+            .checked_sub(amount)
+            .ok_or(IndexerError::NotEnoughAmount)?;
+        let (holder_id, token_id) = (approval.holder_id, approval.token_id);
+        let holder = self.holder_mut(holder_id)?;
+        holder.amount = holder
+            .amount
+            .checked_sub(amount)
+            .ok_or(IndexerError::NotEnoughAmount)?;
+        match self.holder_id_of(&buyer, token_id) {
+            Some(existing) => self.holder_mut(existing)?.amount += amount,
+            None => self.add_holder(self.holders_cnt + 1, buyer, token_id, amount),
+        }
+        Ok(())
+    }
+
+    /// Removes the compacted records, and their ids from the lists of their parties
+    fn apply_compacted(
+        &mut self,
+        holder_ids: Vec<u64>,
+        approved_ids: Vec<u64>,
+        request_ids: Vec<u64>,
+    ) {
+        for holder_id in holder_ids {
+            if let Some(holder) = self.holders.remove(&holder_id) {
+                remove_from(&mut self.owners, &holder.owner, holder_id);
+            }
+        }
+        for approved_id in approved_ids {
+            if let Some(approval) = self.approvals.remove(&approved_id) {
+                remove_from(&mut self.producer_approved, &approval.producer, approved_id);
+                remove_from(&mut self.publisher_approved, &approval.publisher, approved_id);
+            }
+        }
+        for request_id in request_ids {
+            self.requests.remove(&request_id);
+        }
+    }
+
+    /// Returns the id of the holder of the token that the account has, the same way `add_nft_holder` of the contract finds it
+    fn holder_id_of(&self, owner: &Key, token_id: u64) -> Option<u64> {
+        self.owners
+            .get(owner)
+            .into_iter()
+            .flatten()
+            .copied()
+            .find(|id| self.holders.get(id).map(|holder| holder.token_id) == Some(token_id))
+    }
+
+    /// The shares of a contract package are held in escrow until it withdraws them, the accounts are paid directly
+    fn add_escrow(&mut self, party: Key, amount: u64) {
+        if let Key::Hash(_) = party {
+            *self.escrow.entry(party).or_default() += U512::from(amount);
+        }
+    }
+
+    fn add_holder(&mut self, holder_id: u64, owner: Key, token_id: u64, amount: u64) {
+        self.holders.insert(
+            holder_id,
+            Holder {
+                owner,
+                token_id,
+                amount,
+            },
+        );
+        self.owners.entry(owner).or_default().insert(holder_id);
+        self.holders_cnt = self.holders_cnt.max(holder_id);
+    }
+
+    fn remove_pending_request(&mut self, request: &Request, request_id: u64) {
+        remove_from(&mut self.producer_requests, &request.producer, request_id);
+        remove_from(&mut self.publisher_requests, &request.publisher, request_id);
+    }
+
+    fn holder(&self, holder_id: u64) -> Result<&Holder, IndexerError> {
+        self.holders
+            .get(&holder_id)
+            .ok_or(IndexerError::HolderNotFound(holder_id))
+    }
+
+    fn holder_mut(&mut self, holder_id: u64) -> Result<&mut Holder, IndexerError> {
+        self.holders
+            .get_mut(&holder_id)
+            .ok_or(IndexerError::HolderNotFound(holder_id))
+    }
+
+    fn approval_mut(&mut self, approved_id: u64) -> Result<&mut Approval, IndexerError> {
+        self.approvals
+            .get_mut(&approved_id)
+            .ok_or(IndexerError::ApprovalNotFound(approved_id))
+    }
+}
+
+/// Removes the id from the list of the account, keeping the (possibly empty) list, as the contract does
+fn remove_from(lists: &mut BTreeMap<Key, BTreeSet<u64>>, account: &Key, id: u64) {
+    if let Some(list) = lists.get_mut(account) {
+        list.remove(&id);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use casper_types::{account::AccountHash, bytesrepr, Key, PublicKey, SecretKey, U512};
+
+    use super::*;
+
+    const PRODUCER: Key = Key::Account(AccountHash::new([1u8; 32]));
+    const PUBLISHER: Key = Key::Account(AccountHash::new([2u8; 32]));
+    const BUYER: Key = Key::Account(AccountHash::new([3u8; 32]));
+    const PACKAGE: Key = Key::Hash([4u8; 32]);
+
+    fn mint(recipient: Key, token_id: u64, holder_id: u64, amount: u64) -> DropLinkedEvent {
+        DropLinkedEvent::Mint {
+            recipient,
+            token_id,
+            holder_id,
+            amount,
+            comission: 1234,
+            price: 20,
+        }
+    }
+
+    fn settlement(order_id: u64, producer: Key, publisher: Key) -> DropLinkedEvent {
+        DropLinkedEvent::Settlement {
+            order_id,
+            buyer: BUYER,
+            approved_id: 1,
+            token_id: 1,
+            amount: 1,
+            product_price: 200,
+            ratio: 1_000_000_000,
+            ratio_decimals: 0,
+            shipping_price: 10,
+            tax_price: 5,
+            fee: 100,
+            producer,
+            producer_amount: 190,
+            publisher,
+            publisher_amount: 20,
+            treasury_amount: 2,
+            refund: U512::from(7u64),
+        }
+    }
+
+    /// 10 of token 1 minted to the producer (holder 1), with a request of 4 of it (request 1) approved to the publisher (approved 1)
+    fn listed() -> Marketplace {
+        let mut marketplace = Marketplace::new();
+        marketplace
+            .apply_all(vec![
+                mint(PRODUCER, 1, 1, 10),
+                DropLinkedEvent::PublishRequest {
+                    owner: PRODUCER,
+                    publisher: PUBLISHER,
+                    amount: 4,
+                    holder_id: 1,
+                    request_id: 1,
+                },
+                DropLinkedEvent::ApprovedPublish {
+                    request_id: 1,
+                    approved_id: 1,
+                },
+            ])
+            .unwrap();
+        marketplace
+    }
+
+    #[test]
+    fn mint_adds_token_and_holder() {
+        let mut marketplace = Marketplace::new();
+        marketplace.apply(mint(PRODUCER, 1, 1, 10)).unwrap();
+        // a holder id of 0 adds to the existing holder of the recipient
+        marketplace.apply(mint(PRODUCER, 1, 0, 5)).unwrap();
+        marketplace.apply(mint(PRODUCER, 2, 2, 1)).unwrap();
+
+        assert_eq!(
+            marketplace.tokens[&1],
+            Token {
+                price: 20,
+                comission: 1234,
+                total_supply: 15,
+            }
+        );
+        assert_eq!(marketplace.holders[&1].amount, 15);
+        assert_eq!(marketplace.owners[&PRODUCER], BTreeSet::from([1, 2]));
+        assert_eq!(marketplace.holders_cnt, 2);
+        assert_eq!(marketplace.events_cnt, 3);
+        assert_eq!(
+            marketplace.apply(mint(BUYER, 1, 0, 1)),
+            Err(IndexerError::HolderNotFound(0))
+        );
+    }
+
+    #[test]
+    fn publish_request_and_approve() {
+        let mut marketplace = Marketplace::new();
+        marketplace.apply(mint(PRODUCER, 1, 1, 10)).unwrap();
+        marketplace
+            .apply(DropLinkedEvent::PublishRequest {
+                owner: PRODUCER,
+                publisher: PUBLISHER,
+                amount: 4,
+                holder_id: 1,
+                request_id: 1,
+            })
+            .unwrap();
+        assert_eq!(
+            marketplace.producer_requests[&PRODUCER],
+            BTreeSet::from([1])
+        );
+        assert_eq!(
+            marketplace.publisher_requests[&PUBLISHER],
+            BTreeSet::from([1])
+        );
+
+        marketplace
+            .apply(DropLinkedEvent::ApprovedPublish {
+                request_id: 1,
+                approved_id: 1,
+            })
+            .unwrap();
+        assert_eq!(
+            marketplace.approvals[&1],
+            Approval {
+                holder_id: 1,
+                amount: 4,
+                producer: PRODUCER,
+                publisher: PUBLISHER,
+                token_id: 1,
+            }
+        );
+        assert!(marketplace.producer_requests[&PRODUCER].is_empty());
+        assert!(marketplace.publisher_requests[&PUBLISHER].is_empty());
+        assert_eq!(
+            marketplace.producer_approved[&PRODUCER],
+            BTreeSet::from([1])
+        );
+        assert_eq!(
+            marketplace.publisher_approved[&PUBLISHER],
+            BTreeSet::from([1])
+        );
+        // the approved request is kept, as the contract keeps it
+        assert!(marketplace.requests.contains_key(&1));
+        assert_eq!(
+            marketplace.apply(DropLinkedEvent::ApprovedPublish {
+                request_id: 2,
+                approved_id: 2,
+            }),
+            Err(IndexerError::RequestNotFound(2))
+        );
+    }
+
+    #[test]
+    fn disapprove_unlists_the_exhausted_approval() {
+        let mut marketplace = listed();
+        marketplace
+            .apply(DropLinkedEvent::DisapprovedPublish {
+                approved_id: 1,
+                amount: 3,
+            })
+            .unwrap();
+        assert_eq!(marketplace.approvals[&1].amount, 1);
+        assert_eq!(
+            marketplace.producer_approved[&PRODUCER],
+            BTreeSet::from([1])
+        );
+
+        marketplace
+            .apply(DropLinkedEvent::DisapprovedPublish {
+                approved_id: 1,
+                amount: 1,
+            })
+            .unwrap();
+        assert_eq!(marketplace.approvals[&1].amount, 0);
+        assert!(marketplace.producer_approved[&PRODUCER].is_empty());
+        assert!(marketplace.publisher_approved[&PUBLISHER].is_empty());
+        assert_eq!(
+            marketplace.apply(DropLinkedEvent::DisapprovedPublish {
+                approved_id: 1,
+                amount: 1,
+            }),
+            Err(IndexerError::NotEnoughAmount)
+        );
+        assert_eq!(
+            marketplace.apply(DropLinkedEvent::DisapprovedPublish {
+                approved_id: 2,
+                amount: 1,
+            }),
+            Err(IndexerError::ApprovalNotFound(2))
+        );
+    }
+
+    #[test]
+    fn cancel_request_unlists_it() {
+        let mut marketplace = Marketplace::new();
+        marketplace.apply(mint(PRODUCER, 1, 1, 10)).unwrap();
+        marketplace
+            .apply(DropLinkedEvent::PublishRequest {
+                owner: PRODUCER,
+                publisher: PUBLISHER,
+                amount: 4,
+                holder_id: 1,
+                request_id: 1,
+            })
+            .unwrap();
+        marketplace
+            .apply(DropLinkedEvent::CancelRequest { request_id: 1 })
+            .unwrap();
+        assert!(marketplace.producer_requests[&PRODUCER].is_empty());
+        assert!(marketplace.publisher_requests[&PUBLISHER].is_empty());
+        assert!(marketplace.requests.contains_key(&1));
+        assert_eq!(
+            marketplace.apply(DropLinkedEvent::CancelRequest { request_id: 2 }),
+            Err(IndexerError::RequestNotFound(2))
+        );
+    }
+
+    #[test]
+    fn buy_moves_the_amount_to_the_buyer() {
+        let mut marketplace = listed();
+        let buy = |amount| DropLinkedEvent::Buy {
+            amount,
+            approved_id: 1,
+            buyer: BUYER,
+        };
+        // the first buy creates a holder for the buyer, and the next ones add to it
+        marketplace.apply(buy(1)).unwrap();
+        marketplace.apply(buy(2)).unwrap();
+        assert_eq!(marketplace.approvals[&1].amount, 1);
+        assert_eq!(marketplace.holders[&1].amount, 7);
+        assert_eq!(
+            marketplace.holders[&2],
+            Holder {
+                owner: BUYER,
+                token_id: 1,
+                amount: 3,
+            }
+        );
+        assert_eq!(
+            marketplace.holders_of(&BUYER),
+            vec![(2, &marketplace.holders[&2])]
+        );
+        assert_eq!(
+            marketplace.apply(buy(2)),
+            Err(IndexerError::NotEnoughAmount)
+        );
+    }
+
+    #[test]
+    fn settlement_escrows_the_shares_of_contract_packages() {
+        let mut marketplace = listed();
+        marketplace.apply(settlement(1, PRODUCER, PACKAGE)).unwrap();
+        marketplace
+            .apply(settlement(2, PACKAGE, PUBLISHER))
+            .unwrap();
+        assert_eq!(
+            marketplace.sales[&1],
+            Sale {
+                buyer: BUYER,
+                approved_id: 1,
+                token_id: 1,
+                amount: 1,
+                product_price: 200,
+                shipping_price: 10,
+                tax_price: 5,
+                fee: 100,
+                producer_amount: 190,
+                publisher_amount: 20,
+                treasury_amount: 2,
+                refund: U512::from(7u64),
+            }
+        );
+        // the accounts are paid directly, so only the package has an escrow balance
+        assert_eq!(marketplace.escrow.len(), 1);
+        assert_eq!(marketplace.escrow[&PACKAGE], U512::from(210u64));
+
+        marketplace
+            .apply(DropLinkedEvent::Withdrawn {
+                recipient: PACKAGE,
+                amount: U512::from(210u64),
+            })
+            .unwrap();
+        assert_eq!(marketplace.escrow[&PACKAGE], U512::zero());
+        marketplace
+            .apply(settlement(3, PACKAGE, PUBLISHER))
+            .unwrap();
+        assert_eq!(marketplace.escrow[&PACKAGE], U512::from(190u64));
+    }
+
+    #[test]
+    fn payment_is_recorded() {
+        let secret_key = SecretKey::ed25519_from_bytes([5u8; 32]).unwrap();
+        let recipient = PublicKey::from(&secret_key);
+        let mut marketplace = Marketplace::new();
+        marketplace
+            .apply(DropLinkedEvent::Payment {
+                payer: BUYER,
+                recipient: recipient.clone(),
+                product_price: U512::from(1000u64),
+                shipping_price: U512::from(20u64),
+                tax_price: U512::from(10u64),
+                fee: 100,
+                treasury_amount: U512::from(10u64),
+                recipient_amount: U512::from(1020u64),
+                order_reference: Some("order-1".to_string()),
+            })
+            .unwrap();
+        assert_eq!(
+            marketplace.payments,
+            vec![Payment {
+                payer: BUYER,
+                recipient,
+                fee: 100,
+                treasury_amount: U512::from(10u64),
+                recipient_amount: U512::from(1020u64),
+                order_reference: Some("order-1".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn compacted_removes_the_records() {
+        let mut marketplace = listed();
+        marketplace
+            .apply(DropLinkedEvent::DisapprovedPublish {
+                approved_id: 1,
+                amount: 4,
+            })
+            .unwrap();
+        marketplace
+            .apply(DropLinkedEvent::Compacted {
+                holder_ids: vec![1],
+                approved_ids: vec![1],
+                request_ids: vec![1],
+            })
+            .unwrap();
+        assert!(marketplace.holders.is_empty());
+        assert!(marketplace.owners[&PRODUCER].is_empty());
+        assert!(marketplace.approvals.is_empty());
+        assert!(marketplace.requests.is_empty());
+        assert!(marketplace.producer_approved[&PRODUCER].is_empty());
+        // ids that are already removed are skipped
+        marketplace
+            .apply(DropLinkedEvent::Compacted {
+                holder_ids: vec![1, 2],
+                approved_ids: vec![],
+                request_ids: vec![],
+            })
+            .unwrap();
+        assert_eq!(marketplace.events_cnt, 6);
+    }
+
+    #[test]
+    fn other_events_are_only_counted() {
+        let mut marketplace = listed();
+        let before = marketplace.clone();
+        marketplace
+            .apply(DropLinkedEvent::ConfigChanged {
+                name: "fee".to_string(),
+                old_value: "100".to_string(),
+                new_value: "200".to_string(),
+            })
+            .unwrap();
+        assert_eq!(marketplace.events_cnt, before.events_cnt + 1);
+        marketplace.events_cnt = before.events_cnt;
+        assert_eq!(marketplace, before);
+        assert_eq!(
+            marketplace.apply_bytes(&[]),
+            Err(IndexerError::Bytesrepr(bytesrepr::Error::EarlyEndOfStream))
+        );
+    }
+}
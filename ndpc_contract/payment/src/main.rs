@@ -12,9 +12,23 @@ use alloc::string::String;
 
 use casper_contract::{
     contract_api::{runtime, system, account},
+    ext_ffi,
     unwrap_or_revert::UnwrapOrRevert,
 };
-use casper_types::{Key, U512, ContractHash, RuntimeArgs, ApiError};
+use casper_types::{api_error, Key, U512, ContractHash, RuntimeArgs, ApiError};
+
+/// Returns true if the deploy has a runtime arg with the given name
+fn has_named_arg(name: &str) -> bool {
+    let mut arg_size: usize = 0;
+    let ret = unsafe {
+        ext_ffi::casper_get_named_arg_size(name.as_bytes().as_ptr(), name.len(), &mut arg_size as *mut usize)
+    };
+    match api_error::result_from(ret) {
+        Ok(()) => true,
+        Err(ApiError::MissingArgument) => false,
+        Err(error) => runtime::revert(error),
+    }
+}
 
 
 #[no_mangle]
@@ -37,6 +51,10 @@ pub extern "C" fn call() {
     runtimeargs.insert("shipping_price", shipping_price).unwrap_or_revert_with(ApiError::User(8));
     runtimeargs.insert("product_price", product_price).unwrap_or_revert_with(ApiError::User(8));
     runtimeargs.insert("tax_price", tax_price).unwrap_or_revert_with(ApiError::User(9));
+    if has_named_arg("order_reference") {
+        let order_reference : String = runtime::get_named_arg("order_reference");
+        runtimeargs.insert("order_reference", order_reference).unwrap_or_revert_with(ApiError::User(10));
+    }
     runtime::call_contract::<()>(contract_hash, entry_point_name, runtimeargs);
 
 }
\ No newline at end of file
@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[cfg(not(target_arch = "wasm32"))]
+compile_error!("target arch should be wasm32: compile with '--target wasm32-unknown-unknown'");
+
+// We need to explicitly import the std alloc crate and `alloc::string::String` as we're in a
+// `no_std` environment.
+extern crate alloc;
+
+use alloc::string::String;
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{runtime_args, ApiError, ContractHash, Key, RuntimeArgs, U256};
+
+/// Calls the `metadata` entrypoint of the contract for the token `id`, and stores the returned json under the `metadata` named key of the caller,
+/// so that the return value of the entrypoint could be read from the global state (e.g. by the integration tests)
+#[no_mangle]
+pub extern "C" fn call() {
+    let contract_hash_key: Key = runtime::get_named_arg("contract_hash");
+    let id: U256 = runtime::get_named_arg("id");
+
+    let contract_hash_bytes = contract_hash_key.into_hash().unwrap_or_revert_with(ApiError::User(1));
+    let contract_hash = ContractHash::new(contract_hash_bytes);
+    let metadata: String = runtime::call_contract(contract_hash, "metadata", runtime_args! { "id" => id });
+    runtime::put_key("metadata", storage::new_uref(metadata).into());
+}
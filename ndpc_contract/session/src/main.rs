@@ -8,13 +8,13 @@ compile_error!("target arch should be wasm32: compile with '--target wasm32-unkn
 // `no_std` environment.
 extern crate alloc;
 
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 
 use casper_contract::{
     contract_api::{runtime, system, account},
     unwrap_or_revert::UnwrapOrRevert,
 };
-use casper_types::{Key, U512, ContractHash, RuntimeArgs, ApiError};
+use casper_types::{Key, U512, ContractHash, RuntimeArgs, ApiError, PublicKey};
 
 
 #[no_mangle]
@@ -25,8 +25,9 @@ pub extern "C" fn call() {
     let shipping_price: U512 = runtime::get_named_arg("shipping_price");
     let tax_price: U512 = runtime::get_named_arg("tax_price");
     let contract_hash_key : Key = runtime::get_named_arg("contract_hash");
-    let ratio : String = runtime::get_named_arg("current_price_timestamp");
-    let signature : String = runtime::get_named_arg("signature");
+    let quote : String = runtime::get_named_arg("quote");
+    let signers : Vec<PublicKey> = runtime::get_named_arg("signers");
+    let signatures : Vec<String> = runtime::get_named_arg("signatures");
     
     let contract_hash_bytes = contract_hash_key.into_hash().unwrap_or_revert_with(ApiError::User(1));
     let contract_hash = ContractHash::new(contract_hash_bytes);
@@ -39,8 +40,9 @@ pub extern "C" fn call() {
     runtimeargs.insert("purse_addr", Key::URef(new_purse)).unwrap_or_revert_with(ApiError::User(3));
     runtimeargs.insert("amount", cnt).unwrap_or_revert_with(ApiError::User(4));
     runtimeargs.insert("approved_id", approved_id).unwrap_or_revert_with(ApiError::User(5));
-    runtimeargs.insert("signature", signature).unwrap_or_revert_with(ApiError::User(6));
-    runtimeargs.insert("current_price_timestamp", ratio).unwrap_or_revert_with(ApiError::User(7));
+    runtimeargs.insert("signers", signers).unwrap_or_revert_with(ApiError::User(6));
+    runtimeargs.insert("signatures", signatures).unwrap_or_revert_with(ApiError::User(6));
+    runtimeargs.insert("quote", quote).unwrap_or_revert_with(ApiError::User(7));
     runtimeargs.insert("shipping_price", shipping_price).unwrap_or_revert_with(ApiError::User(8));
     runtimeargs.insert("tax_price", tax_price).unwrap_or_revert_with(ApiError::User(9));
     runtime::call_contract::<()>(contract_hash, entry_point_name, runtimeargs);
@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::fmt::Display;
     use std::path::PathBuf;
     use casper_engine_test_support::{
@@ -11,14 +11,17 @@ mod tests {
         DEFAULT_GENESIS_CONFIG_HASH, DEFAULT_PAYMENT, WasmTestBuilder,
     };
     use casper_execution_engine::core::engine_state::{
-        run_genesis_request::RunGenesisRequest, GenesisAccount,
+        run_genesis_request::RunGenesisRequest, ExecuteRequest, GenesisAccount,
     };
+    use casper_execution_engine::core::{engine_state, execution};
     use casper_execution_engine::storage::global_state::in_memory::InMemoryGlobalState;
     use casper_types::CLTyped;
     use casper_types::{
-        account::AccountHash, runtime_args, Key, Motes, PublicKey, RuntimeArgs, SecretKey, U512, ContractHash, Contract,
+        account::AccountHash, runtime_args, ApiError, AsymmetricType, CLValue, Key, Motes, PublicKey, RuntimeArgs, SecretKey, U256, U512, ContractHash, Contract,
     };
-    use casper_types::bytesrepr::{ToBytes, FromBytes};
+    use casper_types::bytesrepr::{Bytes, ToBytes, FromBytes};
+    use casper_types::{ExecutionResult, StoredValue};
+    use droplinked_indexer::{events_from_execution_result, DropLinkedEvent, Marketplace};
     // Defining Objects needed to be used with testing contract : 
     const METADATA_HASH_LENGTH: usize = 32;
 
@@ -31,49 +34,29 @@ mod tests {
             base16::encode_lower(&self.0)
         }
     }
-    pub struct U64list{
-        pub list : BTreeSet<u64>
-    }
-    impl ToBytes for U64list{
-        fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
-            let mut result = Vec::new();
-            result.append(&mut self.list.to_bytes()?);
-            Ok(result)
-        }
-        fn into_bytes(self) -> Result<Vec<u8>, casper_types::bytesrepr::Error>
-        where
-            Self: Sized,
-        {
-            self.to_bytes()
-        }
-        fn serialized_length(&self) -> usize{
-            self.list.serialized_length()
-        }
-    }
-    impl FromBytes for U64list{
-        fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
-            let (list, rem) = FromBytes::from_bytes(bytes)?;
-            Ok((U64list{list}, rem))
-        }
-        fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), casper_types::bytesrepr::Error> {
-            Self::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
-        }
-    }
-    impl CLTyped for U64list{
-        fn cl_type() -> casper_types::CLType {
-            casper_types::CLType::List(Box::new(casper_types::CLType::U64))
+    /// Layout version of the records that the contract stores, written as their first byte
+    const RECORD_VERSION: u8 = 1;
+    /// Layout version of the approved listings, whose producer and publisher are keys (an account or a contract package)
+    const APPROVED_NFT_VERSION: u8 = 2;
+    /// Reads the version byte of a record, checks it against the expected version and returns the bytes of its fields
+    fn record_fields(bytes: &[u8], expected: u8) -> Result<&[u8], casper_types::bytesrepr::Error> {
+        let (version, rem): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
+        if version != expected {
+            return Err(casper_types::bytesrepr::Error::Formatting);
         }
+        Ok(rem)
     }
     pub struct ApprovedNFT {
         pub holder_id: u64,
         pub amount: u64,
-        pub owneraccount: AccountHash,
-        pub publisheraccount: AccountHash,
+        pub owneraccount: Key,
+        pub publisheraccount: Key,
         pub token_id: u64,
     }
     impl ToBytes for ApprovedNFT {
         fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
             let mut result = Vec::new();
+            result.append(&mut APPROVED_NFT_VERSION.to_bytes()?);
             result.append(&mut self.holder_id.to_bytes()?);
             result.append(&mut self.amount.to_bytes()?);
             result.append(&mut self.owneraccount.to_bytes()?);
@@ -88,7 +71,8 @@ mod tests {
             self.to_bytes()
         }
         fn serialized_length(&self) -> usize {
-            self.holder_id.serialized_length()
+            APPROVED_NFT_VERSION.serialized_length()
+                + self.holder_id.serialized_length()
                 + self.amount.serialized_length()
                 + self.owneraccount.serialized_length()
                 + self.publisheraccount.serialized_length()
@@ -98,7 +82,8 @@ mod tests {
     
     impl FromBytes for ApprovedNFT {
         fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
-            let (holder_id, rem) = FromBytes::from_bytes(bytes)?;
+            let rem = record_fields(bytes, APPROVED_NFT_VERSION)?;
+            let (holder_id, rem) = FromBytes::from_bytes(rem)?;
             let (amount, rem) = FromBytes::from_bytes(rem)?;
             let (owneraccount, rem) = FromBytes::from_bytes(rem)?;
             let (publisheraccount, rem) = FromBytes::from_bytes(rem)?;
@@ -125,7 +110,7 @@ mod tests {
     }
     impl Display for ApprovedNFT{
         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-            write!(f, "{{\"holder_id\":\"{}\",\"amount\":\"{}\",\"owneraccount\":\"{}\",\"publisheraccount\":\"{}\",\"token_id\":\"{}\"}}",self.holder_id,self.amount,self.owneraccount,self.publisheraccount,self.token_id)
+            write!(f, "{{\"holder_id\":\"{}\",\"amount\":\"{}\",\"owneraccount\":\"{}\",\"publisheraccount\":\"{}\",\"token_id\":\"{}\"}}",self.holder_id,self.amount,self.owneraccount.to_formatted_string(),self.publisheraccount.to_formatted_string(),self.token_id)
         }
     }   
 
@@ -139,6 +124,7 @@ mod tests {
     impl ToBytes for NftMetadata {
         fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
             let mut result = Vec::new();
+            result.append(&mut RECORD_VERSION.to_bytes()?);
             result.append(&mut self.name.to_bytes()?);
             result.append(&mut self.token_uri.to_bytes()?);
             result.append(&mut self.checksum.to_bytes()?);
@@ -153,7 +139,8 @@ mod tests {
             self.to_bytes()
         }
         fn serialized_length(&self) -> usize {
-            self.name.serialized_length()
+            RECORD_VERSION.serialized_length()
+                + self.name.serialized_length()
                 + self.token_uri.serialized_length()
                 + self.checksum.serialized_length()
                 + self.price.serialized_length()
@@ -169,7 +156,8 @@ mod tests {
     
     impl FromBytes for NftMetadata {
         fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
-            let (name, rem) = FromBytes::from_bytes(bytes)?;
+            let rem = record_fields(bytes, RECORD_VERSION)?;
+            let (name, rem) = FromBytes::from_bytes(rem)?;
             let (token_uri, rem) = FromBytes::from_bytes(rem)?;
             let (checksum, rem) = FromBytes::from_bytes(rem)?;
             let (price, rem) = FromBytes::from_bytes(rem)?;
@@ -200,11 +188,108 @@ mod tests {
         }
     }
     
+    pub struct NFTHolder {
+        pub amount: u64,
+        pub token_id: u64,
+    }
+    impl ToBytes for NFTHolder {
+        fn to_bytes(&self) -> Result<Vec<u8>, casper_types::bytesrepr::Error> {
+            let mut result = Vec::new();
+            result.append(&mut RECORD_VERSION.to_bytes()?);
+            result.append(&mut self.amount.to_bytes()?);
+            result.append(&mut self.token_id.to_bytes()?);
+            Ok(result)
+        }
+        fn serialized_length(&self) -> usize {
+            RECORD_VERSION.serialized_length() + self.amount.serialized_length() + self.token_id.serialized_length()
+        }
+    }
+    impl FromBytes for NFTHolder {
+        fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+            let rem = record_fields(bytes, RECORD_VERSION)?;
+            let (amount, rem) = FromBytes::from_bytes(rem)?;
+            let (token_id, rem) = FromBytes::from_bytes(rem)?;
+            Ok((NFTHolder { amount, token_id }, rem))
+        }
+    }
+    impl CLTyped for NFTHolder {
+        fn cl_type() -> casper_types::CLType {
+            casper_types::CLType::Any
+        }
+    }
+
+    /// Reasons of the provenance entries, as the contract stores them
+    const PROVENANCE_MINT: u8 = 0;
+    const PROVENANCE_BUY: u8 = 1;
+    pub struct ProvenanceEntry {
+        pub from: Option<Key>,
+        pub to: Option<Key>,
+        pub quantity: u64,
+        pub blocktime: u64,
+        pub reason: u8,
+    }
+    impl FromBytes for ProvenanceEntry {
+        fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), casper_types::bytesrepr::Error> {
+            let rem = record_fields(bytes, RECORD_VERSION)?;
+            let (from, rem) = FromBytes::from_bytes(rem)?;
+            let (to, rem) = FromBytes::from_bytes(rem)?;
+            let (quantity, rem) = FromBytes::from_bytes(rem)?;
+            let (blocktime, rem) = FromBytes::from_bytes(rem)?;
+            let (reason, rem) = FromBytes::from_bytes(rem)?;
+            Ok((ProvenanceEntry { from, to, quantity, blocktime, reason }, rem))
+        }
+    }
+    impl CLTyped for ProvenanceEntry {
+        fn cl_type() -> casper_types::CLType {
+            casper_types::CLType::Any
+        }
+    }
 
     // --------------------------------------------------------------------------------------------------------------
     
+    /// The session args that the tests install the contract with, the deployer is the treasury
+    fn install_args() -> RuntimeArgs{
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let account_addr = AccountHash::from(&public_key);
+        runtime_args! {
+            "ratio_verifier" => "0144f5adf499591351807bc83490314262bd6846beee80a16269a83c9901ecec8a".to_string(),
+            "fee" => 100u64,
+            "quote_freshness" => 130000u64,
+            "treasury" => Key::from(account_addr)
+        }
+    }
+
     fn install_contract() -> (WasmTestBuilder<InMemoryGlobalState> ,ContractHash, Contract){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let account_addr = AccountHash::from(&public_key);
+        let mut builder = install_contract_with_args(install_args());
+        builder.expect_success();
+        println!("Deployed The contract!");
+        // ---------------------------------------
+        
+        // Get the contract hash from NAMED_KEYS of the deployer account
+        let contract_hash = builder
+            .get_expected_account(account_addr)
+            .named_keys()
+            .get("droplinked_contract")
+            .expect("must have contract hash key as part of contract creation")
+            .into_hash()
+            .map(ContractHash::new)
+            .expect("must get contract hash");
+        println!("Contract hash = {}" , contract_hash);
+        let contract : Contract = builder.get_contract(contract_hash).unwrap();
+        (builder, contract_hash , contract)
+    }
 
+    /// Runs the genesis (with the deployer, producer and publisher accounts) and deploys the contract with the given session args, without checking the result
+    fn install_contract_with_args(session_args: RuntimeArgs) -> WasmTestBuilder<InMemoryGlobalState>{
+        install_wasm_with_args(CONTRACT_WASM, session_args)
+    }
+
+    /// Runs the genesis (with the deployer, producer and publisher accounts) and deploys the given contract wasm with the given session args, without checking the result
+    fn install_wasm_with_args(wasm: &str, session_args: RuntimeArgs) -> WasmTestBuilder<InMemoryGlobalState>{
         let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
         let public_key = PublicKey::from(&secret_key);
         let account_addr = AccountHash::from(&public_key);
@@ -231,11 +316,7 @@ mod tests {
             None
         );
         // Deploying the contract
-        let session_code = PathBuf::from(CONTRACT_WASM);
-        let session_args = runtime_args! {
-            "ratio_verifier" => "0144f5adf499591351807bc83490314262bd6846beee80a16269a83c9901ecec8a".to_string(),
-            "fee" => 100u64
-        };
+        let session_code = PathBuf::from(wasm);
         let deploy_item = DeployItemBuilder::new()
         .with_empty_payment_bytes(runtime_args! {
             ARG_AMOUNT => *DEFAULT_PAYMENT
@@ -257,22 +338,43 @@ mod tests {
         );
         let mut builder = InMemoryWasmTestBuilder::default();
         builder.run_genesis(&run_genesis_request).commit();
-        builder.exec(execute_request).commit().expect_success();
-        println!("Deployed The contract!");
-        // ---------------------------------------
-        
-        // Get the contract hash from NAMED_KEYS of the deployer account
-        let contract_hash = builder
-            .get_expected_account(account_addr)
-            .named_keys()
-            .get("droplinked_contract")
-            .expect("must have contract hash key as part of contract creation")
-            .into_hash()
-            .map(ContractHash::new)
-            .expect("must get contract hash");
-        println!("Contract hash = {}" , contract_hash);
-        let contract : Contract = builder.get_contract(contract_hash).unwrap();
-        (builder, contract_hash , contract)
+        builder.exec(execute_request).commit();
+        builder
+    }
+
+    /// Asserts that the last execution reverted with the given user error of the contract
+    fn assert_user_error(builder: &WasmTestBuilder<InMemoryGlobalState>, code: u16){
+        match builder.get_error() {
+            Some(engine_state::Error::Exec(execution::Error::Revert(api_error))) => assert_eq!(api_error, ApiError::User(code)),
+            error => panic!("expected the user error {}, got {:?}", code, error),
+        }
+    }
+
+    /// Deploys the contract wasm again from the account, which adds a new version to the package of the account
+    fn upgrade_contract_request(account: AccountHash, deploy_hash: [u8; 32]) -> ExecuteRequest{
+        let deploy_item = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {
+                ARG_AMOUNT => *DEFAULT_PAYMENT
+            })
+            .with_session_code(PathBuf::from(CONTRACT_WASM), RuntimeArgs::new())
+            .with_authorization_keys(&[account])
+            .with_address(account)
+            .with_deploy_hash(deploy_hash)
+            .build();
+        ExecuteRequestBuilder::from_deploy_item(deploy_item).build()
+    }
+
+    /// Reads an indexed list of the contract (a `len` entry plus an entry per index, under blake2b hashed keys)
+    fn query_indexed_list(builder: &WasmTestBuilder<InMemoryGlobalState>, dict_uref: casper_types::URef, key: &str) -> Vec<u64>{
+        let query_entry = |suffix: String| -> Option<u64> {
+            let item_key = base16::encode_lower(&casper_types::crypto::blake2b(format!("{}_{}", key, suffix)));
+            builder
+                .query_dictionary_item(None, dict_uref, &item_key)
+                .ok()
+                .map(|value| value.as_cl_value().expect("should be cl value").clone().into_t::<u64>().expect("should be u64"))
+        };
+        let len = query_entry("len".to_string()).unwrap_or_default();
+        (0..len).map(|index| query_entry(index.to_string()).expect("should exist index")).collect()
     }
 
     #[test]
@@ -397,6 +499,30 @@ mod tests {
         builder.exec(execute_request).commit().expect_failure();
     }
 
+    #[test]
+    fn install_contract_with_invalid_verifier(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let account_addr = AccountHash::from(&public_key);
+        // Should fail with InvalidVerifierKey (68), as the ratio verifier is not a hex encoded public key
+        let builder = install_contract_with_args(runtime_args! {
+            "ratio_verifier" => "not-a-public-key".to_string(),
+            "fee" => 100u64,
+            "quote_freshness" => 130000u64,
+            "treasury" => Key::from(account_addr)
+        });
+        assert_user_error(&builder, 68);
+        // Should fail with UnsupportedSignatureScheme (69), as only Ed25519 keys could sign quotes
+        let secp256k1_key = PublicKey::from(&SecretKey::secp256k1_from_bytes([11u8; 32]).unwrap());
+        let builder = install_contract_with_args(runtime_args! {
+            "ratio_verifier" => secp256k1_key.to_hex(),
+            "fee" => 100u64,
+            "quote_freshness" => 130000u64,
+            "treasury" => Key::from(account_addr)
+        });
+        assert_user_error(&builder, 69);
+    }
+
     #[test]
     fn mint_product_with_error(){
         // The price is given with u8, which should be u64, so it does not execute successfullu!
@@ -435,6 +561,14 @@ mod tests {
     const PUBLISHER_ACCOUNT: [u8; 32] = [8u8; 32];
     const _CUSTOMER_ACCOUNT: [u8; 32] = [9u8; 32];
     const CONTRACT_WASM: &str = "contract.wasm";
+    /// The contract at version 7, before the balances index (built by `make build-legacy-contract`)
+    const LEGACY_CONTRACT_WASM: &str = "contract_v7.wasm";
+    const SESSION_WASM: &str = "session.wasm";
+    const PAYMENT_WASM: &str = "payment.wasm";
+    const READER_WASM: &str = "reader.wasm";
+    const GAS_BUDGETS: &str = "gas_budgets.txt";
+    /// The contract before the `StorageContext`, whose costs are the gas budgets (built by `make record-gas-budgets`)
+    const GAS_BASELINE_CONTRACT_WASM: &str = "contract_gas_baseline.wasm";
     
     #[test]
     fn mint_entrypoint(){
@@ -656,15 +790,8 @@ mod tests {
         // -----------------------------------------
         let producer_requests_dict_uref = contract.named_keys().get("producer_requests").unwrap().into_uref().unwrap();
         // Now the requests_cnt should be reduced by 1 so it should be 0 again
-        let requests_list : U64list = builder
-            .query_dictionary_item(None, producer_requests_dict_uref, producer_account_addr.to_string().as_str())
-            .expect("should exist dict")
-            .as_cl_value()
-            .expect("should be cl value")
-            .clone()
-            .into_t::<U64list>()
-            .expect("should be U64list");
-        assert_eq!(requests_list.list.len() , 0usize);
+        let requests_list = query_indexed_list(&builder, producer_requests_dict_uref, producer_account_addr.to_string().as_str());
+        assert_eq!(requests_list.len() , 0usize);
     }
 
     #[test]
@@ -898,17 +1025,10 @@ mod tests {
         // -----------------------------------
         // Verify the approvement
         let publishers_approved_uref = contract.named_keys().get("publishers_approved").unwrap().into_uref().unwrap();
-        let approved_nft_list : U64list = builder
-            .query_dictionary_item(None, publishers_approved_uref, publisher_account_addr.to_string().as_str())
-            .expect("should exist dict")
-            .as_cl_value()
-            .expect("should be cl value")
-            .clone()
-            .into_t::<U64list>()
-            .expect("should be U64list");
+        let approved_nft_list = query_indexed_list(&builder, publishers_approved_uref, publisher_account_addr.to_string().as_str());
 
             // there should exist one entity in there after aprovement
-        assert_eq!(approved_nft_list.list.len() , 1);
+        assert_eq!(approved_nft_list.len() , 1);
 
         let approved_uref = contract.named_keys().get("approved").unwrap().into_uref().unwrap();
         let approved_nft : ApprovedNFT = builder
@@ -921,7 +1041,7 @@ mod tests {
             .expect("should be U64list");
 
         // If you want to change the data of keys on the top of the file, you should edit account hashes below on your own
-        assert_eq!(format!("{}" , approved_nft).to_string() , "{\"holder_id\":\"1\",\"amount\":\"10\",\"owneraccount\":\"3d5de8c609159a0954e773dd686fb7724428316cb30e00bdc899976127747f55\",\"publisheraccount\":\"105b69f2d74a211a6cb337cba6751a8f15cc7b44b7c65329c29731b67e1ac047\",\"token_id\":\"1\"}");
+        assert_eq!(format!("{}" , approved_nft).to_string() , "{\"holder_id\":\"1\",\"amount\":\"10\",\"owneraccount\":\"account-hash-3d5de8c609159a0954e773dd686fb7724428316cb30e00bdc899976127747f55\",\"publisheraccount\":\"account-hash-105b69f2d74a211a6cb337cba6751a8f15cc7b44b7c65329c29731b67e1ac047\",\"token_id\":\"1\"}");
     }
     #[test]
     fn approve_entry_point_with_error(){
@@ -1467,6 +1587,2521 @@ mod tests {
             .commit();
     }
 
+    #[test]
+    fn buy_entry_point_with_malformed_quote(){
+        // Should not execute because the quote is not a hex encoded PriceQuote, so it could not be decoded
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let buyer_purse = builder.get_expected_account(publisher_account_addr).main_purse();
+        let contract_buy = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "buy",
+            runtime_args! {
+                "amount" => 1u64,
+                "purse_addr" => Key::URef(buyer_purse),
+                "approved_id" => 1u64,
+                "quote" => "2000,1680000000000".to_string(),
+                "signers" => Vec::<PublicKey>::new(),
+                "signatures" => Vec::<String>::new(),
+                "shipping_price" => U512::zero(),
+                "tax_price" => U512::zero()
+            }
+        ).build();
+        builder
+            .exec(contract_buy)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn set_quote_freshness_entry_point(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_set_quote_freshness = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_quote_freshness",
+            runtime_args! {
+                "quote_freshness" => 300000u64
+            }
+        ).build();
+        builder
+            .exec(contract_set_quote_freshness)
+            .expect_success()
+            .commit();
+        let quote_freshness = builder
+            .query(None, Key::Hash(contract_hash.value()), &["quote_freshness".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u64>()
+            .expect("should be u64.");
+        assert_eq!(quote_freshness, 300000u64);
+    }
+
+    #[test]
+    fn set_quote_freshness_entry_point_with_error(){
+        // Should not execute, the first call is not from the owner, and the second one is out of the allowed bounds
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_set_quote_freshness = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "set_quote_freshness",
+            runtime_args! {
+                "quote_freshness" => 300000u64
+            }
+        ).build();
+        builder
+            .exec(contract_set_quote_freshness)
+            .expect_failure()
+            .commit();
+        let contract_set_quote_freshness = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_quote_freshness",
+            runtime_args! {
+                "quote_freshness" => 1u64
+            }
+        ).build();
+        builder
+            .exec(contract_set_quote_freshness)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn oracle_signers_entry_points(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // Only the owner could add a signer
+        let contract_add_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "add_oracle_signer",
+            runtime_args! {
+                "public_key" => oracle_public_key.clone()
+            }
+        ).build();
+        builder
+            .exec(contract_add_oracle_signer)
+            .expect_failure()
+            .commit();
+        let contract_add_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "add_oracle_signer",
+            runtime_args! {
+                "public_key" => oracle_public_key.clone()
+            }
+        ).build();
+        builder
+            .exec(contract_add_oracle_signer)
+            .expect_success()
+            .commit();
+        let oracle_signers = builder
+            .query(None, Key::Hash(contract_hash.value()), &["oracle_signers".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<Vec<PublicKey>>()
+            .expect("should be Vec<PublicKey>.");
+        assert_eq!(oracle_signers.len(), 2usize);
+        assert!(oracle_signers.contains(&oracle_public_key));
+        // 2 of 2 is valid, 3 of 2 is not
+        let contract_set_oracle_threshold = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_oracle_threshold",
+            runtime_args! {
+                "threshold" => 2u8
+            }
+        ).build();
+        builder
+            .exec(contract_set_oracle_threshold)
+            .expect_success()
+            .commit();
+        let contract_set_oracle_threshold = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_oracle_threshold",
+            runtime_args! {
+                "threshold" => 3u8
+            }
+        ).build();
+        builder
+            .exec(contract_set_oracle_threshold)
+            .expect_failure()
+            .commit();
+        // Removing a signer would leave less signers than the threshold
+        let contract_remove_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "remove_oracle_signer",
+            runtime_args! {
+                "public_key" => oracle_public_key
+            }
+        ).build();
+        builder
+            .exec(contract_remove_oracle_signer)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn set_treasury_entry_point(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let treasury_account_addr = AccountHash::new([11u8; 32]);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // Only the owner could rotate the treasury
+        let contract_set_treasury = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "set_treasury",
+            runtime_args! {
+                "treasury" => Key::from(treasury_account_addr)
+            }
+        ).build();
+        builder
+            .exec(contract_set_treasury)
+            .expect_failure()
+            .commit();
+        let contract_set_treasury = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_treasury",
+            runtime_args! {
+                "treasury" => Key::from(treasury_account_addr)
+            }
+        ).build();
+        builder
+            .exec(contract_set_treasury)
+            .expect_success()
+            .commit();
+        let treasury = builder
+            .query(None, Key::Hash(contract_hash.value()), &["treasury".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<Key>()
+            .expect("should be Key.");
+        assert_eq!(treasury, Key::from(treasury_account_addr));
+    }
+
+    #[test]
+    fn access_control_entry_points(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // An operator could tune the quote freshness, but could not grant roles
+        let contract_grant_operator = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "grant_operator",
+            runtime_args! {
+                "account" => Key::from(publisher_account_addr)
+            }
+        ).build();
+        builder
+            .exec(contract_grant_operator)
+            .expect_success()
+            .commit();
+        let contract_set_quote_freshness = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "set_quote_freshness",
+            runtime_args! {
+                "quote_freshness" => 60000u64
+            }
+        ).build();
+        builder
+            .exec(contract_set_quote_freshness)
+            .expect_success()
+            .commit();
+        let contract_grant_operator = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "grant_operator",
+            runtime_args! {
+                "account" => Key::from(producer_account_addr)
+            }
+        ).build();
+        builder
+            .exec(contract_grant_operator)
+            .expect_failure()
+            .commit();
+        // The ownership is only transferred after the new owner accepts it
+        let contract_transfer_ownership = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "transfer_ownership",
+            runtime_args! {
+                "new_owner" => Key::from(producer_account_addr)
+            }
+        ).build();
+        builder
+            .exec(contract_transfer_ownership)
+            .expect_success()
+            .commit();
+        let contract_accept_ownership = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "accept_ownership",
+            runtime_args! {}
+        ).build();
+        builder
+            .exec(contract_accept_ownership)
+            .expect_failure()
+            .commit();
+        let contract_accept_ownership = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "accept_ownership",
+            runtime_args! {}
+        ).build();
+        builder
+            .exec(contract_accept_ownership)
+            .expect_success()
+            .commit();
+        let owner = builder
+            .query(None, Key::Hash(contract_hash.value()), &["owner".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<Key>()
+            .expect("should be Key.");
+        assert_eq!(owner, Key::from(producer_account_addr));
+    }
+
+    #[test]
+    fn roles_accept_contract_packages(){
+        // The owner could hand the ownership and the operator role to a contract package, any other key fails with UnsupportedKey (63)
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let package = Key::Hash([7u8; 32]);
+        let contract_transfer_ownership = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "transfer_ownership",
+            runtime_args! {
+                "new_owner" => package
+            }
+        ).build();
+        builder
+            .exec(contract_transfer_ownership)
+            .expect_success()
+            .commit();
+        let pending_owner = builder
+            .query(None, Key::Hash(contract_hash.value()), &["pending_owner".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<Option<Key>>()
+            .expect("should be Option<Key>.");
+        assert_eq!(pending_owner, Some(package));
+        let contract_grant_operator = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "grant_operator",
+            runtime_args! {
+                "account" => package
+            }
+        ).build();
+        builder
+            .exec(contract_grant_operator)
+            .expect_success()
+            .commit();
+        let operators_uref = contract.named_keys().get("operators").unwrap().into_uref().unwrap();
+        let is_operator = builder
+            .query_dictionary_item(None, operators_uref, &base16::encode_lower(&casper_types::crypto::blake2b(package.to_bytes().unwrap())))
+            .expect("should exist dict")
+            .as_cl_value()
+            .expect("should be cl value")
+            .clone()
+            .into_t::<bool>()
+            .expect("should be bool");
+        assert!(is_operator);
+        let contract_grant_operator = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "grant_operator",
+            runtime_args! {
+                "account" => Key::Balance([1u8; 32])
+            }
+        ).build();
+        builder
+            .exec(contract_grant_operator)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 63);
+    }
+
+    #[test]
+    fn set_fee_entry_point(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_set_fee = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_fee",
+            runtime_args! {
+                "fee" => 250u64,
+                "effective_at" => Option::<u64>::None
+            }
+        ).build();
+        builder
+            .exec(contract_set_fee)
+            .expect_success()
+            .commit();
+        let fee = builder
+            .query(None, Key::Hash(contract_hash.value()), &["fee".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u64>()
+            .expect("should be u64.");
+        assert_eq!(fee, 250u64);
+    }
+
+    #[test]
+    fn set_fee_entry_point_with_error(){
+        // Should not execute because the fee is more than the maximum fee (1000 basis points)
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_set_fee = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_fee",
+            runtime_args! {
+                "fee" => 5000u64,
+                "effective_at" => Option::<u64>::None
+            }
+        ).build();
+        builder
+            .exec(contract_set_fee)
+            .expect_failure()
+            .commit();
+    }
+
+
+    #[test]
+    fn rotate_oracle_signer_entry_point(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let ratio_verifier = PublicKey::from_hex("0144f5adf499591351807bc83490314262bd6846beee80a16269a83c9901ecec8a").unwrap();
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_rotate_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "rotate_oracle_signer",
+            runtime_args! {
+                "public_key" => ratio_verifier.clone(),
+                "new_public_key" => oracle_public_key.clone(),
+                "grace_period" => 60000u64
+            }
+        ).build();
+        builder
+            .exec(contract_rotate_oracle_signer)
+            .expect_success()
+            .commit();
+        let oracle_signers = builder
+            .query(None, Key::Hash(contract_hash.value()), &["oracle_signers".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<Vec<PublicKey>>()
+            .expect("should be Vec<PublicKey>.");
+        assert_eq!(oracle_signers, vec![oracle_public_key.clone()]);
+        let retired_oracle_signers = builder
+            .query(None, Key::Hash(contract_hash.value()), &["retired_oracle_signers".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<Vec<(PublicKey, PublicKey, u64)>>()
+            .expect("should be Vec<(PublicKey, PublicKey, u64)>.");
+        assert_eq!(retired_oracle_signers.len(), 1usize);
+        assert_eq!(retired_oracle_signers[0].0, ratio_verifier);
+        assert_eq!(retired_oracle_signers[0].1, oracle_public_key);
+    }
+
+    #[test]
+    fn rotate_oracle_signer_entry_point_with_error(){
+        // Should not execute because the grace period is more than the maximum (1 hour)
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let ratio_verifier = PublicKey::from_hex("0144f5adf499591351807bc83490314262bd6846beee80a16269a83c9901ecec8a").unwrap();
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_rotate_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "rotate_oracle_signer",
+            runtime_args! {
+                "public_key" => ratio_verifier,
+                "new_public_key" => oracle_public_key,
+                "grace_period" => 7200000u64
+            }
+        ).build();
+        builder
+            .exec(contract_rotate_oracle_signer)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn add_oracle_signer_with_unsupported_scheme(){
+        // Should fail with UnsupportedSignatureScheme (69), as the signatures of a Secp256k1 key could not be verified by the contract
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_add_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "add_oracle_signer",
+            runtime_args! {
+                "public_key" => PublicKey::from(&SecretKey::secp256k1_from_bytes([11u8; 32]).unwrap())
+            }
+        ).build();
+        builder
+            .exec(contract_add_oracle_signer)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 69);
+    }
+
+    #[test]
+    fn direct_pay_with_invalid_recipient(){
+        // Should fail with InvalidRecipientKey (67), as the recipient is not a hex encoded public key
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_direct_pay = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "direct_pay",
+            runtime_args! {
+                "product_price" => U512::from(1000u64),
+                "shipping_price" => U512::zero(),
+                "tax_price" => U512::zero(),
+                "recipient" => "zz01".to_string(),
+                "purse_addr" => Key::from(producer_account_addr)
+            }
+        ).build();
+        builder
+            .exec(contract_direct_pay)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 67);
+    }
+
+    #[test]
+    fn direct_pay_emits_payment(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let treasury_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let events_uref = contract.named_keys().get("__events").unwrap().into_uref().unwrap();
+        let fund_customer = ExecuteRequestBuilder::transfer(treasury_account_addr, runtime_args! {
+            "amount" => U512::from(DEFAULT_ACCOUNT_INITIAL_BALANCE),
+            "target" => customer_account_addr,
+            "id" => Option::<u64>::None
+        }).build();
+        builder.exec(fund_customer).expect_success().commit();
+
+        for order_reference in [None, Some("order-1234".to_string())] {
+            let producer_before = builder.get_purse_balance(builder.get_expected_account(producer_account_addr).main_purse());
+            let treasury_before = builder.get_purse_balance(builder.get_expected_account(treasury_account_addr).main_purse());
+            // 1 CSPR product price with 0.2 CSPR of shipping and 0.1 CSPR of tax, paid from a purse of 2 CSPR
+            let direct_pay_request = session_direct_pay_request(customer_account_addr, contract_hash, public_key_producer.to_hex(), order_reference.clone());
+            builder.exec(direct_pay_request).expect_success().commit();
+
+            let last_result = builder.get_last_exec_results().expect("should have the execution result")[0].clone();
+            let last_events = events_from_execution_result(&ExecutionResult::from(&*last_result), events_uref).unwrap();
+            assert_eq!(last_events.len(), 1);
+            // fee (1%) of the product price goes to the treasury, the rest of it with the shipping and tax prices to the recipient,
+            // and what is left in the purse is refunded to the payer
+            let treasury_amount = U512::from(10_000_000u64);
+            let recipient_amount = U512::from(990_000_000u64 + 200_000_000u64 + 100_000_000u64);
+            let refund = U512::from(2_000_000_000u64 - 1_300_000_000u64);
+            assert_eq!(last_events[0].1, DropLinkedEvent::Payment {
+                payer: Key::from(customer_account_addr),
+                recipient: public_key_producer.clone(),
+                product_price: U512::from(1_000_000_000u64),
+                shipping_price: U512::from(200_000_000u64),
+                tax_price: U512::from(100_000_000u64),
+                fee: 100,
+                treasury_amount,
+                recipient_amount,
+                order_reference,
+            });
+            assert_eq!(builder.get_purse_balance(builder.get_expected_account(producer_account_addr).main_purse()), producer_before + recipient_amount);
+            assert_eq!(builder.get_purse_balance(builder.get_expected_account(treasury_account_addr).main_purse()), treasury_before + treasury_amount);
+            let transfers = match ExecutionResult::from(&*last_result) {
+                ExecutionResult::Success { transfers, .. } => transfers,
+                ExecutionResult::Failure { error_message, .. } => panic!("direct_pay failed : {}", error_message),
+            };
+            let refunded = transfers.into_iter().any(|transfer_addr| {
+                match builder.query(None, Key::Transfer(transfer_addr), &[]) {
+                    Ok(StoredValue::Transfer(transfer)) => transfer.to == Some(customer_account_addr) && transfer.amount == refund,
+                    _ => false,
+                }
+            });
+            assert!(refunded);
+        }
+    }
+
+    #[test]
+    fn direct_pay_with_invalid_order_reference(){
+        // Should fail with InvalidOrderReference (58) for an empty or a longer than 64 bytes reference, and with InvalidOptionalArg (57) when it is not a String
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let cases = [
+            (CLValue::from_t(String::new()).unwrap(), 58),
+            (CLValue::from_t("a".repeat(65)).unwrap(), 58),
+            (CLValue::from_t(1234u64).unwrap(), 57),
+        ];
+        for (order_reference, code) in cases {
+            let mut args = runtime_args! {
+                "product_price" => U512::from(1000u64),
+                "shipping_price" => U512::zero(),
+                "tax_price" => U512::zero(),
+                "recipient" => public_key_producer.to_hex(),
+                "purse_addr" => Key::from(producer_account_addr)
+            };
+            args.insert_cl_value("order_reference", order_reference);
+            let contract_direct_pay = ExecuteRequestBuilder::contract_call_by_hash(
+                producer_account_addr,
+                contract_hash,
+                "direct_pay",
+                args
+            ).build();
+            builder
+                .exec(contract_direct_pay)
+                .expect_failure()
+                .commit();
+            assert_user_error(&builder, code);
+        }
+    }
+
+    #[test]
+    fn buy_with_malformed_signature(){
+        // Should fail with InvalidSignatureHex (65) when the signature is not hex, and with MalformedSignature (66) when it is not 64 bytes
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_add_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "add_oracle_signer",
+            runtime_args! {
+                "public_key" => oracle_public_key.clone()
+            }
+        ).build();
+        builder.exec(contract_add_oracle_signer).expect_success().commit();
+
+        let (quote, _signature) = signed_quote(1_000_000_000, 1_000_000, 1, producer_account_addr, &oracle_secret_key);
+        for (signature, code) in [("not-hex".to_string(), 65u16), ("abcd".to_string(), 66u16)] {
+            let buy_request = session_buy_request(producer_account_addr, contract_hash, 1, 1, quote.clone(), oracle_public_key.clone(), signature, 1_000_000);
+            builder.exec(buy_request).expect_failure().commit();
+            assert_user_error(&builder, code);
+        }
+    }
+
+    #[test]
+    fn set_paused_entry_point(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let mint_metadata = "{\"name\" : \"Nike Shoes\", \"token_uri\" : \"bafkreibjrxjhy7evb7e5rp6sfyp6rqi2slczpgl3p2pafqhqn7xx226rii\" , \"checksum\" : \"oijepriwguhjpersijf[aopcoisemriguhspiodcpsoeiruhgd\"}".to_string();
+        let mint_args = runtime_args! {
+            "amount" => 100u64,
+            "recipient" => Key::from(producer_account_addr),
+            "metadata" => mint_metadata,
+            "price" => 200u64,
+            "comission" => 1234u64
+        };
+        // Pause minting (4), other entrypoints stay open
+        let contract_set_paused = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_paused",
+            runtime_args! {
+                "paused" => 4u8
+            }
+        ).build();
+        builder
+            .exec(contract_set_paused)
+            .expect_success()
+            .commit();
+        let contract_mint_request = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "mint",
+            mint_args.clone(),
+        ).build();
+        builder
+            .exec(contract_mint_request)
+            .expect_failure()
+            .commit();
+        // Unpause and mint again
+        let contract_set_paused = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_paused",
+            runtime_args! {
+                "paused" => 0u8
+            }
+        ).build();
+        builder
+            .exec(contract_set_paused)
+            .expect_success()
+            .commit();
+        let contract_mint_request = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "mint",
+            mint_args,
+        ).build();
+        builder
+            .exec(contract_mint_request)
+            .expect_success()
+            .commit();
+    }
+
+    #[test]
+    fn set_paused_entry_point_with_error(){
+        // Should not execute because the caller is neither the owner nor an operator
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let contract_set_paused = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "set_paused",
+            runtime_args! {
+                "paused" => 31u8
+            }
+        ).build();
+        builder
+            .exec(contract_set_paused)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn upgrade_contract_test(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let mint_metadata = "{\"name\" : \"Nike Shoes\", \"token_uri\" : \"bafkreibjrxjhy7evb7e5rp6sfyp6rqi2slczpgl3p2pafqhqn7xx226rii\" , \"checksum\" : \"oijepriwguhjpersijf[aopcoisemriguhspiodcpsoeiruhgd\"}".to_string();
+        let contract_mint_request = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "mint",
+            runtime_args! {
+                "amount" => 100u64,
+                "recipient" => Key::from(producer_account_addr),
+                "metadata" => mint_metadata,
+                "price" => 200u64,
+                "comission" => 1234u64
+            },
+        ).build();
+        builder
+            .exec(contract_mint_request)
+            .expect_success()
+            .commit();
+        // Deploying the contract again from the deployer account adds a new version to its package
+        builder
+            .exec(upgrade_contract_request(deployer_account_addr, [42u8; 32]))
+            .expect_success()
+            .commit();
+        let new_contract_hash = builder
+            .get_expected_account(deployer_account_addr)
+            .named_keys()
+            .get("droplinked_contract")
+            .expect("must have contract hash key as part of contract upgrade")
+            .into_hash()
+            .map(ContractHash::new)
+            .expect("must get contract hash");
+        assert_ne!(new_contract_hash, contract_hash);
+        // The new version keeps the state of the previous one
+        let tokens_cnt = builder
+            .query(None, Key::Hash(new_contract_hash.value()), &["tokens_cnt".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u64>()
+            .expect("should be u64.");
+        assert_eq!(tokens_cnt, 1u64);
+        let contract_version = builder
+            .query(None, Key::Hash(new_contract_hash.value()), &["contract_version".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u32>()
+            .expect("should be u32.");
+        assert_eq!(contract_version, 11u32);
+        // The access uref of the package is only held by the contract
+        assert!(builder
+            .get_expected_account(deployer_account_addr)
+            .named_keys()
+            .get("droplinked_access_uref")
+            .is_none());
+        let new_contract : Contract = builder.get_contract(new_contract_hash).unwrap();
+        assert!(new_contract.named_keys().get("droplinked_access_uref").is_some());
+        // migrate is not callable out of the upgrade, and the previous version is disabled
+        let contract_migrate = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            new_contract_hash,
+            "migrate",
+            runtime_args! {}
+        ).build();
+        builder
+            .exec(contract_migrate)
+            .expect_failure()
+            .commit();
+        let contract_set_paused = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_paused",
+            runtime_args! {
+                "paused" => 0u8
+            }
+        ).build();
+        builder
+            .exec(contract_set_paused)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn upgrade_contract_without_authorization(){
+        // Should fail with AccessDenied (15) when anyone but the owner asks for the access uref of the package, and with UpgradeNotAuthorized (56)
+        // when the owner upgrades without an executed AuthorizeUpgrade proposal, once governance is configured
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // The deployer does not hold the access uref, so it could only add versions through upgrade_access
+        assert!(builder
+            .get_expected_account(deployer_account_addr)
+            .named_keys()
+            .get("droplinked_access_uref")
+            .is_none());
+        let contract_upgrade_access = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "upgrade_access",
+            runtime_args! {}
+        ).build();
+        builder
+            .exec(contract_upgrade_access)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 15);
+        let contract_set_governors = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_governors",
+            runtime_args! {
+                "governors" => vec![Key::from(deployer_account_addr)],
+                "threshold" => 1u8,
+                "delay" => 3_600_000u64
+            }
+        ).build();
+        builder
+            .exec(contract_set_governors)
+            .expect_success()
+            .commit();
+        builder
+            .exec(upgrade_contract_request(deployer_account_addr, [42u8; 32]))
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 56);
+        // An executed AuthorizeUpgrade proposal allows a single upgrade
+        let contract_propose = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "propose",
+            runtime_args! {
+                "action" => base16::encode_lower(&[7u8])
+            }
+        ).build();
+        builder
+            .exec(contract_propose)
+            .expect_success()
+            .commit();
+        let contract_execute_proposal = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "execute_proposal",
+            runtime_args! {
+                "proposal_id" => 1u64
+            }
+        ).with_block_time(3_600_000).build();
+        builder
+            .exec(contract_execute_proposal)
+            .expect_success()
+            .commit();
+        builder
+            .exec(upgrade_contract_request(deployer_account_addr, [43u8; 32]))
+            .expect_success()
+            .commit();
+        builder
+            .exec(upgrade_contract_request(deployer_account_addr, [44u8; 32]))
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 56);
+    }
+
+    #[test]
+    fn governance_entry_points(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // 2 of 2 governors, with the minimum delay of an hour
+        let contract_set_governors = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_governors",
+            runtime_args! {
+                "governors" => vec![Key::from(deployer_account_addr), Key::from(producer_account_addr)],
+                "threshold" => 2u8,
+                "delay" => 3_600_000u64
+            }
+        ).build();
+        builder
+            .exec(contract_set_governors)
+            .expect_success()
+            .commit();
+        // The owner could not change the fee directly anymore
+        let contract_set_fee = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "set_fee",
+            runtime_args! {
+                "fee" => 200u64,
+                "effective_at" => Option::<u64>::None
+            }
+        ).build();
+        builder
+            .exec(contract_set_fee)
+            .expect_failure()
+            .commit();
+        // Propose GovernanceAction::SetFee { fee: 200, effective_at: None }
+        let mut action: Vec<u8> = vec![0u8];
+        action.append(&mut 200u64.to_bytes().unwrap());
+        action.append(&mut Option::<u64>::None.to_bytes().unwrap());
+        let contract_propose = ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "propose",
+            runtime_args! {
+                "action" => base16::encode_lower(&action)
+            }
+        ).build();
+        builder
+            .exec(contract_propose)
+            .expect_success()
+            .commit();
+        let execute_proposal = |block_time: u64| ExecuteRequestBuilder::contract_call_by_hash(
+            deployer_account_addr,
+            contract_hash,
+            "execute_proposal",
+            runtime_args! {
+                "proposal_id" => 1u64
+            }
+        ).with_block_time(block_time).build();
+        // Only the proposer approved it, so it could not be executed yet
+        builder
+            .exec(execute_proposal(3_600_000))
+            .expect_failure()
+            .commit();
+        let contract_approve_proposal = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "approve_proposal",
+            runtime_args! {
+                "proposal_id" => 1u64
+            }
+        ).build();
+        builder
+            .exec(contract_approve_proposal)
+            .expect_success()
+            .commit();
+        // It is queued, and could not be executed before the delay passes
+        builder
+            .exec(execute_proposal(3_599_999))
+            .expect_failure()
+            .commit();
+        builder
+            .exec(execute_proposal(3_600_000))
+            .expect_success()
+            .commit();
+        let fee = builder
+            .query(None, Key::Hash(contract_hash.value()), &["fee".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u64>()
+            .expect("should be u64.");
+        assert_eq!(fee, 200u64);
+        // An executed proposal could not be executed or cancelled again
+        builder
+            .exec(execute_proposal(3_600_000))
+            .expect_failure()
+            .commit();
+        let contract_cancel_proposal = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "cancel_proposal",
+            runtime_args! {
+                "proposal_id" => 1u64
+            }
+        ).build();
+        builder
+            .exec(contract_cancel_proposal)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn governance_entry_points_with_error(){
+        // Should fail with InvalidGovernance (54) because the threshold is more than the number of governors, because the delay is less than an hour,
+        // and because a governor is neither an account nor a contract package
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let deployer = Key::from(deployer_account_addr);
+        for (governors, threshold, delay) in [(vec![deployer], 2u8, 3_600_000u64), (vec![deployer], 1u8, 0u64), (vec![deployer], 1u8, 3_599_999u64), (vec![deployer, Key::Balance([1u8; 32])], 1u8, 3_600_000u64)] {
+            let contract_set_governors = ExecuteRequestBuilder::contract_call_by_hash(
+                deployer_account_addr,
+                contract_hash,
+                "set_governors",
+                runtime_args! {
+                    "governors" => governors,
+                    "threshold" => threshold,
+                    "delay" => delay
+                }
+            ).build();
+            builder
+                .exec(contract_set_governors)
+                .expect_failure()
+                .commit();
+            assert_user_error(&builder, 54);
+        }
+    }
+
+    #[test]
+    fn mint_entrypoint_emits_ces_event(){
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let mint_metadata = "{\"name\" : \"Nike Shoes\", \"token_uri\" : \"bafkreibjrxjhy7evb7e5rp6sfyp6rqi2slczpgl3p2pafqhqn7xx226rii\" , \"checksum\" : \"oijepriwguhjpersijf[aopcoisemriguhspiodcpsoeiruhgd\"}".to_string();
+        let contract_mint_request = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "mint",
+            runtime_args! {
+                "amount" => 100u64,
+                "recipient" => Key::from(producer_account_addr),
+                "metadata" => mint_metadata,
+                "price" => 200u64,
+                "comission" => 1234u64
+            },
+        ).build();
+        builder
+            .exec(contract_mint_request)
+            .expect_success()
+            .commit();
+        let ces_version = builder
+            .query(None, Key::Hash(contract_hash.value()), &["__events_ces_version".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<String>()
+            .expect("should be String.");
+        assert_eq!(ces_version, "0.1.0");
+        let events_length = builder
+            .query(None, Key::Hash(contract_hash.value()), &["__events_length".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u32>()
+            .expect("should be u32.");
+        assert_eq!(events_length, 1u32);
+        // The event is stored as the prefixed event name, followed by its typed fields
+        let contract : Contract = builder.get_contract(contract_hash).unwrap();
+        let events_uref = contract.named_keys().get("__events").unwrap().into_uref().unwrap();
+        let event = builder
+            .query_dictionary_item(None, events_uref, "0")
+            .expect("should exist dict")
+            .as_cl_value()
+            .expect("should be cl value")
+            .clone()
+            .into_t::<Bytes>()
+            .expect("should be Bytes");
+        let (name, rem) = String::from_bytes(&event).unwrap();
+        // its parties are keys, which the versioned name tells from the account hashes of the events emitted before version 6
+        assert_eq!(name, "event_MintV2");
+        let (recipient, rem) = Key::from_bytes(rem).unwrap();
+        assert_eq!(recipient, Key::from(producer_account_addr));
+        let (token_id, rem) = u64::from_bytes(rem).unwrap();
+        assert_eq!(token_id, 1u64);
+        let (_holder_id, rem) = u64::from_bytes(rem).unwrap();
+        let (amount, _rem) = u64::from_bytes(rem).unwrap();
+        assert_eq!(amount, 100u64);
+    }
+
+    #[test]
+    fn get_order_entry_point_with_error(){
+        // error : no order is made yet, so there is no order with id 1
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let orders_cnt = builder
+            .query(None, Key::Hash(contract_hash.value()), &["orders_cnt".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u64>()
+            .expect("should be u64.");
+        assert_eq!(orders_cnt, 0u64);
+        let contract_get_order = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "get_order",
+            runtime_args! {
+                "order_id" => 1u64
+            }
+        ).build();
+        builder
+            .exec(contract_get_order)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn migrate_storage_entry_point(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // a new contract stores its records in the current layouts
+        let schema_version = builder
+            .query(None, Key::Hash(contract_hash.value()), &["schema_version".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u32>()
+            .expect("should be u32.");
+        assert_eq!(schema_version, 1u32);
+        // so there is nothing to migrate
+        let contract_migrate_storage = ExecuteRequestBuilder::contract_call_by_hash(
+            account_addr,
+            contract_hash,
+            "migrate_storage",
+            runtime_args! {}
+        ).build();
+        builder
+            .exec(contract_migrate_storage)
+            .expect_success()
+            .commit();
+        // error : only the owner or an operator could call it
+        let contract_migrate_storage = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "migrate_storage",
+            runtime_args! {}
+        ).build();
+        builder
+            .exec(contract_migrate_storage)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn clean_holders_entry_point(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // accounts without a stray entry in holders are skipped
+        let contract_clean_holders = ExecuteRequestBuilder::contract_call_by_hash(
+            account_addr,
+            contract_hash,
+            "clean_holders",
+            runtime_args! {
+                "accounts" => vec![Key::from(publisher_account_addr)]
+            }
+        ).build();
+        builder
+            .exec(contract_clean_holders)
+            .expect_success()
+            .commit();
+        // error : only the owner or an operator could call it
+        let contract_clean_holders = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "clean_holders",
+            runtime_args! {
+                "accounts" => vec![Key::from(publisher_account_addr)]
+            }
+        ).build();
+        builder
+            .exec(contract_clean_holders)
+            .expect_failure()
+            .commit();
+        // error : the batch is larger than 50 accounts
+        let contract_clean_holders = ExecuteRequestBuilder::contract_call_by_hash(
+            account_addr,
+            contract_hash,
+            "clean_holders",
+            runtime_args! {
+                "accounts" => vec![Key::from(publisher_account_addr); 51]
+            }
+        ).build();
+        builder
+            .exec(contract_clean_holders)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn paginated_getter_entry_points(){
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // an account without any list gets an empty page
+        let contract_get_owner_holders = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "get_owner_holders",
+            runtime_args! {
+                "account" => Key::from(publisher_account_addr),
+                "offset" => 0u64,
+                "limit" => 100u64
+            }
+        ).build();
+        builder
+            .exec(contract_get_owner_holders)
+            .expect_success()
+            .commit();
+        // error : the page is larger than 100 ids
+        let contract_get_buyer_orders = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "get_buyer_orders",
+            runtime_args! {
+                "account" => Key::from(publisher_account_addr),
+                "offset" => 0u64,
+                "limit" => 101u64
+            }
+        ).build();
+        builder
+            .exec(contract_get_buyer_orders)
+            .expect_failure()
+            .commit();
+    }
+
+    #[test]
+    fn withdraw_entry_point(){
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        let publisher_purse = builder.get_expected_account(publisher_account_addr).main_purse();
+        // error : an account is paid directly, so it has nothing to withdraw
+        let contract_withdraw = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "withdraw",
+            runtime_args! {
+                "purse_addr" => Key::URef(publisher_purse)
+            }
+        ).build();
+        builder
+            .exec(contract_withdraw)
+            .expect_failure()
+            .commit();
+        // a contract package is a party too, so its lists could be read
+        let contract_get_owner_holders = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "get_owner_holders",
+            runtime_args! {
+                "account" => Key::Hash([7u8; 32]),
+                "offset" => 0u64,
+                "limit" => 100u64
+            }
+        ).build();
+        builder
+            .exec(contract_get_owner_holders)
+            .expect_success()
+            .commit();
+        // error : a uref is neither an account nor a contract package
+        let contract_get_owner_holders = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "get_owner_holders",
+            runtime_args! {
+                "account" => Key::URef(publisher_purse),
+                "offset" => 0u64,
+                "limit" => 100u64
+            }
+        ).build();
+        builder
+            .exec(contract_get_owner_holders)
+            .expect_failure()
+            .commit();
+    }
+
+    /// The producer mints `minted` of a token and the publisher lists all of them, then the customer buys `bought` of them (at the blocktime 1000000), with a quote of the oracle signer `[10u8; 32]`
+    fn sell_token(builder: &mut WasmTestBuilder<InMemoryGlobalState>, contract_hash: ContractHash, minted: u64, bought: u64){
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        list_token(builder, contract_hash, minted, 20);
+        let (quote, signature) = signed_quote(1_000_000_000, 1_000_000, 1, customer_account_addr, &oracle_secret_key);
+        let buy_request = session_buy_request(customer_account_addr, contract_hash, 1, bought, quote, oracle_public_key, signature, 1_000_000);
+        builder.exec(buy_request).expect_success().commit();
+    }
+
+    /// Mints `minted` of a token at `price` for the producer, lists all of it with the publisher (approved id 1), funds the customer
+    /// and adds the [10u8; 32] key as an oracle signer
+    fn list_token(builder: &mut WasmTestBuilder<InMemoryGlobalState>, contract_hash: ContractHash, minted: u64, price: u64){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let mint_metadata = "{\"name\" : \"Nike Shoes\", \"token_uri\" : \"bafkreibjrxjhy7evb7e5rp6sfyp6rqi2slczpgl3p2pafqhqn7xx226rii\" , \"checksum\" : \"oijepriwguhjpersijf\"}".to_string();
+        let requests = vec![
+            ExecuteRequestBuilder::contract_call_by_hash(producer_account_addr, contract_hash, "mint", runtime_args! {
+                "amount" => minted,
+                "recipient" => Key::from(producer_account_addr),
+                "metadata" => mint_metadata,
+                "price" => price,
+                "comission" => 1234u64
+            }).build(),
+            ExecuteRequestBuilder::contract_call_by_hash(publisher_account_addr, contract_hash, "publish_request", runtime_args! {
+                "producer-account" => Key::from(producer_account_addr),
+                "amount" => minted,
+                "holder_id" => 1u64,
+            }).build(),
+            ExecuteRequestBuilder::contract_call_by_hash(producer_account_addr, contract_hash, "approve", runtime_args! {
+                "request_id" => 1u64
+            }).build(),
+            ExecuteRequestBuilder::transfer(account_addr, runtime_args! {
+                "amount" => U512::from(DEFAULT_ACCOUNT_INITIAL_BALANCE),
+                "target" => customer_account_addr,
+                "id" => Option::<u64>::None
+            }).build(),
+            ExecuteRequestBuilder::contract_call_by_hash(account_addr, contract_hash, "add_oracle_signer", runtime_args! {
+                "public_key" => oracle_public_key
+            }).build(),
+        ];
+        for request in requests {
+            builder.exec(request).expect_success().commit();
+        }
+    }
+
+    #[test]
+    fn buy_emits_settlement_and_refunds(){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let treasury_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        list_token(&mut builder, contract_hash, 10, 20);
+        let balance = |builder: &WasmTestBuilder<InMemoryGlobalState>, account: AccountHash| {
+            builder.get_purse_balance(builder.get_expected_account(account).main_purse())
+        };
+        let producer_before = balance(&builder, producer_account_addr);
+        let publisher_before = balance(&builder, publisher_account_addr);
+        let treasury_before = balance(&builder, treasury_account_addr);
+
+        // 4 tokens of price 20 (cents) at 1e9 motes per 100 cents, with 1 CSPR of shipping and 0.5 CSPR of tax, paid from a purse of 5 CSPR
+        let (quote, signature) = signed_quote(1_000_000_000, 1_000_000, 1, customer_account_addr, &oracle_secret_key);
+        let buy_request = session_buy_request_with_prices(customer_account_addr, contract_hash, 1, 4, quote, oracle_public_key, signature, 1_000_000, U512::from(1_000_000_000u64), U512::from(500_000_000u64));
+        builder.exec(buy_request).expect_success().commit();
+
+        // product price : 20 * 1e9 * 4 / 100, the fee (1%) of it goes to the treasury, the comission (12.34%) of the rest to the publisher,
+        // and the rest with the shipping and tax prices to the producer
+        let product_price = 800_000_000u64;
+        let treasury_amount = 8_000_000u64;
+        let producer_amount = 694_267_200u64 + 1_500_000_000u64;
+        let publisher_amount = 97_732_800u64;
+        let refund = U512::from(5_000_000_000u64 - 2_300_000_000u64);
+
+        let contract : Contract = builder.get_contract(contract_hash).unwrap();
+        let events_length = builder
+            .query(None, Key::Hash(contract_hash.value()), &["__events_length".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u32>()
+            .expect("should be u32.");
+        let events_uref = contract.named_keys().get("__events").unwrap().into_uref().unwrap();
+        let event = builder
+            .query_dictionary_item(None, events_uref, &(events_length - 1).to_string())
+            .expect("should exist dict")
+            .as_cl_value()
+            .expect("should be cl value")
+            .clone()
+            .into_t::<Bytes>()
+            .expect("should be Bytes");
+        let (settlement, _rem) = DropLinkedEvent::from_bytes(&event).unwrap();
+        assert_eq!(settlement, DropLinkedEvent::Settlement {
+            order_id: 1,
+            buyer: Key::from(customer_account_addr),
+            approved_id: 1,
+            token_id: 1,
+            amount: 4,
+            product_price,
+            ratio: 1_000_000_000,
+            ratio_decimals: 0,
+            shipping_price: 1_000_000_000,
+            tax_price: 500_000_000,
+            fee: 100,
+            producer: Key::from(producer_account_addr),
+            producer_amount,
+            publisher: Key::from(publisher_account_addr),
+            publisher_amount,
+            treasury_amount,
+            refund,
+        });
+
+        // the shares arrived, and what is left in the purse is transferred back to the buyer
+        assert_eq!(balance(&builder, producer_account_addr), producer_before + producer_amount);
+        assert_eq!(balance(&builder, publisher_account_addr), publisher_before + publisher_amount);
+        assert_eq!(balance(&builder, treasury_account_addr), treasury_before + treasury_amount);
+        let last_result = builder.get_last_exec_results().expect("should have the execution result")[0].clone();
+        let transfers = match ExecutionResult::from(&*last_result) {
+            ExecutionResult::Success { transfers, .. } => transfers,
+            ExecutionResult::Failure { error_message, .. } => panic!("buy failed : {}", error_message),
+        };
+        let refunded = transfers.into_iter().any(|transfer_addr| {
+            match builder.query(None, Key::Transfer(transfer_addr), &[]) {
+                Ok(StoredValue::Transfer(transfer)) => transfer.to == Some(customer_account_addr) && transfer.amount == refund,
+                _ => false,
+            }
+        });
+        assert!(refunded);
+        // the buy does not keep its log or purse in the named keys of the contract anymore
+        assert!(!contract.named_keys().contains_key("_log"));
+        assert!(!contract.named_keys().contains_key("_purse"));
+    }
+
+    #[test]
+    fn buy_with_overflowing_price(){
+        // Should fail with PriceOverflow (73) when the price does not fit in a u64, and with MalformedQuote (26) when the quote has more than 9 decimals
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        list_token(&mut builder, contract_hash, 10, u64::MAX / 2);
+        let (quote, signature) = signed_quote(1_000_000_000, 1_000_000, 1, customer_account_addr, &oracle_secret_key);
+        let buy_request = session_buy_request(customer_account_addr, contract_hash, 1, 2, quote, oracle_public_key.clone(), signature, 1_000_000);
+        builder.exec(buy_request).expect_failure().commit();
+        assert_user_error(&builder, 73);
+
+        let (quote, signature) = signed_quote_with_decimals(1_000_000_000, 30, 1_000_000, 1, customer_account_addr, &oracle_secret_key);
+        let buy_request = session_buy_request(customer_account_addr, contract_hash, 1, 1, quote, oracle_public_key, signature, 1_000_000);
+        builder.exec(buy_request).expect_failure().commit();
+        assert_user_error(&builder, 26);
+    }
+
+    #[test]
+    fn compact_entry_point(){
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let dict = |name: &str| contract.named_keys().get(name).unwrap().into_uref().unwrap();
+        // the customer buys all of them, so the holder and the listing of the producer are exhausted
+        sell_token(&mut builder, contract_hash, 10, 10);
+
+        // error : the owners and the holder ids should be matched by their index
+        let contract_compact = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "compact",
+            runtime_args! {
+                "holder_owners" => vec![Key::from(producer_account_addr)],
+                "holder_ids" => Vec::<u64>::new(),
+                "approved_ids" => Vec::<u64>::new(),
+                "request_ids" => Vec::<u64>::new()
+            }
+        ).build();
+        builder.exec(contract_compact).expect_failure().commit();
+        assert_user_error(&builder, 70);
+        // error : the batch is larger than 50 ids
+        let contract_compact = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "compact",
+            runtime_args! {
+                "holder_owners" => Vec::<Key>::new(),
+                "holder_ids" => Vec::<u64>::new(),
+                "approved_ids" => vec![1u64; 51],
+                "request_ids" => Vec::<u64>::new()
+            }
+        ).build();
+        builder.exec(contract_compact).expect_failure().commit();
+        assert_user_error(&builder, 60);
+        // anyone could compact; the holder of the customer is not exhausted, so it stays
+        let contract_compact = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "compact",
+            runtime_args! {
+                "holder_owners" => vec![Key::from(producer_account_addr), Key::from(customer_account_addr)],
+                "holder_ids" => vec![1u64, 2u64],
+                "approved_ids" => vec![1u64],
+                "request_ids" => vec![1u64]
+            }
+        ).build();
+        builder.exec(contract_compact).expect_success().commit();
+        assert!(query_indexed_list(&builder, dict("owners"), &producer_account_addr.to_string()).is_empty());
+        assert_eq!(query_indexed_list(&builder, dict("owners"), &customer_account_addr.to_string()), vec![2u64]);
+        assert!(query_indexed_list(&builder, dict("producers_approved"), &producer_account_addr.to_string()).is_empty());
+        assert!(query_indexed_list(&builder, dict("publishers_approved"), &publisher_account_addr.to_string()).is_empty());
+        // the removed records could not be used anymore
+        let contract_publish_request = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "publish_request",
+            runtime_args! {
+                "producer-account" => Key::from(producer_account_addr),
+                "amount" => 0u64,
+                "holder_id" => 1u64,
+            }
+        ).build();
+        builder.exec(contract_publish_request).expect_failure().commit();
+        assert_user_error(&builder, 9);
+        let contract_approve = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "approve",
+            runtime_args! {
+                "request_id" => 1u64
+            }
+        ).build();
+        builder.exec(contract_approve).expect_failure().commit();
+        assert_user_error(&builder, 25);
+    }
+
+    #[test]
+    fn compact_keeps_holder_with_live_listing(){
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let dict = |name: &str| contract.named_keys().get(name).unwrap().into_uref().unwrap();
+        // the whole holder is listed twice (approved ids 1 and 2), as approve does not lock the amount
+        list_token(&mut builder, contract_hash, 10, 20);
+        let requests = vec![
+            ExecuteRequestBuilder::contract_call_by_hash(publisher_account_addr, contract_hash, "publish_request", runtime_args! {
+                "producer-account" => Key::from(producer_account_addr),
+                "amount" => 10u64,
+                "holder_id" => 1u64,
+            }).build(),
+            ExecuteRequestBuilder::contract_call_by_hash(producer_account_addr, contract_hash, "approve", runtime_args! {
+                "request_id" => 2u64
+            }).build(),
+        ];
+        for request in requests {
+            builder.exec(request).expect_success().commit();
+        }
+        // the first listing sells the whole holder
+        let (quote, signature) = signed_quote(1_000_000_000, 1_000_000, 1, customer_account_addr, &oracle_secret_key);
+        let buy_request = session_buy_request(customer_account_addr, contract_hash, 1, 10, quote, oracle_public_key.clone(), signature, 1_000_000);
+        builder.exec(buy_request).expect_success().commit();
+
+        // the holder is exhausted, but the second listing is still on it, so it is not compacted
+        let contract_compact = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "compact",
+            runtime_args! {
+                "holder_owners" => vec![Key::from(producer_account_addr)],
+                "holder_ids" => vec![1u64],
+                "approved_ids" => Vec::<u64>::new(),
+                "request_ids" => Vec::<u64>::new()
+            }
+        ).build();
+        builder.exec(contract_compact).expect_success().commit();
+        assert_eq!(query_indexed_list(&builder, dict("owners"), &producer_account_addr.to_string()), vec![1u64]);
+        // error : buying from the second listing fails with NotEnoughAmount (5), as its holder has nothing left
+        let (quote, signature) = signed_quote(1_000_000_000, 1_000_000, 2, customer_account_addr, &oracle_secret_key);
+        let buy_request = session_buy_request(customer_account_addr, contract_hash, 2, 1, quote, oracle_public_key, signature, 1_000_000);
+        builder.exec(buy_request).expect_failure().commit();
+        assert_user_error(&builder, 5);
+    }
+
+    #[test]
+    fn token_provenance_entry_point(){
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let dict = |name: &str| contract.named_keys().get(name).unwrap().into_uref().unwrap();
+        sell_token(&mut builder, contract_hash, 10, 4);
+        // the log of the token has its mint and its sale, in order
+        let entry_ids = query_indexed_list(&builder, dict("token_provenance"), "1");
+        assert_eq!(entry_ids, vec![1u64, 2u64]);
+        let entries: Vec<ProvenanceEntry> = entry_ids.iter().map(|entry_id| {
+            builder
+                .query_dictionary_item(None, dict("provenance"), &entry_id.to_string())
+                .expect("should exist dict")
+                .as_cl_value()
+                .expect("should be cl value")
+                .clone()
+                .into_t::<ProvenanceEntry>()
+                .expect("should be ProvenanceEntry")
+        }).collect();
+        assert_eq!(entries[0].from, None);
+        assert_eq!(entries[0].to, Some(Key::from(producer_account_addr)));
+        assert_eq!((entries[0].quantity, entries[0].reason), (10u64, PROVENANCE_MINT));
+        assert_eq!(entries[1].from, Some(Key::from(producer_account_addr)));
+        assert_eq!(entries[1].to, Some(Key::from(customer_account_addr)));
+        assert_eq!((entries[1].quantity, entries[1].blocktime, entries[1].reason), (4u64, 1_000_000u64, PROVENANCE_BUY));
+        // the log could be read a page at a time
+        let contract_get_token_provenance = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "get_token_provenance",
+            runtime_args! {
+                "token_id" => 1u64,
+                "offset" => 1u64,
+                "limit" => 10u64
+            }
+        ).build();
+        builder
+            .exec(contract_get_token_provenance)
+            .expect_success()
+            .commit();
+        // error : the page is larger than 100 entries
+        let contract_get_token_provenance = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "get_token_provenance",
+            runtime_args! {
+                "token_id" => 1u64,
+                "offset" => 0u64,
+                "limit" => 101u64
+            }
+        ).build();
+        builder
+            .exec(contract_get_token_provenance)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 61);
+    }
+
+    #[test]
+    fn balances_entry_points(){
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let dict = |name: &str| contract.named_keys().get(name).unwrap().into_uref().unwrap();
+        sell_token(&mut builder, contract_hash, 10, 4);
+        // the producer keeps 6 of the token and the customer gets 4, in a new holder
+        let balance = |account: AccountHash| {
+            let balance_key = base16::encode_lower(&casper_types::crypto::blake2b(format!("{}_{}", base16::encode_lower(&account.value()), 1)));
+            builder
+                .query_dictionary_item(None, dict("balances"), &balance_key)
+                .expect("should exist dict")
+                .as_cl_value()
+                .expect("should be cl value")
+                .clone()
+                .into_t::<u64>()
+                .expect("should be u64")
+        };
+        assert_eq!(balance(producer_account_addr), 6u64);
+        assert_eq!(balance(customer_account_addr), 4u64);
+        assert_eq!(query_indexed_list(&builder, dict("token_holders"), "1"), vec![1u64, 2u64]);
+        let holder_owner = |holder_id: u64| {
+            builder
+                .query_dictionary_item(None, dict("holder_owners"), &holder_id.to_string())
+                .expect("should exist dict")
+                .as_cl_value()
+                .expect("should be cl value")
+                .clone()
+                .into_t::<Key>()
+                .expect("should be Key")
+        };
+        assert_eq!(holder_owner(1), Key::from(producer_account_addr));
+        assert_eq!(holder_owner(2), Key::from(customer_account_addr));
+
+        let contract_balance_of = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "balance_of",
+            runtime_args! {
+                "recipient" => Key::from(customer_account_addr),
+                "id" => U256::from(1u64)
+            }
+        ).build();
+        builder
+            .exec(contract_balance_of)
+            .expect_success()
+            .commit();
+        let contract_holders_of = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "holders_of",
+            runtime_args! {
+                "token_id" => 1u64,
+                "offset" => 0u64,
+                "limit" => 10u64
+            }
+        ).build();
+        builder
+            .exec(contract_holders_of)
+            .expect_success()
+            .commit();
+        // error : the page is larger than 100 holders
+        let contract_holders_of = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "holders_of",
+            runtime_args! {
+                "token_id" => 1u64,
+                "offset" => 0u64,
+                "limit" => 101u64
+            }
+        ).build();
+        builder
+            .exec(contract_holders_of)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 61);
+    }
+
+    #[test]
+    fn upgrade_indexes_existing_holders_on_first_touch(){
+        // A contract of version 7, whose holders have no balances, is upgraded with a listed token; trading goes on right away,
+        // and the producer's holder is indexed by its first balance_of and by the buy
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let deployer_account_addr = AccountHash::from(&public_key);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let mut builder = install_wasm_with_args(LEGACY_CONTRACT_WASM, install_args());
+        builder.expect_success();
+        let contract_hash_of = |builder: &WasmTestBuilder<InMemoryGlobalState>| builder
+            .get_expected_account(deployer_account_addr)
+            .named_keys()
+            .get("droplinked_contract")
+            .expect("must have contract hash key")
+            .into_hash()
+            .map(ContractHash::new)
+            .expect("must get contract hash");
+        let legacy_contract_hash = contract_hash_of(&builder);
+        list_token(&mut builder, legacy_contract_hash, 10, 20);
+        builder
+            .exec(upgrade_contract_request(deployer_account_addr, [42u8; 32]))
+            .expect_success()
+            .commit();
+        let contract_hash = contract_hash_of(&builder);
+        assert_ne!(contract_hash, legacy_contract_hash);
+        // The upgrade moved the access uref of the deployer into the contract
+        assert!(builder
+            .get_expected_account(deployer_account_addr)
+            .named_keys()
+            .get("droplinked_access_uref")
+            .is_none());
+        let contract : Contract = builder.get_contract(contract_hash).unwrap();
+        let dict = |name: &str| contract.named_keys().get(name).unwrap().into_uref().unwrap();
+        let schema_version = builder
+            .query(None, Key::Hash(contract_hash.value()), &["schema_version".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u32>()
+            .expect("should be u32.");
+        assert_eq!(schema_version, 1u32);
+        let balance = |builder: &WasmTestBuilder<InMemoryGlobalState>, account: AccountHash| {
+            let balance_key = base16::encode_lower(&casper_types::crypto::blake2b(format!("{}_{}", base16::encode_lower(&account.value()), 1)));
+            builder
+                .query_dictionary_item(None, dict("balances"), &balance_key)
+                .ok()
+                .map(|value| value.as_cl_value().expect("should be cl value").clone().into_t::<u64>().expect("should be u64"))
+        };
+        // The holder of the producer is minted before the index, so it has no balance until it is touched
+        assert_eq!(balance(&builder, producer_account_addr), None);
+        let contract_balance_of = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "balance_of",
+            runtime_args! {
+                "recipient" => Key::from(producer_account_addr),
+                "id" => U256::from(1u64)
+            }
+        ).build();
+        builder
+            .exec(contract_balance_of)
+            .expect_success()
+            .commit();
+        assert_eq!(balance(&builder, producer_account_addr), Some(10u64));
+        assert_eq!(query_indexed_list(&builder, dict("token_holders"), "1"), vec![1u64]);
+        // The listing made before the upgrade is bought from, without any backfill
+        let (quote, signature) = signed_quote(1_000_000_000, 1_000_000, 1, customer_account_addr, &oracle_secret_key);
+        let buy_request = session_buy_request(customer_account_addr, contract_hash, 1, 4, quote, oracle_public_key, signature, 1_000_000);
+        builder.exec(buy_request).expect_success().commit();
+        assert_eq!(balance(&builder, producer_account_addr), Some(6u64));
+        assert_eq!(balance(&builder, customer_account_addr), Some(4u64));
+        assert_eq!(query_indexed_list(&builder, dict("token_holders"), "1"), vec![1u64, 2u64]);
+    }
+
+    #[test]
+    fn cep85_entry_points(){
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let name = builder
+            .query(None, Key::Hash(contract_hash.value()), &["name".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value")
+            .clone()
+            .into_t::<String>()
+            .expect("should be String");
+        assert_eq!(name, "droplinked");
+        assert!(contract.named_keys().contains_key("uri"));
+        sell_token(&mut builder, contract_hash, 10, 4);
+        let calls = vec![
+            ("balance_of_batch", runtime_args! {
+                "recipients" => vec![Key::from(customer_account_addr), Key::from(customer_account_addr)],
+                "ids" => vec![U256::from(1u64), U256::from(2u64)]
+            }),
+            ("supply_of", runtime_args! { "id" => U256::from(1u64) }),
+            ("supply_of_batch", runtime_args! { "ids" => vec![U256::from(1u64), U256::from(2u64)] }),
+            ("total_supply_of", runtime_args! { "id" => U256::from(1u64) }),
+            ("total_supply_of_batch", runtime_args! { "ids" => vec![U256::from(1u64)] }),
+            ("is_non_fungible", runtime_args! { "id" => U256::from(1u64) }),
+            ("uri", runtime_args! { "id" => Some(U256::from(1u64)) }),
+            ("uri", runtime_args! { "id" => Option::<U256>::None }),
+            ("metadata", runtime_args! { "id" => U256::from(1u64) }),
+        ];
+        for (entry_point, args) in calls {
+            let contract_call = ExecuteRequestBuilder::contract_call_by_hash(
+                customer_account_addr,
+                contract_hash,
+                entry_point,
+                args
+            ).build();
+            builder
+                .exec(contract_call)
+                .expect_success()
+                .commit();
+        }
+        // error : the recipients and the ids do not match
+        let contract_balance_of_batch = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "balance_of_batch",
+            runtime_args! {
+                "recipients" => vec![Key::from(customer_account_addr)],
+                "ids" => vec![U256::from(1u64), U256::from(2u64)]
+            }
+        ).build();
+        builder
+            .exec(contract_balance_of_batch)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 72);
+        // error : droplinked token ids are u64
+        let contract_supply_of = ExecuteRequestBuilder::contract_call_by_hash(
+            customer_account_addr,
+            contract_hash,
+            "supply_of",
+            runtime_args! {
+                "id" => U256::from(u64::MAX) + U256::one()
+            }
+        ).build();
+        builder
+            .exec(contract_supply_of)
+            .expect_failure()
+            .commit();
+        assert_user_error(&builder, 71);
+    }
+
+    #[test]
+    fn metadata_json_round_trips(){
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , _contract) = install_contract();
+        // the name and the token uri have quotes, a backslash and control characters, which should be escaped in the json
+        let name = "Nike \"Air\" \\ Max\n\u{1}";
+        let token_uri = "ipfs://\"bafkrei\"\\\t";
+        let checksum = "oijepriwguhjpersijf";
+        let mint_metadata = serde_json::json!({ "name": name, "token_uri": token_uri, "checksum": checksum }).to_string();
+        let contract_mint = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "mint",
+            runtime_args! {
+                "amount" => 10u64,
+                "recipient" => Key::from(producer_account_addr),
+                "metadata" => mint_metadata,
+                "price" => 20u64,
+                "comission" => 1234u64
+            }
+        ).build();
+        builder.exec(contract_mint).expect_success().commit();
+        // the reader session stores the json returned by metadata under the `metadata` named key of the caller
+        let deploy_item = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {
+                ARG_AMOUNT => *DEFAULT_PAYMENT
+            })
+            .with_session_code(PathBuf::from(READER_WASM), runtime_args! {
+                "contract_hash" => Key::from(contract_hash),
+                "id" => U256::from(1u64)
+            })
+            .with_authorization_keys(&[producer_account_addr])
+            .with_address(producer_account_addr)
+            .build();
+        builder.exec(ExecuteRequestBuilder::from_deploy_item(deploy_item).build()).expect_success().commit();
+        let metadata = builder
+            .query(None, Key::Account(producer_account_addr), &["metadata".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<String>()
+            .expect("should be String.");
+        let metadata: serde_json::Value = serde_json::from_str(&metadata).expect("should be a valid json");
+        assert_eq!(metadata, serde_json::json!({ "name": name, "token_uri": token_uri, "checksum": checksum }));
+    }
+
+    /// Serializes a CSPR/USD quote the way the contract decodes it, and signs it (with the casper message prefix) with the oracle key
+    fn signed_quote(ratio: u64, timestamp: u64, approved_id: u64, buyer: AccountHash, oracle_secret_key: &SecretKey) -> (String, String) {
+        signed_quote_with_decimals(ratio, 0, timestamp, approved_id, buyer, oracle_secret_key)
+    }
+
+    /// Same as `signed_quote`, with the given number of decimals for the ratio
+    fn signed_quote_with_decimals(ratio: u64, decimals: u8, timestamp: u64, approved_id: u64, buyer: AccountHash, oracle_secret_key: &SecretKey) -> (String, String) {
+        signed_quote_for_buyer(ratio, decimals, timestamp, approved_id, Some(Key::from(buyer)), oracle_secret_key)
+    }
+
+    /// Same as `signed_quote_with_decimals`, with an optional buyer; a quote for any buyer is encoded the same by the versions before the buyer became a key
+    fn signed_quote_for_buyer(ratio: u64, decimals: u8, timestamp: u64, approved_id: u64, buyer: Option<Key>, oracle_secret_key: &SecretKey) -> (String, String) {
+        let mut quote_bytes = Vec::new();
+        quote_bytes.append(&mut 1u8.to_bytes().unwrap());
+        quote_bytes.append(&mut "CSPR/USD".to_string().to_bytes().unwrap());
+        quote_bytes.append(&mut ratio.to_bytes().unwrap());
+        quote_bytes.append(&mut decimals.to_bytes().unwrap());
+        quote_bytes.append(&mut timestamp.to_bytes().unwrap());
+        quote_bytes.append(&mut (timestamp + 60000).to_bytes().unwrap());
+        quote_bytes.append(&mut Some(approved_id).to_bytes().unwrap());
+        quote_bytes.append(&mut buyer.to_bytes().unwrap());
+        let mut message = b"Casper Message:\n".to_vec();
+        message.extend_from_slice(&quote_bytes);
+        let signature = casper_types::crypto::sign(message, oracle_secret_key, &PublicKey::from(oracle_secret_key));
+        // the contract takes the raw signature, without the tag byte of its bytesrepr
+        (base16::encode_lower(&quote_bytes), base16::encode_lower(&signature.to_bytes().unwrap()[1..]))
+    }
+
+    /// Builds a deploy of the session code, which moves 5 CSPR of the buyer to a new purse and calls buy with it, signed by one oracle signer
+    #[allow(clippy::too_many_arguments)]
+    fn session_buy_request(buyer: AccountHash, contract_hash: ContractHash, approved_id: u64, cnt: u64, quote: String, signer: PublicKey, signature: String, block_time: u64) -> ExecuteRequest{
+        session_buy_request_with_prices(buyer, contract_hash, approved_id, cnt, quote, signer, signature, block_time, U512::zero(), U512::zero())
+    }
+
+    /// Same as `session_buy_request`, with the given shipping and tax prices
+    #[allow(clippy::too_many_arguments)]
+    fn session_buy_request_with_prices(buyer: AccountHash, contract_hash: ContractHash, approved_id: u64, cnt: u64, quote: String, signer: PublicKey, signature: String, block_time: u64, shipping_price: U512, tax_price: U512) -> ExecuteRequest{
+        let deploy_item = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {
+                ARG_AMOUNT => *DEFAULT_PAYMENT
+            })
+            .with_session_code(PathBuf::from(SESSION_WASM), runtime_args! {
+                "cnt" => cnt,
+                "approved_id" => approved_id,
+                "amount" => U512::from(5_000_000_000u64),
+                "shipping_price" => shipping_price,
+                "tax_price" => tax_price,
+                "contract_hash" => Key::from(contract_hash),
+                "quote" => quote,
+                "signers" => vec![signer],
+                "signatures" => vec![signature]
+            })
+            .with_authorization_keys(&[buyer])
+            .with_address(buyer)
+            .build();
+        ExecuteRequestBuilder::from_deploy_item(deploy_item).with_block_time(block_time).build()
+    }
+
+    /// Builds a deploy of the payment session, which moves 2 CSPR from the payer to a new purse and passes it to direct_pay
+    fn session_direct_pay_request(payer: AccountHash, contract_hash: ContractHash, recipient: String, order_reference: Option<String>) -> ExecuteRequest{
+        let mut session_args = runtime_args! {
+            "amount" => U512::from(2_000_000_000u64),
+            "product_price" => U512::from(1_000_000_000u64),
+            "shipping_price" => U512::from(200_000_000u64),
+            "tax_price" => U512::from(100_000_000u64),
+            "contract_hash" => Key::from(contract_hash),
+            "recipient" => recipient
+        };
+        if let Some(order_reference) = order_reference {
+            session_args.insert("order_reference", order_reference).unwrap();
+        }
+        let deploy_item = DeployItemBuilder::new()
+            .with_empty_payment_bytes(runtime_args! {
+                ARG_AMOUNT => *DEFAULT_PAYMENT
+            })
+            .with_session_code(PathBuf::from(PAYMENT_WASM), session_args)
+            .with_authorization_keys(&[payer])
+            .with_address(payer)
+            .build();
+        ExecuteRequestBuilder::from_deploy_item(deploy_item).build()
+    }
+
+    /// Reads the gas budgets of `gas_budgets.txt` (a `<name> <gas>` line per deploy), which hold the costs of the contract before the `StorageContext`
+    ///
+    /// It panics if the file does not exist, as the budgets are only recorded from the baseline contract by `make record-gas-budgets`
+    fn read_gas_budgets() -> BTreeMap<String, u64>{
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(GAS_BUDGETS);
+        let budgets = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("{} is missing, record it with make record-gas-budgets", path.display()));
+        budgets
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(name, gas)| (name.to_string(), gas.trim().parse().expect("should be a gas amount")))
+            .collect()
+    }
+
+    /// Installs the given contract wasm and measures the gas used by mint, publish_request, approve and buy (with the session code)
+    ///
+    /// The quote is for any buyer, so the deploys are the same for the baseline contract, whose quotes scoped the buyer by its account hash
+    fn measure_gas(wasm: &str) -> (WasmTestBuilder<InMemoryGlobalState>, ContractHash, Vec<(&'static str, u64)>){
+        let secret_key = SecretKey::ed25519_from_bytes(DEPLOYER_ACCOUNT).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let account_addr = AccountHash::from(&public_key);
+
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let oracle_secret_key = SecretKey::ed25519_from_bytes([10u8; 32]).unwrap();
+        let oracle_public_key = PublicKey::from(&oracle_secret_key);
+
+        let mut builder = install_wasm_with_args(wasm, install_args());
+        builder.expect_success();
+        let contract_hash = builder
+            .get_expected_account(account_addr)
+            .named_keys()
+            .get("droplinked_contract")
+            .expect("must have contract hash key as part of contract creation")
+            .into_hash()
+            .map(ContractHash::new)
+            .expect("must get contract hash");
+        let mint_metadata = "{\"name\" : \"Nike Shoes\", \"token_uri\" : \"bafkreibjrxjhy7evb7e5rp6sfyp6rqi2slczpgl3p2pafqhqn7xx226rii\" , \"checksum\" : \"oijepriwguhjpersijf\"}".to_string();
+        let contract_mint_request = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "mint",
+            runtime_args! {
+                "amount" => 100u64,
+                "recipient" => Key::from(producer_account_addr),
+                "metadata" => mint_metadata,
+                "price" => 200u64,
+                "comission" => 1234u64
+            },
+        ).build();
+        builder.exec(contract_mint_request).expect_success().commit();
+        let mint_gas = builder.last_exec_gas_cost();
+
+        let contract_publish_request = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "publish_request",
+            runtime_args! {
+                "producer-account" => Key::from(producer_account_addr),
+                "amount" => 10u64,
+                "holder_id" => 1u64,
+            }
+        ).build();
+        builder.exec(contract_publish_request).expect_success().commit();
+        let publish_request_gas = builder.last_exec_gas_cost();
+
+        let contract_approve = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "approve",
+            runtime_args! {
+                "request_id" => 1u64
+            }
+        ).build();
+        builder.exec(contract_approve).expect_success().commit();
+        let approve_gas = builder.last_exec_gas_cost();
+
+        // the buyer should be an account other than the producer, the publisher and the treasury (the deployer), as a purse could not be transferred to itself
+        let fund_customer = ExecuteRequestBuilder::transfer(
+            account_addr,
+            runtime_args! {
+                "amount" => U512::from(DEFAULT_ACCOUNT_INITIAL_BALANCE),
+                "target" => customer_account_addr,
+                "id" => Option::<u64>::None
+            }
+        ).build();
+        builder.exec(fund_customer).expect_success().commit();
+        let contract_add_oracle_signer = ExecuteRequestBuilder::contract_call_by_hash(
+            account_addr,
+            contract_hash,
+            "add_oracle_signer",
+            runtime_args! {
+                "public_key" => oracle_public_key.clone()
+            }
+        ).build();
+        builder.exec(contract_add_oracle_signer).expect_success().commit();
+
+        // the session moves the amount to a new purse, and calls buy with it
+        let block_time = 1_000_000u64;
+        let (quote, signature) = signed_quote_for_buyer(1_000_000_000, 0, block_time, 1, None, &oracle_secret_key);
+        let buy_request = session_buy_request(customer_account_addr, contract_hash, 1, 1, quote, oracle_public_key, signature, block_time);
+        builder.exec(buy_request).expect_success().commit();
+        let buy_gas = builder.last_exec_gas_cost();
+
+        let costs = vec![
+            ("mint", mint_gas.value().as_u64()),
+            ("publish_request", publish_request_gas.value().as_u64()),
+            ("approve", approve_gas.value().as_u64()),
+            ("buy", buy_gas.value().as_u64()),
+        ];
+        (builder, contract_hash, costs)
+    }
+
+    /// Gas used by mint, publish_request, approve and buy (with the session code), checked against the costs of the contract before the `StorageContext`
+    #[test]
+    fn gas_benchmarks(){
+        let secret_key_customer = SecretKey::ed25519_from_bytes(_CUSTOMER_ACCOUNT).unwrap();
+        let public_key_customer = PublicKey::from(&secret_key_customer);
+        let customer_account_addr = AccountHash::from(&public_key_customer);
+
+        let budgets = read_gas_budgets();
+        let (builder, contract_hash, costs) = measure_gas(CONTRACT_WASM);
+        // the StorageContext caches the named keys and records that mint, approve and buy used to look up more than once
+        for (name, cost) in costs.into_iter().filter(|(name, _)| ["mint", "approve", "buy"].contains(name)) {
+            let budget = budgets.get(name).unwrap_or_else(|| panic!("no gas budget for {}, record it with make record-gas-budgets", name));
+            assert!(cost < *budget, "{} used {} gas, not less than the {} gas it used before the StorageContext", name, cost, budget);
+        }
+        let contract : Contract = builder.get_contract(contract_hash).unwrap();
+        let owners_uref = contract.named_keys().get("owners").unwrap().into_uref().unwrap();
+        assert_eq!(query_indexed_list(&builder, owners_uref, &customer_account_addr.to_string()), vec![2u64]);
+    }
+
+    /// Records the costs of the contract before the `StorageContext` (built by `make record-gas-budgets`) as the gas budgets
+    #[test]
+    #[ignore]
+    fn record_gas_budgets(){
+        let (_builder, _contract_hash, costs) = measure_gas(GAS_BASELINE_CONTRACT_WASM);
+        let budgets: String = costs.iter().map(|(name, cost)| format!("{} {}\n", name, cost)).collect();
+        std::fs::write(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(GAS_BUDGETS), budgets).expect("should write the gas budgets");
+    }
+
+    #[test]
+    fn indexer_matches_contract_state(){
+        let secret_key_publisher = SecretKey::ed25519_from_bytes(PUBLISHER_ACCOUNT).unwrap();
+        let public_key_publisher = PublicKey::from(&secret_key_publisher);
+        let publisher_account_addr = AccountHash::from(&public_key_publisher);
+
+        let secret_key_producer = SecretKey::ed25519_from_bytes(PRODUCER_ACCOUNT).unwrap();
+        let public_key_producer = PublicKey::from(&secret_key_producer);
+        let producer_account_addr = AccountHash::from(&public_key_producer);
+
+        let (mut builder, contract_hash , contract) = install_contract();
+        let mint_metadata = "{\"name\" : \"Nike Shoes\", \"token_uri\" : \"bafkreibjrxjhy7evb7e5rp6sfyp6rqi2slczpgl3p2pafqhqn7xx226rii\" , \"checksum\" : \"oijepriwguhjpersijf[aopcoisemriguhspiodcpsoeiruhgd\"}".to_string();
+        // The second mint of the same metadata is added to the first holder (and its event has holder_id 0)
+        for _ in 0..2 {
+            let contract_mint_request = ExecuteRequestBuilder::contract_call_by_hash(
+                producer_account_addr,
+                contract_hash,
+                "mint",
+                runtime_args! {
+                    "amount" => 100u64,
+                    "recipient" => Key::from(producer_account_addr),
+                    "metadata" => mint_metadata.clone(),
+                    "price" => 200u64,
+                    "comission" => 1234u64
+                },
+            ).build();
+            builder.exec(contract_mint_request).expect_success().commit();
+        }
+        // Two publish requests, the first one is approved and then partly disapproved, and the second one is cancelled
+        for publish_amount in [10u64, 5u64] {
+            let contract_publish_request = ExecuteRequestBuilder::contract_call_by_hash(
+                publisher_account_addr,
+                contract_hash,
+                "publish_request",
+                runtime_args! {
+                    "producer-account" => Key::from(producer_account_addr),
+                    "amount" => publish_amount,
+                    "holder_id" => 1u64,
+                }
+            ).build();
+            builder.exec(contract_publish_request).expect_success().commit();
+        }
+        let contract_approve = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "approve",
+            runtime_args! {
+                "request_id" => 1u64
+            }
+        ).build();
+        builder.exec(contract_approve).expect_success().commit();
+        let contract_cancel = ExecuteRequestBuilder::contract_call_by_hash(
+            publisher_account_addr,
+            contract_hash,
+            "cancel_request",
+            runtime_args! {
+                "request_id" => 2u64
+            }
+        ).build();
+        builder.exec(contract_cancel).expect_success().commit();
+        let contract_disapprove = ExecuteRequestBuilder::contract_call_by_hash(
+            producer_account_addr,
+            contract_hash,
+            "disapprove",
+            runtime_args! {
+                "amount" => 2u64,
+                "approved_id" => 1u64,
+                "publisher-account" => Key::from(publisher_account_addr)
+            }
+        ).build();
+        builder.exec(contract_disapprove).expect_success().commit();
+
+        // The events of the last deploy could be read from its execution result
+        let events_uref = contract.named_keys().get("__events").unwrap().into_uref().unwrap();
+        let last_result = builder.get_last_exec_results().expect("should have the execution result")[0].clone();
+        let last_events = events_from_execution_result(&ExecutionResult::from(&*last_result), events_uref).unwrap();
+        assert_eq!(last_events, vec![(6u32, DropLinkedEvent::DisapprovedPublish { approved_id: 1, amount: 2 })]);
+
+        // Fold all the events of the contract, read from the global state
+        let events_length = builder
+            .query(None, Key::Hash(contract_hash.value()), &["__events_length".to_string()])
+            .expect("should be stored value.")
+            .as_cl_value()
+            .expect("should be cl value.")
+            .clone()
+            .into_t::<u32>()
+            .expect("should be u32.");
+        assert_eq!(events_length, 7u32);
+        let mut marketplace = Marketplace::new();
+        for index in 0..events_length {
+            let event = builder
+                .query_dictionary_item(None, events_uref, &index.to_string())
+                .expect("should exist dict")
+                .as_cl_value()
+                .expect("should be cl value")
+                .clone()
+                .into_t::<Bytes>()
+                .expect("should be Bytes");
+            marketplace.apply_bytes(&event).unwrap();
+        }
+
+        // Compare the folded marketplace with the dictionaries of the contract
+        let dict = |name: &str| contract.named_keys().get(name).unwrap().into_uref().unwrap();
+        let token = &marketplace.tokens[&1];
+        let meta = builder
+            .query_dictionary_item(None, dict("metadatas"), "1")
+            .expect("should exist dict")
+            .as_cl_value()
+            .expect("should be cl value")
+            .clone()
+            .into_t::<NftMetadata>()
+            .expect("should be NFTMetadata");
+        assert_eq!((meta.price, meta.comission), (token.price, token.comission));
+        let total_supply = builder
+            .query_dictionary_item(None, dict("total_supply"), "1")
+            .expect("should exist dict")
+            .as_cl_value()
+            .expect("should be cl value")
+            .clone()
+            .into_t::<u64>()
+            .expect("should be u64");
+        assert_eq!(total_supply, token.total_supply);
+        for (holder_id, indexed_holder) in marketplace.holders.iter() {
+            let holder = builder
+                .query_dictionary_item(None, dict("holders"), &holder_id.to_string())
+                .expect("should exist dict")
+                .as_cl_value()
+                .expect("should be cl value")
+                .clone()
+                .into_t::<NFTHolder>()
+                .expect("should be NFTHolder");
+            assert_eq!((holder.amount, holder.token_id), (indexed_holder.amount, indexed_holder.token_id));
+        }
+        for (approved_id, approval) in marketplace.approvals.iter() {
+            let approved_nft = builder
+                .query_dictionary_item(None, dict("approved"), &approved_id.to_string())
+                .expect("should exist dict")
+                .as_cl_value()
+                .expect("should be cl value")
+                .clone()
+                .into_t::<ApprovedNFT>()
+                .expect("should be ApprovedNFT");
+            assert_eq!(approved_nft.holder_id, approval.holder_id);
+            assert_eq!(approved_nft.amount, approval.amount);
+            assert_eq!(approved_nft.owneraccount, approval.producer);
+            assert_eq!(approved_nft.publisheraccount, approval.publisher);
+            assert_eq!(approved_nft.token_id, approval.token_id);
+        }
+        let lists = [
+            ("owners", producer_account_addr, &marketplace.owners),
+            ("producer_requests", producer_account_addr, &marketplace.producer_requests),
+            ("publiser_requests", publisher_account_addr, &marketplace.publisher_requests),
+            ("producers_approved", producer_account_addr, &marketplace.producer_approved),
+            ("publishers_approved", publisher_account_addr, &marketplace.publisher_approved),
+        ];
+        for (name, account, indexed_lists) in lists {
+            let list : BTreeSet<u64> = query_indexed_list(&builder, dict(name), &account.to_string()).into_iter().collect();
+            assert_eq!(&list, &indexed_lists[&Key::from(account)], "{} should match", name);
+        }
+        assert_eq!(marketplace.holders[&1].amount, 200u64);
+        assert_eq!(marketplace.approvals[&1].amount, 8u64);
+        assert!(marketplace.producer_requests[&Key::from(producer_account_addr)].is_empty());
+    }
 }
 
 fn main() {